@@ -1,5 +1,7 @@
+use sedona_ecs::EcsSource;
+
 const GAME_SOURCE: &str = "src/**/*.rs";
 
 fn main() {
-    sedona_ecs::build_ecs(GAME_SOURCE);
+    sedona_ecs::build_ecs_from_sources(&[EcsSource::local(GAME_SOURCE)], &[]);
 }
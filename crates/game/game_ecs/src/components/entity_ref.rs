@@ -7,9 +7,6 @@ pub struct CameraEntityRef(pub Option<Entity>);
 #[component]
 pub struct NodeEntityRef(pub Option<Entity>);
 
-#[component]
-pub struct ChildEntityRef(pub Option<Entity>);
-
 #[component]
 pub struct WeatherEntityRef(pub Option<Entity>);
 
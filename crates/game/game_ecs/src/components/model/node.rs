@@ -1,7 +1,9 @@
+use crate::world::Propagate;
 use glam::Mat4;
 use sedona_ecs::component;
 
-#[component]
+/// A glTF node, whose global transform follows its parent's through `propagate_node_component`.
+#[component(propagate(group = update, after = transform_update))]
 pub struct NodeComponent {
     pub handle: usize,
     pub name: Option<String>,
@@ -51,3 +53,15 @@ impl NodeComponent {
         self.global_transform = *parent_transform * self.local_transform;
     }
 }
+
+impl Propagate for NodeComponent {
+    type Carried = Mat4;
+
+    fn carried(&self) -> Mat4 {
+        self.global_transform
+    }
+
+    fn propagate(&mut self, parent_transform: &Mat4) {
+        self.update_global(parent_transform);
+    }
+}
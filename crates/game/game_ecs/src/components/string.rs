@@ -1,8 +1,5 @@
 use sedona_ecs::component;
 
-#[component]
-pub struct StringId(pub Option<String>);
-
 #[component]
 pub struct Name(pub String);
 
@@ -6,7 +6,6 @@ pub struct TransformComponent {
     pub position: Vec3,
     pub rotation: Vec3,
     pub scale: f32,
-    pub dirty: bool,
 }
 
 impl TransformComponent {
@@ -15,7 +14,6 @@ impl TransformComponent {
             position: position.unwrap_or_default(),
             rotation: rotation.unwrap_or_default(),
             scale: scale.unwrap_or(1.0),
-            dirty: false,
         }
     }
 
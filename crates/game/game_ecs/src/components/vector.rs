@@ -1,9 +1,6 @@
-use crate::components::{CameraEntityRef, ChildEntityRef, MeshComponent, WeatherEntityRef};
+use crate::components::{CameraEntityRef, MeshComponent, WeatherEntityRef};
 use sedona_ecs::component;
 
-#[component]
-pub struct Children(pub Vec<ChildEntityRef>);
-
 #[component]
 pub struct Cameras(pub Vec<CameraEntityRef>);
 
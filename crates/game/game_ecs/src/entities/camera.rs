@@ -1,4 +1,5 @@
-use crate::components::{CameraComponent, StringId, TransformComponent};
+use crate::components::{CameraComponent, TransformComponent};
+use crate::world::StringId;
 use sedona_ecs::entity;
 
 #[entity]
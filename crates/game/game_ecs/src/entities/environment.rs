@@ -1,4 +1,5 @@
-use crate::components::{EnvironmentComponent, StringId, Weathers};
+use crate::components::{EnvironmentComponent, Weathers};
+use crate::world::StringId;
 use sedona_ecs::entity;
 
 #[entity]
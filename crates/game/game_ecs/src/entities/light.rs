@@ -1,4 +1,5 @@
-use crate::components::{LightData, StringId, TransformComponent};
+use crate::components::{LightData, TransformComponent};
+use crate::world::StringId;
 use sedona_ecs::entity;
 
 #[entity]
@@ -1,10 +1,13 @@
-use crate::components::{Children, MeshComponents, NodeCameraComponent, NodeComponent};
+use crate::components::{MeshComponents, NodeCameraComponent, NodeComponent};
+use crate::world::{Children, Parent, StringId};
 use sedona_ecs::entity;
 
 #[entity(serialize=false)]
 pub struct NodeEntity {
+    pub string_id: StringId,
     pub node: NodeComponent,
     pub meshes: MeshComponents,
     pub camera: NodeCameraComponent,
+    pub parent: Parent,
     pub children: Children,
 }
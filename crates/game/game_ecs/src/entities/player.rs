@@ -1,4 +1,5 @@
-use crate::components::{ModelPath, NodeEntityRef, PlayerComponent, StringId, TransformComponent};
+use crate::components::{ModelPath, NodeEntityRef, PlayerComponent, TransformComponent};
+use crate::world::StringId;
 use sedona_ecs::entity;
 
 #[entity]
@@ -1,4 +1,5 @@
-use crate::components::{ModelPath, NodeEntityRef, StringId, TransformComponent};
+use crate::components::{ModelPath, NodeEntityRef, TransformComponent};
+use crate::world::{Children, Parent, StringId};
 use sedona_ecs::entity;
 
 #[entity]
@@ -8,4 +9,6 @@ pub struct PropEntity {
     pub model_path: ModelPath,
     #[serde(skip)]
     pub model: NodeEntityRef,
+    pub parent: Parent,
+    pub children: Children,
 }
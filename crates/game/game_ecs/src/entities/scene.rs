@@ -1,4 +1,4 @@
-use crate::components::{Children, StringId};
+use crate::world::{Children, StringId};
 use sedona_ecs::entity;
 
 #[entity]
@@ -1,4 +1,5 @@
-use crate::components::{SoundData, StringId};
+use crate::components::SoundData;
+use crate::world::StringId;
 use sedona_ecs::entity;
 
 #[entity]
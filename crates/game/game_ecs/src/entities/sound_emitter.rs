@@ -1,4 +1,5 @@
-use crate::components::{SoundEntityRef, StringId, TransformComponent};
+use crate::components::{SoundEntityRef, TransformComponent};
+use crate::world::StringId;
 use sedona_ecs::entity;
 
 #[entity]
@@ -1,4 +1,5 @@
-use crate::components::{StringId, WeatherComponent};
+use crate::components::WeatherComponent;
+use crate::world::StringId;
 use sedona_ecs::entity;
 
 #[entity]
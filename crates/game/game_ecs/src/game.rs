@@ -15,6 +15,7 @@ use sedona_resource::resources::Resources;
 use sedona_resource::ticker::Ticker;
 use sedona_settings::{Settings, value_as, SettingsValue};
 use sedona_window::window::WindowContext;
+use crate::utils::save::{SaveGame, load_save, save_directory, startup_slot};
 use crate::utils::serialize::load_game_packages_into_world;
 
 pub type GameResources = Resources<GameEventQueues>;
@@ -24,6 +25,8 @@ pub struct BigBerg {
     pub world: World,
     pub resources: Option<GameResources>,
     pub event_handlers: GameEventHandlers,
+    /// The save loaded at startup, until its variables are restored after post startup.
+    pub loaded_save: Option<SaveGame>,
 }
 
 impl Game for BigBerg {
@@ -65,7 +68,17 @@ impl Game for BigBerg {
     fn startup(&mut self) {
         let resources = self.resources.as_mut().unwrap();
 
-        load_game_packages_into_world(&mut self.world, resources);
+        if let Some(slot) = startup_slot(&resources.config) {
+            let directory = save_directory(&resources.config);
+            match load_save(&mut self.world, &directory, slot) {
+                Ok(save) => self.loaded_save = Some(save),
+                Err(error) => log::error!("Failed to load save slot {}: {}", slot, error),
+            }
+        }
+
+        if self.loaded_save.is_none() {
+            load_game_packages_into_world(&mut self.world, resources);
+        }
 
         systems_startup(&mut self.event_handlers, resources, &mut self.world);
         resources.ticker.start();
@@ -75,6 +88,10 @@ impl Game for BigBerg {
         let resources = self.resources.as_mut().unwrap();
 
         systems_post_startup(&mut self.event_handlers, resources, &mut self.world);
+
+        if let Some(save) = self.loaded_save.take() {
+            save.restore_variables(&mut resources.variables);
+        }
     }
 
     fn first(&mut self) {
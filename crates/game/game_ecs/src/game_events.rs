@@ -2,7 +2,7 @@ use crate::GameResources;
 use crate::events::KeyEvent;
 use crate::world::World;
 use sedona_ecs::create_event_structs;
-use sedona_event::{EventHandlers, EventQueue};
+use sedona_event::{EventHandlers, EventQueue, HandlerQueue};
 
 create_event_structs!(
     pub GameEvent,
@@ -3,6 +3,7 @@ pub mod entities;
 pub mod events;
 pub mod game;
 pub mod game_events;
+pub mod resources;
 pub mod systems;
 pub mod utils;
 pub mod world;
@@ -12,14 +13,22 @@ pub use game_events::*;
 
 mod tests {
     use crate::components::{
-        ModelPath, NodeEntityRef, PlayerComponent, StringId, TransformComponent,
+        LightData, LightType, ModelPath, NodeComponent, NodeEntityRef, PlayerComponent,
+        TransformComponent, WeatherEntityRef, Weathers,
+    };
+    use crate::entities::{EnvironmentEntity, LightEntity, PlayerEntity, PropEntity, WeatherEntity};
+    use crate::utils::migration::game_migrations;
+    use crate::utils::serialize::{
+        PackageError, load_game_package_into_world, save_game_package,
+    };
+    use crate::world::{
+        Changed, Children, Commands, DiffError, Entity, EntityType, Parent, PrefabError, Query,
+        ReflectError, StringId, Via, With, Without, World, WorldCell, WorldCreate,
     };
-    use crate::entities::PlayerEntity;
-    use crate::utils::serialize::save_game_package;
-    use crate::world::{World, WorldCreate};
 
     #[test]
     fn test_serialization() {
+        let path = std::env::temp_dir().join(format!("package_{}.ron", sedona_ecs::Uuid::new_v4()));
         let mut world = World::default();
 
         world.create(PlayerEntity {
@@ -30,6 +39,929 @@ mod tests {
             model: NodeEntityRef::default(),
         });
 
-        save_game_package(&world, "../../../data/big_berg.ron");
+        save_game_package(&world, path.to_str().unwrap()).unwrap();
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_despawn_generations() {
+        let mut world = World::default();
+        let players: Query<(&Entity, &PlayerComponent)> = Query::new();
+
+        let spawned: Vec<Entity> = (0..3)
+            .map(|_| world.create(PlayerEntity::default()))
+            .collect();
+
+        assert!(world.despawn(spawned[0]));
+        assert!(!world.despawn(spawned[0]));
+        assert!(!world.is_alive(spawned[0]));
+
+        let query = world.with_query(players);
+        assert!(query.get(spawned[0]).is_none());
+
+        for entity in &spawned[1..] {
+            let (found, _) = query.get(*entity).unwrap();
+            assert_eq!(found, entity);
+        }
+
+        let mut loaded: World = ron::from_str(&ron::to_string(&world).unwrap()).unwrap();
+        assert!(loaded.despawn(spawned[1]));
+        let created = loaded.create(PlayerEntity::default());
+        assert!(loaded.is_alive(spawned[2]) && loaded.is_alive(created));
+
+        // A loaded id that comes back gets a new generation, so old handles stay stale.
+        let reused = loaded.create_with_id(spawned[1].id(), PlayerEntity::default());
+        assert_eq!(reused.generation(), spawned[1].generation() + 1);
+        assert!(!loaded.is_alive(spawned[1]));
+        assert!(loaded.is_alive(reused));
+
+        // Merging the data back gives despawned ids fresh ones instead.
+        assert!(loaded.despawn(spawned[2]));
+        let package: World = ron::from_str(&ron::to_string(&world).unwrap()).unwrap();
+        let reassigned = loaded.merge(package);
+        assert!(reassigned.contains_key(&spawned[1].id()) && reassigned.contains_key(&spawned[2].id()));
+        assert!(!loaded.is_alive(spawned[2]));
+    }
+
+    #[test]
+    fn test_commands_apply() {
+        let mut world = World::default();
+        let mut commands = Commands::new();
+
+        let existing = world.create(PlayerEntity::default());
+        let created = commands.create(PlayerEntity::default());
+        commands.despawn(existing);
+        commands.insert(created, PlayerComponent {
+            third_person: true,
+            speed_multiplier: 2.0,
+        });
+
+        assert!(!world.is_alive(created));
+        commands.apply(&mut world);
+        assert!(commands.is_empty());
+        assert!(!world.is_alive(existing));
+
+        let players: Query<(&Entity, &PlayerComponent)> = Query::new();
+        let query = world.with_query(players);
+        let (_, player) = query.get(created).unwrap();
+        assert!(player.third_person);
+    }
+
+    #[test]
+    fn test_changed_filter() {
+        let mut world = World::default();
+        let changed: Query<(&TransformComponent, &NodeEntityRef), Changed<TransformComponent>> =
+            Query::new();
+        let players: Query<(&mut PlayerComponent, &mut TransformComponent)> = Query::new();
+
+        world.create(PlayerEntity::default());
+        let moved = world.create(PlayerEntity::default());
+
+        world.begin_system("reader");
+        assert_eq!(world.with_query(changed).iter().count(), 2);
+
+        world.begin_system("reader");
+        assert_eq!(world.with_query(changed).iter().count(), 0);
+
+        world.begin_system("writer");
+        world.with_query_mut(players).get_mut(moved).unwrap().1.scale = 2.0;
+
+        world.begin_system("reader");
+        let query = world.with_query(changed);
+        let rows: Vec<_> = query.iter().collect();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].0.scale, 2.0);
+    }
+
+    #[test]
+    fn test_optional_and_presence_filters() {
+        let mut world = World::default();
+        let models: Query<(&Entity, Option<&PlayerComponent>), With<ModelPath>> = Query::new();
+        let props: Query<&TransformComponent, Without<PlayerComponent>> = Query::new();
+
+        let player = world.create(PlayerEntity::default());
+        let prop = world.create(PropEntity::default());
+
+        let query = world.with_query(models);
+        assert_eq!(query.iter().count(), 2);
+        assert!(query.get(player).unwrap().1.is_some());
+        assert!(query.get(prop).unwrap().1.is_none());
+
+        let query = world.with_query(props);
+        assert_eq!(query.iter().count(), 1);
+        assert!(query.get(player).is_none());
+        assert!(query.get(prop).is_some());
+    }
+
+    #[test]
+    fn test_spawn_prefab() {
+        let root = std::env::temp_dir().join(format!("prefabs_{}", sedona_ecs::Uuid::new_v4()));
+        std::fs::create_dir_all(root.join("props")).unwrap();
+
+        let prefabs = [
+            (
+                "props/prop.ron",
+                r#"(entity_type: PropEntity, components: { "model_path": ModelPath("assets/models/cube.glb") })"#,
+            ),
+            (
+                "props/cube.ron",
+                r#"(
+                    base: "props/prop.ron",
+                    components: {
+                        "string_id": StringId(Some("prop_cube")),
+                        "transform": (position: (0.0, 5.0, 0.0), rotation: (0.0, 0.0, 0.0), scale: 1.0),
+                    },
+                    children: ["props/ball.ron"],
+                )"#,
+            ),
+            ("props/ball.ron", r#"(base: "props/prop.ron", components: { "string_id": StringId(Some("prop_ball")) })"#),
+            ("props/loop.ron", r#"(base: "props/loop.ron")"#),
+            ("props/broken.ron", r#"(base: "props/cube.ron", children: ["props/missing.ron"])"#),
+        ];
+        for (path, content) in prefabs {
+            std::fs::write(root.join(path), content).unwrap();
+        }
+
+        let mut world = World::default();
+        world.set_prefab_root(&root);
+        let cube = world.spawn_prefab("props/cube.ron").unwrap();
+
+        let props: Query<(&Entity, &StringId, &ModelPath, &TransformComponent)> = Query::new();
+        let query = world.with_query(props);
+        let (_, string_id, model_path, transform) = query.get(cube).unwrap();
+        assert_eq!(string_id.0.as_deref(), Some("prop_cube"));
+        assert_eq!(model_path.0, "assets/models/cube.glb");
+        assert_eq!(transform.position.y, 5.0);
+        assert_eq!(query.iter().count(), 2);
+
+        let ball = world.children(cube)[0];
+        assert_eq!(world.string_id(ball), Some("prop_ball"));
+        assert_eq!(world.parent(ball), Some(cube));
+
+        assert!(matches!(
+            world.spawn_prefab("props/loop.ron"),
+            Err(PrefabError::Cycle { .. })
+        ));
+
+        // The cube and ball spawned before the missing child are despawned again.
+        assert!(matches!(
+            world.spawn_prefab("props/broken.ron"),
+            Err(PrefabError::Io { .. })
+        ));
+        assert_eq!(world.with_query(props).iter().count(), 2);
+
+        assert!(world.despawn_recursive(cube));
+        assert!(!world.is_alive(ball));
+        assert_eq!(world.with_query(props).iter().count(), 0);
+
+        // Weathers have no `Parent`, so they can't be nested.
+        std::fs::write(root.join("weather.ron"), r#"(entity_type: WeatherEntity)"#).unwrap();
+        std::fs::write(root.join("props/rainy.ron"), r#"(base: "props/prop.ron", children: ["weather.ron"])"#).unwrap();
+        assert!(matches!(
+            world.spawn_prefab("props/rainy.ron"),
+            Err(PrefabError::Unlinked { entity_type: EntityType::WeatherEntity, parent_type: EntityType::PropEntity, .. })
+        ));
+        assert!(world.with_query(props).iter().next().is_none());
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn test_component_reflection() {
+        let mut world = World::default();
+        let prop = world.create(PropEntity {
+            model_path: ModelPath(String::from("assets/models/cube.glb")),
+            ..Default::default()
+        });
+
+        let names: Vec<_> = EntityType::PropEntity.components().iter().map(|c| c.name).collect();
+        assert_eq!(names, ["string_id", "transform", "model_path", "model", "parent", "children"]);
+        assert_eq!(EntityType::PropEntity.component("model_path").unwrap().type_name, "ModelPath");
+
+        assert_eq!(
+            world.component_ron(prop, "model_path").unwrap(),
+            r#"("assets/models/cube.glb")"#
+        );
+        world.set_component_ron(prop, "model_path", r#"("assets/models/ball.glb")"#).unwrap();
+        assert_eq!(
+            world.component_ron(prop, "model_path").unwrap(),
+            r#"("assets/models/ball.glb")"#
+        );
+
+        // Trailing characters are rejected without touching the component.
+        assert!(matches!(
+            world.set_component_ron(prop, "model_path", r#"("assets/models/cone.glb") x"#),
+            Err(ReflectError::Value { .. })
+        ));
+        assert_eq!(
+            world.component_ron(prop, "model_path").unwrap(),
+            r#"("assets/models/ball.glb")"#
+        );
+
+        assert!(matches!(
+            world.component_ron(prop, "player_data"),
+            Err(ReflectError::UnknownComponent { .. })
+        ));
+        world.despawn(prop);
+        assert!(matches!(
+            world.set_component_ron(prop, "model_path", "()"),
+            Err(ReflectError::StaleEntity { .. })
+        ));
+    }
+
+    #[test]
+    fn test_merge_remaps_colliding_ids() {
+        let mut package = World::default();
+        let weather = package.create(WeatherEntity::default());
+        let environment = package.create(EnvironmentEntity {
+            weathers: Weathers(vec![WeatherEntityRef(Some(weather))]),
+            ..Default::default()
+        });
+        let package = ron::to_string(&package).unwrap();
+
+        let mut world = World::default();
+        assert!(world.merge(ron::from_str(&package).unwrap()).is_empty());
+        let reassigned = world.merge(ron::from_str(&package).unwrap());
+        assert_eq!(reassigned.len(), 2);
+
+        let weathers: Query<(&Entity, &Weathers)> = Query::new();
+        let query = world.with_query(weathers);
+        let (_, original) = query.get(environment).unwrap();
+        assert_eq!(original.0[0].0, Some(weather));
+
+        let copy = world.entity(reassigned[&environment.id()]).unwrap();
+        let (_, copied) = query.get(copy).unwrap();
+        let copied_weather = copied.0[0].0.unwrap();
+        assert_eq!(copied_weather.id(), reassigned[&weather.id()]);
+        assert!(world.is_alive(copied_weather));
+    }
+
+    #[test]
+    fn test_package_overrides() {
+        let mut world = World::default();
+        let cube = world.create(PropEntity {
+            string_id: StringId(Some(String::from("prop_cube"))),
+            ..Default::default()
+        });
+        let ball = world.create(PropEntity {
+            string_id: StringId(Some(String::from("prop_ball"))),
+            ..Default::default()
+        });
+
+        let path = std::env::temp_dir().join(format!("mod_{}.ron", sedona_ecs::Uuid::new_v4()));
+        std::fs::write(
+            &path,
+            r#"(
+                overrides: [
+                    (string_id: "prop_cube", components: { "model_path": ("assets/models/crate.glb") }),
+                    (string_id: "prop_ball", delete: true),
+                    (string_id: "prop_missing", delete: true),
+                    (string_id: "prop_cube", components: { "model_path": 5 }),
+                ],
+            )"#,
+        )
+        .unwrap();
+
+        let report = load_game_package_into_world(&mut world, &path, &game_migrations()).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        // The second override of the cube fails, so only the first one counts.
+        assert_eq!((report.added, report.patched, report.deleted), (0, 1, 1));
+        assert!(!world.is_alive(ball));
+        assert_eq!(
+            world.component_ron(cube, "model_path").unwrap(),
+            r#"("assets/models/crate.glb")"#
+        );
+    }
+
+    #[test]
+    fn test_package_non_finite_floats() {
+        let mut world = World::default();
+        let prop = world.create(PropEntity {
+            transform: TransformComponent {
+                position: glam::Vec3::new(f32::NAN, f32::INFINITY, f32::NEG_INFINITY),
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+
+        let id = sedona_ecs::Uuid::new_v4();
+        for extension in ["ron", "snap"] {
+            let path = std::env::temp_dir().join(format!("package_{id}.{extension}"));
+            save_game_package(&world, path.to_str().unwrap()).unwrap();
+            let mut loaded = World::default();
+            load_game_package_into_world(&mut loaded, &path, &game_migrations()).unwrap();
+            std::fs::remove_file(&path).unwrap();
+
+            let transforms: Query<&TransformComponent> = Query::new();
+            let position = loaded.with_query(transforms).get(prop).unwrap().position;
+            assert!(position.x.is_nan());
+            assert_eq!((position.y, position.z), (f32::INFINITY, f32::NEG_INFINITY));
+        }
+    }
+
+    #[test]
+    fn test_package_migration() {
+        let path = std::env::temp_dir().join(format!("package_{}.ron", sedona_ecs::Uuid::new_v4()));
+
+        let mut world = World::default();
+        let light = world.create(LightEntity {
+            light_data: LightData {
+                light_type: LightType::SpotLight {
+                    inner_cone_angle: 0.5,
+                    outer_cone_angle: 1.0,
+                },
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+        let prop = world.create(PropEntity {
+            model_path: ModelPath(String::from("assets/models/cube.glb")),
+            ..Default::default()
+        });
+        save_game_package(&world, path.to_str().unwrap()).unwrap();
+
+        let mut loaded = World::default();
+        load_game_package_into_world(&mut loaded, &path, &game_migrations()).unwrap();
+        assert!(loaded.component_ron(light, "light_data").unwrap().contains("SpotLight"));
+
+        // A version 2 package written before `model_paths` was renamed.
+        let content = std::fs::read_to_string(&path).unwrap();
+        std::fs::write(&path, content.replace("model_paths:", "model_files:")).unwrap();
+
+        assert!(matches!(
+            load_game_package_into_world(&mut World::default(), &path, &game_migrations()),
+            Err(PackageError::Deserialize { .. })
+        ));
+
+        let mut migrations = game_migrations();
+        migrations.register(2, |world| world.rename_column("model_files", "model_paths"));
+
+        let mut loaded = World::default();
+        load_game_package_into_world(&mut loaded, &path, &migrations).unwrap();
+        assert_eq!(
+            loaded.component_ron(prop, "model_path").unwrap(),
+            r#"("assets/models/cube.glb")"#
+        );
+
+        std::fs::write(&path, "(version: 4, world: ())").unwrap();
+        assert!(matches!(
+            load_game_package_into_world(&mut World::default(), &path, &migrations),
+            Err(PackageError::UnsupportedVersion { version: 4, latest: 3, .. })
+        ));
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_unversioned_package() {
+        let mut world = World::default();
+        let light = world.create(LightEntity {
+            light_data: LightData {
+                light_type: LightType::SpotLight {
+                    inner_cone_angle: 0.5,
+                    outer_cone_angle: 1.0,
+                },
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+        let prop = world.create(PropEntity {
+            model_path: ModelPath(String::from("assets/models/cube.glb")),
+            ..Default::default()
+        });
+        let scene = world.create(crate::entities::SceneEntity::default());
+
+        // Before packages were versioned they held the world itself, and `Children` held
+        // optional references.
+        let path = std::env::temp_dir().join(format!("package_{}.ron", sedona_ecs::Uuid::new_v4()));
+        let prop_ron = ron::to_string(&prop).unwrap();
+        let mut content = ron::ser::to_string_pretty(&world, Default::default()).unwrap();
+        let scenes = content.find("scene_entities").unwrap();
+        let scene_children = content[scenes..].replacen(
+            "children: [\n            ([]),",
+            &format!("children: [([(Some({prop_ron})), (None)]),"),
+            1,
+        );
+        content.replace_range(scenes.., &scene_children);
+        std::fs::write(&path, content).unwrap();
+
+        let mut loaded = World::default();
+        load_game_package_into_world(&mut loaded, &path, &game_migrations()).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(loaded.component_ron(light, "light_data").unwrap().contains("SpotLight"));
+        assert_eq!(
+            loaded.component_ron(prop, "model_path").unwrap(),
+            r#"("assets/models/cube.glb")"#
+        );
+        assert_eq!(loaded.children(scene), &[prop]);
+    }
+
+    #[test]
+    fn test_binary_snapshot() {
+        let path = std::env::temp_dir().join(format!("autosave_{}.snap", sedona_ecs::Uuid::new_v4()));
+
+        let mut world = World::default();
+        let light = world.create(LightEntity {
+            string_id: StringId(Some(String::from("light_spot"))),
+            light_data: LightData {
+                light_type: LightType::SpotLight {
+                    inner_cone_angle: 0.5,
+                    outer_cone_angle: 1.0,
+                },
+                ..Default::default()
+            },
+            ..Default::default()
+        });
+        let player = world.create(PlayerEntity::default());
+        world.despawn(player);
+        save_game_package(&world, path.to_str().unwrap()).unwrap();
+
+        let mut loaded = World::default();
+        let report = load_game_package_into_world(&mut loaded, &path, &game_migrations()).unwrap();
+        assert_eq!(report.added, 1);
+        assert_eq!(
+            loaded.component_ron(light, "light_data").unwrap(),
+            world.component_ron(light, "light_data").unwrap()
+        );
+        assert_eq!(
+            loaded.component_ron(light, "string_id").unwrap(),
+            r#"(Some("light_spot"))"#
+        );
+
+        let mut bytes = std::fs::read(&path).unwrap();
+        let last = bytes.len() - 1;
+        bytes[last] ^= 0xff;
+        std::fs::write(&path, bytes).unwrap();
+        assert!(matches!(
+            load_game_package_into_world(&mut World::default(), &path, &game_migrations()),
+            Err(PackageError::Snapshot {
+                error: sedona_ecs::snapshot::SnapshotError::ChecksumMismatch { .. },
+                ..
+            })
+        ));
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_save_slots() {
+        let directory = std::env::temp_dir().join(format!("saves_{}", sedona_ecs::Uuid::new_v4()));
+
+        let mut world = World::default();
+        let player = world.create(PlayerEntity::default());
+        world.create(PropEntity {
+            model_path: ModelPath(String::from("models/crate.glb")),
+            ..Default::default()
+        });
+
+        let game_time = sedona_utils::time::Time::new(2025, 6, 1, 18, 30, 0.0);
+        world.resource_mut::<crate::resources::GameTime>().time = Some(game_time);
+        let mut variables = sedona_settings::Settings::new(game_settings::GAME_VARIABLE_KEY_COUNT);
+        variables.insert(game_settings::ACTIVE_PLAYER, sedona_settings::SettingsValue::EntityId(player.id()));
+        variables.insert(game_settings::PLAY_TIME, sedona_settings::SettingsValue::Float(42.0));
+        variables.insert(game_settings::SUN_ROTATION, sedona_settings::SettingsValue::Float(1.0));
+
+        let saved = crate::utils::save::save_game(&world, &variables, &directory, "slot_1").unwrap();
+        assert_eq!(saved.play_time, 42.0);
+        assert_eq!(saved.game_time, Some(game_time));
+        assert_eq!(crate::utils::save::list_saves(&directory), vec![saved.clone()]);
+
+        let mut loaded = World::default();
+        let save = crate::utils::save::load_save(&mut loaded, &directory, "slot_1").unwrap();
+        assert_eq!(save.metadata, saved);
+        assert!(loaded.component_ron(player, "player_data").is_ok());
+        assert_eq!(loaded.resource::<crate::resources::GameTime>().time, Some(game_time));
+
+        // A save written before the latest migration is upgraded like any other package.
+        let world_path = directory.join("slot_1").join("world.ron");
+        let content = std::fs::read_to_string(&world_path).unwrap();
+        let latest = game_migrations().latest_version();
+        let older = content.replacen(&format!("version: {latest}"), "version: 1", 1);
+        assert_ne!(older, content);
+        std::fs::write(&world_path, older).unwrap();
+        let mut migrated = World::default();
+        crate::utils::save::load_save(&mut migrated, &directory, "slot_1").unwrap();
+        assert!(migrated.component_ron(player, "player_data").is_ok());
+
+        let mut restored = sedona_settings::Settings::new(game_settings::GAME_VARIABLE_KEY_COUNT);
+        save.restore_variables(&mut restored);
+        assert_eq!(
+            restored.get(game_settings::ACTIVE_PLAYER),
+            Some(&sedona_settings::SettingsValue::EntityId(player.id()))
+        );
+        assert_eq!(restored.get(game_settings::SUN_ROTATION), None);
+
+        assert!(crate::utils::save::load_save(&mut World::default(), &directory, "missing").is_err());
+
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn test_world_resources() {
+        let mut world = World::default();
+        let camera = world.create(PropEntity::default());
+        assert_eq!(world.resource::<crate::resources::ActiveCamera>().entity, None);
+
+        world.resource_mut::<crate::resources::ActiveCamera>().entity = Some(camera);
+
+        let mut package = World::default();
+        package.create(PropEntity::default());
+        world.merge(package);
+        assert_eq!(world.resource::<crate::resources::ActiveCamera>().entity, Some(camera));
+
+        let mut package = World::default();
+        let package_camera = package.create(PropEntity::default());
+        package.resource_mut::<crate::resources::ActiveCamera>().entity = Some(package_camera);
+        world.merge(package);
+        let merged = world.resource::<crate::resources::ActiveCamera>().entity.unwrap();
+        assert_eq!(merged, package_camera);
+        assert!(world.is_alive(merged));
+
+        let package: World = ron::from_str("(resources: (model_loads: ()))").unwrap();
+        world.merge(package);
+        assert_eq!(world.resource::<crate::resources::ActiveCamera>().entity, Some(merged));
+
+        let package: World = ron::from_str("(resources: (active_camera: ()))").unwrap();
+        world.merge(package);
+        assert_eq!(world.resource::<crate::resources::ActiveCamera>().entity, None);
+
+        world.resource_mut::<crate::resources::ActiveCamera>().entity = Some(merged);
+        let mut package = World::default();
+        *package.resource_mut::<crate::resources::ActiveCamera>() = Default::default();
+        world.merge(package);
+        assert_eq!(world.resource::<crate::resources::ActiveCamera>().entity, None);
+    }
+
+    #[test]
+    fn test_lifecycle_hooks() {
+        let mut world = World::default();
+
+        let child = world.create(crate::entities::NodeEntity {
+            node: crate::components::NodeComponent {
+                handle: 2,
+                ..Default::default()
+            },
+            meshes: crate::components::MeshComponents(vec![crate::components::MeshComponent {
+                handle: 7,
+            }]),
+            ..Default::default()
+        });
+        let root = world.create(crate::entities::NodeEntity {
+            node: crate::components::NodeComponent {
+                handle: 1,
+                ..Default::default()
+            },
+            children: crate::world::Children(vec![child]),
+            ..Default::default()
+        });
+
+        let prop = world.create(PropEntity {
+            model_path: ModelPath(String::from("models/crate.glb")),
+            model: NodeEntityRef(Some(root)),
+            ..Default::default()
+        });
+        assert_eq!(
+            world.resource::<crate::resources::ModelLoads>().pending,
+            vec![prop]
+        );
+
+        assert!(world.despawn(prop));
+        assert!(!world.is_alive(root));
+        assert!(!world.is_alive(child));
+        assert!(!world.despawn(prop));
+
+        let releases = world.resource::<crate::resources::RenderReleases>();
+        assert_eq!(releases.objects, vec![7]);
+        assert_eq!(releases.nodes, vec![1, 2]);
+    }
+
+    #[test]
+    fn test_hierarchy() {
+        let mut world = World::default();
+        let node = |parent: Option<Entity>, children: Vec<Entity>| crate::entities::NodeEntity {
+            parent: crate::world::Parent(parent),
+            children: crate::world::Children(children),
+            ..Default::default()
+        };
+
+        let a = world.create(node(None, vec![]));
+        let d = world.create(node(Some(a), vec![]));
+        let b = world.create(node(None, vec![]));
+        let c = world.create(node(Some(b), vec![]));
+        let root = world.create(node(None, vec![a, b]));
+
+        assert_eq!(world.parent(a), Some(root));
+        assert_eq!(world.parent(d), Some(a));
+        assert_eq!(world.children(root), &[a, b]);
+        assert_eq!(world.children(b), &[c]);
+        assert_eq!(
+            world.iter_descendants_depth_first(root).collect::<Vec<_>>(),
+            vec![a, d, b, c]
+        );
+        assert_eq!(
+            world.iter_descendants_breadth_first(root).collect::<Vec<_>>(),
+            vec![a, b, d, c]
+        );
+
+        assert!(!world.set_parent(root, c));
+        assert!(world.set_parent(d, b));
+        assert!(world.children(a).is_empty());
+        assert_eq!(world.children(b), &[c, d]);
+
+        assert!(world.remove_parent(d));
+        assert!(!world.remove_parent(d));
+        assert_eq!(world.parent(d), None);
+        assert_eq!(world.children(b), &[c]);
+
+        assert!(world.set_parent(d, a));
+        assert!(world.despawn(a));
+        assert_eq!(world.parent(d), None);
+        assert_eq!(world.children(root), &[b]);
+
+        assert!(world.despawn_recursive(root));
+        assert!(!world.is_alive(b));
+        assert!(!world.is_alive(c));
+        assert!(world.is_alive(d));
+    }
+
+    #[test]
+    fn test_node_propagation() {
+        let mut world = World::default();
+        let nodes: Query<&NodeComponent> = Query::new();
+        let nodes_mut: Query<&mut NodeComponent> = Query::new();
+        let changed_parents: Query<&Entity, Changed<Parent>> = Query::new();
+        let changed_children: Query<&Entity, Changed<Children>> = Query::new();
+
+        let translation = |x: f32| glam::Mat4::from_translation(glam::Vec3::new(x, 0.0, 0.0));
+        let node = |parent: Option<Entity>, x: f32| crate::entities::NodeEntity {
+            node: NodeComponent::new(0, None, translation(x)),
+            parent: Parent(parent),
+            ..Default::default()
+        };
+        let x = |world: &World, entity: Entity| world.with_query(nodes).get(entity).unwrap().global_transform.w_axis.x;
+        let propagate = |world: &mut World| {
+            world.begin_system("propagate_node_component");
+            crate::world::propagate_node_component(world);
+        };
+
+        let root = world.create(node(None, 1.0));
+        let child = world.create(node(Some(root), 2.0));
+        let grandchild = world.create(node(Some(child), 4.0));
+        let other = world.create(node(None, 8.0));
+
+        propagate(&mut world);
+        assert_eq!((x(&world, child), x(&world, grandchild)), (3.0, 7.0));
+
+        world.begin_system("transform_update");
+        world.with_query_mut(nodes_mut).get_mut(root).unwrap().update_global(&translation(10.0));
+        propagate(&mut world);
+        assert_eq!((x(&world, root), x(&world, child), x(&world, grandchild)), (11.0, 13.0, 17.0));
+
+        world.begin_system("hierarchy_reader");
+        world.increment_change_tick();
+        assert!(world.set_parent(child, other));
+
+        world.begin_system("hierarchy_reader");
+        assert_eq!(world.with_query(changed_parents).iter().copied().collect::<Vec<_>>(), [child]);
+        let mut parents: Vec<Entity> = world.with_query(changed_children).iter().copied().collect();
+        parents.sort_by_key(|parent| parent.id());
+        let mut expected = [root, other];
+        expected.sort_by_key(|parent| parent.id());
+        assert_eq!(parents, expected);
+
+        propagate(&mut world);
+        assert_eq!((x(&world, child), x(&world, grandchild)), (10.0, 14.0));
+
+        world.increment_change_tick();
+        assert!(world.remove_parent(grandchild));
+
+        world.begin_system("hierarchy_reader");
+        assert_eq!(world.with_query(changed_parents).iter().copied().collect::<Vec<_>>(), [grandchild]);
+        assert_eq!(world.with_query(changed_children).iter().copied().collect::<Vec<_>>(), [child]);
+    }
+
+    #[test]
+    fn test_string_id_index() {
+        let mut world = World::default();
+        let weather = |id: &str| WeatherEntity {
+            string_id: StringId(Some(String::from(id))),
+            ..Default::default()
+        };
+
+        let first = world.create(weather("weather_default"));
+        let duplicate = world.create(weather("weather_default"));
+        let rain = world.create(weather("weather_rain"));
+        assert_eq!(world.find_by_string_id("weather_default"), Some(first));
+        assert_eq!(world.find_by_string_id("weather_rain"), Some(rain));
+        assert_eq!(world.find_by_string_id("weather_snow"), None);
+
+        world.despawn(first);
+        assert_eq!(world.find_by_string_id("weather_default"), Some(duplicate));
+        world.reindex_string_ids();
+        assert_eq!(world.find_by_string_id("weather_default"), Some(duplicate));
+        world.despawn(duplicate);
+        assert_eq!(world.find_by_string_id("weather_default"), None);
+
+        let mut package = World::default();
+        let prop = package.create(PropEntity {
+            string_id: StringId(Some(String::from("prop_crate"))),
+            ..Default::default()
+        });
+        world.merge(package);
+        assert_eq!(world.find_by_string_id("prop_crate"), Some(prop));
+    }
+
+    #[test]
+    fn test_find_camera_per_model() {
+        use crate::systems::player::find_camera;
+
+        let mut world = World::default();
+        let node = |string_id: &str, parent: Option<Entity>| crate::entities::NodeEntity {
+            string_id: StringId(Some(String::from(string_id))),
+            parent: crate::world::Parent(parent),
+            ..Default::default()
+        };
+
+        let first_model = world.create(node("model_player", None));
+        let first_camera = world.create(node("camera_main", Some(first_model)));
+        let second_model = world.create(node("model_player", None));
+        let second_camera = world.create(node("camera_main", Some(second_model)));
+
+        assert_eq!(
+            world.find_all_by_string_id("camera_main").collect::<Vec<_>>(),
+            vec![first_camera, second_camera]
+        );
+        assert_eq!(find_camera("camera_main", first_model, &world), Some(first_camera));
+        assert_eq!(find_camera("camera_main", second_model, &world), Some(second_camera));
+        assert_eq!(find_camera("camera_rear", second_model, &world), None);
+    }
+
+    #[test]
+    fn test_via_queries() {
+        let mut world = World::default();
+        let parents: Query<(&StringId, Via<Parent, &StringId>)> = Query::new();
+        let models: Query<(&Entity, Via<NodeEntityRef, &mut NodeComponent>)> = Query::new();
+        let changed: Query<&NodeComponent, Changed<NodeComponent>> = Query::new();
+        let node = |id: &str, parent: Option<Entity>| crate::entities::NodeEntity {
+            string_id: StringId(Some(String::from(id))),
+            parent: Parent(parent),
+            ..Default::default()
+        };
+        let player = |model: Entity| PlayerEntity {
+            model: NodeEntityRef(Some(model)),
+            ..Default::default()
+        };
+
+        let root = world.create(node("root", None));
+        let child = world.create(node("child", Some(root)));
+        let gone = world.create(node("gone", None));
+        world.despawn(gone);
+
+        let query = world.with_query(parents);
+        let (_, parent_id) = query.get(child).unwrap();
+        assert_eq!(parent_id.into_inner().unwrap().0.as_deref(), Some("root"));
+        assert!(query.get(root).unwrap().1.is_none());
+
+        let first = world.create(player(child));
+        let second = world.create(player(child));
+        let stale = world.create(player(gone));
+
+        world.begin_system("reader");
+        assert_eq!(world.with_query(changed).iter().count(), 2);
+
+        world.begin_system("writer");
+        let mut query = world.with_query_mut(models);
+        let joined: Vec<_> = query
+            .iter_mut()
+            .map(|(entity, node)| (*entity, node.is_some()))
+            .collect();
+        assert_eq!(joined, vec![(first, true), (second, false), (stale, false)]);
+
+        let mut query = world.with_query_mut(models);
+        let (_, node) = query.get_mut(second).unwrap();
+        node.into_inner().unwrap().name = Some(String::from("renamed"));
+
+        world.begin_system("reader");
+        let query = world.with_query(changed);
+        let rows: Vec<_> = query.iter().collect();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].name.as_deref(), Some("renamed"));
+    }
+
+    #[test]
+    fn test_query_chunks() {
+        let mut world = World::default();
+        let transforms: Query<(&Entity, &mut TransformComponent, Option<&PlayerComponent>)> =
+            Query::new();
+        let players: Query<&TransformComponent, With<PlayerComponent>> = Query::new();
+        let changed: Query<&TransformComponent, Changed<TransformComponent>> = Query::new();
+
+        world.create(PlayerEntity::default());
+        world.create(PropEntity::default());
+        world.create(PropEntity::default());
+
+        world.begin_system("reader");
+        assert_eq!(world.with_query(changed).iter().count(), 3);
+
+        world.begin_system("writer");
+        let mut query = world.with_query_mut(transforms);
+        let mut chunks = vec![];
+        for (entities, transforms, players) in query.chunks_mut() {
+            assert_eq!(entities.len(), transforms.len());
+            transforms.iter_mut().for_each(|transform| transform.scale = 2.0);
+            chunks.push((entities.len(), players.map(|players| players.len())));
+        }
+        chunks.sort();
+        assert_eq!(chunks, vec![(1, Some(1)), (2, None)]);
+
+        let query = world.with_query(players);
+        let chunks: Vec<_> = query.chunks().collect();
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(chunks[0].len(), 1);
+
+        world.begin_system("reader");
+        let query = world.with_query(changed);
+        assert_eq!(query.iter().count(), 3);
+        assert!(query.iter().all(|transform| transform.scale == 2.0));
+    }
+
+    #[test]
+    #[should_panic(expected = "use `with_query_mut`")]
+    fn test_cell_refuses_mutable_shared_queries() {
+        let mut world = World::default();
+        world.create(PropEntity::default());
+
+        let mut cell = WorldCell::new(&mut world);
+        let read: Query<&TransformComponent> = Query::new();
+        assert_eq!(cell.with_query(read).iter().count(), 1);
+        let write: Query<&mut TransformComponent> = Query::new();
+        let mut transforms = cell.with_query_mut(write);
+        assert_eq!(transforms.iter_mut().count(), 1);
+
+        let write: Query<&mut TransformComponent> = Query::new();
+        cell.with_query(write);
+    }
+
+    #[test]
+    fn test_world_diff() {
+        let mut world = World::default();
+        let light = world.create(LightEntity {
+            string_id: StringId(Some(String::from("light_spot"))),
+            ..Default::default()
+        });
+        let player = world.create(PlayerEntity::default());
+        let before = world.snapshot().unwrap();
+
+        let prop = world.create(PropEntity {
+            model_path: ModelPath(String::from("models/crate.glb")),
+            ..Default::default()
+        });
+        world.despawn(player);
+        world
+            .set_component_ron(light, "string_id", r#"(Some("light_renamed"))"#)
+            .unwrap();
+        let after = world.snapshot().unwrap();
+
+        let diff = world.diff_since(&before).unwrap();
+        assert_eq!(
+            diff.added.iter().map(|added| added.entity).collect::<Vec<_>>(),
+            vec![prop]
+        );
+        assert_eq!(
+            diff.removed.iter().map(|removed| removed.entity).collect::<Vec<_>>(),
+            vec![player]
+        );
+        assert_eq!(diff.changed.len(), 1);
+        assert_eq!(diff.changed[0].component, "string_id");
+        assert_eq!(
+            diff.added[0].component::<ModelPath>("model_path").unwrap(),
+            ModelPath(String::from("models/crate.glb"))
+        );
+
+        world.revert_diff(&diff).unwrap();
+        assert_eq!(world.snapshot().unwrap(), before);
+        assert!(world.is_alive(player));
+        assert!(!world.is_alive(prop));
+        assert_eq!(world.find_by_string_id("light_spot"), Some(light));
+        assert!(matches!(world.revert_diff(&diff), Err(DiffError::Conflict { .. })));
+
+        // A diff that can't be fully applied leaves the world as it was.
+        let mut corrupt = diff.clone();
+        corrupt.added[0].components.insert(String::from("model_path"), vec![0xff]);
+        assert!(matches!(world.apply_diff(&corrupt), Err(DiffError::Snapshot(_))));
+        corrupt.added[0].components.remove("model_path");
+        assert!(matches!(world.apply_diff(&corrupt), Err(DiffError::MissingComponent { .. })));
+        let mut corrupt = diff.clone();
+        corrupt.changed[0].after = vec![0xff];
+        assert!(matches!(world.apply_diff(&corrupt), Err(DiffError::Snapshot(_))));
+        assert_eq!(world.snapshot().unwrap(), before);
+        assert!(world.is_alive(player));
+
+        world.apply_diff(&diff).unwrap();
+        assert_eq!(world.snapshot().unwrap(), after);
+        assert!(world.is_alive(prop));
+        assert!(world.diff_since(&after).unwrap().is_empty());
     }
 }
@@ -0,0 +1,18 @@
+use crate::world::Entity;
+use sedona_ecs::resource;
+
+/// Entities created with a `ModelPath` after startup, waiting for their model to load.
+#[resource]
+pub struct ModelLoads {
+    #[serde(skip)]
+    pub pending: Vec<Entity>,
+}
+
+/// Renderer handles of despawned nodes, released once the frame's systems have run.
+#[resource]
+pub struct RenderReleases {
+    #[serde(skip)]
+    pub objects: Vec<usize>,
+    #[serde(skip)]
+    pub nodes: Vec<usize>,
+}
@@ -0,0 +1,9 @@
+use crate::world::Entity;
+use sedona_ecs::resource;
+
+#[resource]
+pub struct ActiveCamera {
+    /// Cameras are glTF nodes, which are rebuilt on load rather than saved.
+    #[serde(skip)]
+    pub entity: Option<Entity>,
+}
@@ -0,0 +1,7 @@
+pub mod asset;
+pub mod camera;
+pub mod time;
+
+pub use asset::*;
+pub use camera::*;
+pub use time::*;
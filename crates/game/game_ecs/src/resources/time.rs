@@ -0,0 +1,9 @@
+use sedona_ecs::resource;
+use sedona_utils::time::Time;
+
+/// The in-game date and time, saved with the world.
+#[resource]
+pub struct GameTime {
+    /// Set from `START_TIME` at startup, unless the loaded save or packages hold one.
+    pub time: Option<Time>,
+}
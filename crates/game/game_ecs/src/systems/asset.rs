@@ -1,33 +1,119 @@
 use crate::GameResources;
-use crate::components::{ModelPath, NodeEntityRef, TransformComponent};
+use crate::components::{
+    MeshComponents, ModelPath, NodeComponent, NodeEntityRef, TransformComponent,
+};
+use crate::resources::{ModelLoads, RenderReleases};
 use crate::utils::gltf::load_gltf_scene;
-use crate::world::{Query, World};
-use sedona_ecs::system;
+use crate::world::{Commands, Entity, Query, World};
+use sedona_ecs::{on_create, on_destroy, system};
 
 #[system(group=post_startup)]
 pub fn asset_load_models(
     world: &mut World,
     resources: &mut GameResources,
-    renderables: Query<(&mut ModelPath, &mut TransformComponent, &mut NodeEntityRef)>,
+    commands: &mut Commands,
+    renderables: Query<(&mut NodeEntityRef, &ModelPath, &TransformComponent)>,
 ) {
-    let mut load_requests = Vec::new();
+    for (node_ref, model_path, transform) in world.with_query_mut(renderables).iter_mut() {
+        load_model(node_ref, model_path, transform, commands, resources);
+    }
+
+    world.resource_mut::<ModelLoads>().pending.clear();
+}
 
-    for idx in 0..world.with_query_mut(renderables).len() {
-        if let Some((model_path, transform, _)) = world.with_query_mut(renderables).at_mut(idx) {
-            load_requests.push((idx, model_path.0.clone(), transform.to_matrix()));
+/// Loads the models of entities created since the last frame.
+#[system(group=update)]
+pub fn asset_load_pending_models(
+    world: &mut World,
+    resources: &mut GameResources,
+    commands: &mut Commands,
+    renderables: Query<(&mut NodeEntityRef, &ModelPath, &TransformComponent)>,
+) {
+    let pending = std::mem::take(&mut world.resource_mut::<ModelLoads>().pending);
+
+    for entity in pending {
+        if let Some((node_ref, model_path, transform)) =
+            world.with_query_mut(renderables).get_mut(entity)
+        {
+            load_model(node_ref, model_path, transform, commands, resources);
         }
     }
+}
+
+/// Gives back the renderer handles queued by `release_node`, objects before their nodes.
+#[system(group=last)]
+pub fn asset_release_render_handles(
+    resources: &mut GameResources,
+    render_releases: &mut RenderReleases,
+) {
+    for object in render_releases.objects.drain(..) {
+        if let Err(err) = resources.renderer.remove_render_object(object) {
+            log::warn!("Failed to release render object {object}: {err}");
+        }
+    }
+
+    for node in render_releases.nodes.drain(..) {
+        if let Err(err) = resources.renderer.remove_render_node(node) {
+            log::warn!("Failed to release render node {node}: {err}");
+        }
+    }
+}
 
-    for (idx, path, matrix) in load_requests {
-        match load_gltf_scene(&path, matrix, world, resources) {
-            Ok(root_entity) => {
-                if let Some((_, _, node_ref)) = world.with_query_mut(renderables).at_mut(idx) {
-                    node_ref.0 = Some(root_entity);
-                }
-            }
-            Err(err) => {
-                log::error!("Failed to load model '{path}': {err}");
-            }
+#[on_create(ModelPath)]
+pub fn queue_model_load(world: &mut World, entity: Entity) {
+    world.resource_mut::<ModelLoads>().pending.push(entity);
+}
+
+/// Despawns the node tree of a model along with the entity it belongs to.
+#[on_destroy(NodeEntityRef)]
+pub fn release_model(world: &mut World, entity: Entity) {
+    let models: Query<&NodeEntityRef> = Query::new();
+
+    if let Some(root) = world
+        .with_query(models)
+        .get(entity)
+        .and_then(|model| model.0)
+    {
+        world.despawn_recursive(root);
+    }
+}
+
+/// Queues the renderer handles of a node for release.
+#[on_destroy(NodeEntity)]
+pub fn release_node(world: &mut World, entity: Entity) {
+    let nodes: Query<(&NodeComponent, &MeshComponents)> = Query::new();
+
+    let nodes = world.with_query(nodes);
+    let Some((node, meshes)) = nodes.get(entity) else {
+        return;
+    };
+    let node_handle = node.handle;
+    let mesh_handles: Vec<usize> = meshes.0.iter().map(|mesh| mesh.handle).collect();
+
+    let render_releases = world.resource_mut::<RenderReleases>();
+    render_releases.objects.extend(mesh_handles);
+    render_releases.nodes.push(node_handle);
+}
+
+fn load_model(
+    node_ref: &mut NodeEntityRef,
+    model_path: &ModelPath,
+    transform: &TransformComponent,
+    commands: &mut Commands,
+    resources: &mut GameResources,
+) {
+    if node_ref.0.is_some() {
+        return;
+    }
+
+    let path = &model_path.0;
+
+    match load_gltf_scene(path, transform.to_matrix(), commands, resources) {
+        Ok(root_entity) => {
+            node_ref.0 = Some(root_entity);
+        }
+        Err(err) => {
+            log::error!("Failed to load model '{path}': {err}");
         }
     }
 }
@@ -1,11 +1,11 @@
 use crate::components::{
-    CameraComponent, CameraProjection, NodeCameraComponent, NodeComponent, StringId,
-    TransformComponent,
+    CameraComponent, CameraProjection, NodeCameraComponent, NodeComponent, TransformComponent,
 };
 use crate::entities::CameraEntity;
-use crate::world::{Entity, EntityType, Query, World, WorldCreate};
+use crate::resources::ActiveCamera;
+use crate::world::{Entity, EntityType, Query, StringId, World, WorldCreate};
 use crate::{GameEventHandlers, GameResources};
-use game_settings::{ACTIVE_CAMERA, ACTIVE_PLAYER, FOV};
+use game_settings::{ACTIVE_PLAYER, FOV};
 use glam::Mat4;
 use sedona_ecs::system;
 use sedona_settings::{SHADOW_MAP_RESOLUTION, SettingsValue, value_as};
@@ -43,10 +43,7 @@ pub fn camera_startup(
     //     },
     // });
     //
-    // resources.variables.insert(
-    //     ACTIVE_CAMERA,
-    //     SettingsValue::EntityId(default_camera.id()),
-    // );
+    // world.resource_mut::<ActiveCamera>().entity = Some(default_camera);
 }
 
 #[system(group=window_resized)]
@@ -56,17 +53,17 @@ pub fn camera_window_resized(
     cameras: Query<&mut CameraComponent>,
     node_cameras: Query<&mut NodeCameraComponent>,
 ) {
-    let active_camera_id = match resources.variables.get(ACTIVE_CAMERA) {
-        Some(SettingsValue::EntityId(id)) => *id,
-        _ => return,
+    let Some(active_camera) = world.resource::<ActiveCamera>().entity else {
+        return;
     };
+    let active_camera_id = active_camera.id();
 
     let (width, height) = resources.window.size();
     let aspect_ratio = width as f32 / height as f32;
 
-    if let Some(camera) = world.with_query_mut(cameras).get_mut(active_camera_id) {
+    if let Some(camera) = world.with_query_mut(cameras).get_mut(active_camera) {
         update_projection_matrix(camera, aspect_ratio);
-    } else if let Some(camera) = world.with_query_mut(node_cameras).get_mut(active_camera_id) {
+    } else if let Some(camera) = world.with_query_mut(node_cameras).get_mut(active_camera) {
         if let Some(camera) = &mut camera.camera {
             update_projection_matrix(camera, aspect_ratio);
         } else {
@@ -1,13 +1,17 @@
-use crate::components::{ModelPath, NodeEntityRef, PlayerComponent, StringId, TransformComponent};
+use crate::components::{ModelPath, NodeEntityRef, PlayerComponent, TransformComponent};
 use crate::entities::{PlayerEntity, PropEntity};
-use crate::world::{World, WorldCreate};
+use crate::world::{StringId, World, WorldCreate};
 use crate::{GameEventHandlers, GameResources};
-use game_settings::{ACTIVE_CAMERA, ACTIVE_PLAYER};
+use game_settings::ACTIVE_PLAYER;
 use sedona_ecs::system;
 use sedona_settings::SettingsValue;
 
+const PREFAB_ROOT: &str = "data/prefabs";
+
 #[system(group=pre_startup)]
-pub fn main_pre_startup(world: &mut World, event_handlers: &mut GameEventHandlers) {}
+pub fn main_pre_startup(world: &mut World, event_handlers: &mut GameEventHandlers) {
+    world.set_prefab_root(PREFAB_ROOT);
+}
 
 #[system(group=startup)]
 pub fn main_startup(
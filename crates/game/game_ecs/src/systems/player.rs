@@ -1,14 +1,15 @@
 use crate::world::EntityType;
-use crate::components::{Children, NodeCameraComponent, NodeComponent, NodeEntityRef};
+use crate::components::NodeEntityRef;
 use crate::components::{PlayerComponent, TransformComponent};
 use crate::events::KeyEvent;
+use crate::resources::ActiveCamera;
 use crate::systems::{camera_window_resized, update_projection_matrix};
-use crate::world::{Entity, Query, QueryMutFrom, World};
+use crate::world::{Entity, Query, QueryMutFrom, With, World};
 use crate::{GameEventHandlers, GameResources};
-use game_settings::{ACTIVE_CAMERA, ACTIVE_PLAYER, MOUSE_SENSITIVITY};
+use game_settings::{ACTIVE_PLAYER, MOUSE_SENSITIVITY};
 use glam::{EulerRot, Quat, Vec3};
 use sedona_app::KeyCode;
-use sedona_ecs::{system, Uuid};
+use sedona_ecs::{Uuid, system};
 use sedona_settings::{SettingsValue, value_as};
 
 #[system(group=pre_startup)]
@@ -22,26 +23,35 @@ pub fn player_pre_startup(world: &mut World, event_handlers: &mut GameEventHandl
 pub fn player_post_startup(
     world: &mut World,
     resources: &mut GameResources,
-    players: Query<(&Entity, &PlayerComponent)>,
+    players: Query<&Entity, With<PlayerComponent>>,
 ) {
-    if let Some((entity, _)) = world.with_query(players).iter().next() {
+    if let Some(entity) = world.with_query(players).iter().next() {
         resources
             .variables
             .insert(ACTIVE_PLAYER, SettingsValue::EntityId(entity.id()));
     }
 }
 
-#[system(group=update)]
+/// Run condition for systems that drive the active player.
+pub fn has_active_player(resources: &GameResources) -> bool {
+    active_player_id(resources).is_some()
+}
+
+fn active_player_id(resources: &GameResources) -> Option<Uuid> {
+    match resources.variables.get(ACTIVE_PLAYER) {
+        Some(SettingsValue::EntityId(id)) => Some(*id),
+        _ => None,
+    }
+}
+
+#[system(group=update, run_if=has_active_player)]
 pub fn player_update(
     world: &mut World,
     resources: &mut GameResources,
     players: Query<(&mut PlayerComponent, &mut TransformComponent)>,
     _pq0: Query<&mut PlayerComponent>,
 ) {
-    let active_player_id = match resources.variables.get(ACTIVE_PLAYER) {
-        Some(SettingsValue::EntityId(id)) => *id,
-        _ => return,
-    };
+    let active_player_id = active_player_id(resources).expect("checked by has_active_player");
 
     if let Some((player, transform)) = world.with_query_mut(players).get_mut(active_player_id) {
         let mouse_sensitivity = value_as(resources.config.get(MOUSE_SENSITIVITY)).unwrap_or(0.1);
@@ -88,12 +98,10 @@ pub fn player_update(
             let new_pos = Vec3::from(orig_pos) + move_vec * distance;
             transform.position = new_pos;
         }
-
-        transform.dirty = true;
     }
 }
 
-#[system(group=mouse_wheel)]
+#[system(group=mouse_wheel, run_if=has_active_player)]
 pub fn mod_player_speed(
     delta: f32,
     world: &mut World,
@@ -104,10 +112,7 @@ pub fn mod_player_speed(
     const MAX_SPEED: f32 = 10000.0;
     const STEP: f32 = 0.1;
 
-    let active_player_id = match resources.variables.get(ACTIVE_PLAYER) {
-        Some(SettingsValue::EntityId(id)) => *id,
-        _ => return,
-    };
+    let active_player_id = active_player_id(resources).expect("checked by has_active_player");
 
     if let Some(player) = world.with_query_mut(players).get_mut(active_player_id) {
         let current = player.speed_multiplier.max(MIN_SPEED);
@@ -121,24 +126,11 @@ pub fn mod_player_speed(
     }
 }
 
-#[system(group=manual_event)]
-pub fn _toggle_perspective_query(
-    world: &mut World,
-    resources: &mut GameResources,
-    children: Query<&Children>,
-    players: Query<(&mut PlayerComponent, &mut NodeEntityRef)>,
-    node_info: Query<(&Entity, &NodeComponent, &NodeCameraComponent)>,
-) {
-}
-
 pub fn toggle_perspective(_event: &KeyEvent, world: &mut World, resources: &mut GameResources) {
     let players: Query<(&mut PlayerComponent, &mut NodeEntityRef)> = Query::new();
-    let node_info: Query<(&Entity, &NodeComponent, &NodeCameraComponent)> = Query::new();
-    let children: Query<&Children> = Query::new();
 
-    let active_player_id = match resources.variables.get(ACTIVE_PLAYER) {
-        Some(SettingsValue::EntityId(id)) => *id,
-        _ => return,
+    let Some(active_player_id) = active_player_id(resources) else {
+        return;
     };
 
     let maybe_target = {
@@ -159,42 +151,22 @@ pub fn toggle_perspective(_event: &KeyEvent, world: &mut World, resources: &mut
     };
 
     if let Some((target_name, model_entity)) = maybe_target {
-        if let Some(id) = find_camera(target_name, model_entity, world, node_info, children) {
-            resources
-                .variables
-                .insert(ACTIVE_CAMERA, SettingsValue::EntityId(id));
+        if let Some(camera) = find_camera(target_name, model_entity, world) {
+            world.resource_mut::<ActiveCamera>().entity = Some(camera);
             camera_window_resized(world, resources, Query::new(), Query::new());
         }
     }
 }
 
-fn find_camera(
-    target_name: &str,
-    start_node: Entity,
-    world: &mut World,
-    node_info: Query<(&Entity, &NodeComponent, &NodeCameraComponent)>,
-    children: Query<(&Children)>,
-) -> Option<Uuid> {
-    let mut stack = vec![start_node];
-
-    while let Some(current_entity) = stack.pop() {
-        if let Some((entity, node, cam)) = world.with_query(node_info).get(current_entity.id()) {
-            if let Some(name) = &node.name {
-                if name == target_name {
-                    return Some(entity.id());
-                }
-            }
-        }
+/// Looks up a camera node by name, only accepting one from the model under `model_node`.
+pub(crate) fn find_camera(target_name: &str, model_node: Entity, world: &World) -> Option<Entity> {
+    let camera = world
+        .find_all_by_string_id(target_name)
+        .find(|camera| world.is_ancestor_of(model_node, *camera));
 
-        if let Some(children) = world.with_query(children).get(current_entity.id()) {
-            for child in children.0.iter() {
-                if let Some(entity) = child.0 {
-                    stack.push(entity);
-                }
-            }
-        }
+    if camera.is_none() {
+        log::error!("Could not find a camera with name {target_name}");
     }
 
-    log::error!("Could not find a camera with name {target_name}");
-    None
+    camera
 }
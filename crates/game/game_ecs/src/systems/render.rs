@@ -3,11 +3,10 @@ use crate::components::{
     CameraComponent, CameraProjection, NodeCameraComponent, NodeComponent, TransformComponent,
     WeatherComponent,
 };
+use crate::resources::ActiveCamera;
 use crate::utils::view_uniforms::update_view_uniforms;
 use crate::world::{Entity, EntityType, Query, World};
-use game_settings::ACTIVE_CAMERA;
 use sedona_ecs::system;
-use sedona_settings::{value_as, SettingsValue};
 
 #[system(group=window_resized)]
 pub fn render_window_resized(resources: &mut GameResources) {
@@ -17,23 +16,24 @@ pub fn render_window_resized(resources: &mut GameResources) {
 
 #[system(group=post_update)]
 pub fn render_update(
-    world: &mut World,
+    world: &World,
     resources: &mut GameResources,
+    active_camera: &ActiveCamera,
     cameras: Query<(&CameraComponent, &TransformComponent)>,
     node_cameras: Query<(&NodeCameraComponent, &NodeComponent)>,
 ) {
-    let active_camera_id = match resources.variables.get(ACTIVE_CAMERA) {
-        Some(SettingsValue::EntityId(id)) => *id,
-        _ => return,
+    let Some(active_camera) = active_camera.entity else {
+        return;
     };
+    let active_camera_id = active_camera.id();
 
-    if let Some((camera, transform)) = world.with_query(cameras).get(active_camera_id) {
+    if let Some((camera, transform)) = world.with_query(cameras).get(active_camera) {
         let transform_matrix = transform.to_matrix();
         update_view_uniforms(resources, camera, transform_matrix);
         return;
     }
 
-    if let Some((node_camera, node)) = world.with_query(node_cameras).get(active_camera_id) {
+    if let Some((node_camera, node)) = world.with_query(node_cameras).get(active_camera) {
         if let Some(camera) = &node_camera.camera {
             let transform_matrix = node.global_transform;
             update_view_uniforms(resources, camera, transform_matrix);
@@ -1,21 +1,53 @@
 use crate::events::KeyEvent;
-use crate::utils::serialize::save_game_package;
+use crate::utils::save::{AUTOSAVE_SLOT, QUICKSAVE_SLOT, autosave_interval, save_to_slot};
 use crate::world::World;
 use crate::{GameEventHandlers, GameResources};
+use game_settings::{AUTOSAVE_TIMER, PLAY_TIME};
 use sedona_app::KeyCode;
 use sedona_ecs::system;
+use sedona_settings::{SettingsValue, value_as};
 
 #[system(group=pre_startup)]
 pub fn serialize_pre_startup(world: &mut World, event_handlers: &mut GameEventHandlers) {
     event_handlers.key_down.register(KeyCode::KeyS, save_world);
 }
 
+#[system(group=update)]
+pub fn serialize_update(world: &mut World, resources: &mut GameResources) {
+    let dt = resources.input_state.dt() as f64;
+
+    let play_time = value_as::<f64>(resources.variables.get(PLAY_TIME)).unwrap_or(0.0) + dt;
+    resources
+        .variables
+        .insert(PLAY_TIME, SettingsValue::Float(play_time));
+
+    let interval = autosave_interval(&resources.config) as f64;
+    if interval <= 0.0 {
+        return;
+    }
+
+    let mut timer = value_as::<f64>(resources.variables.get(AUTOSAVE_TIMER)).unwrap_or(0.0) + dt;
+    if timer >= interval {
+        save_to_slot(world, resources, AUTOSAVE_SLOT);
+        timer = 0.0;
+    }
+
+    resources
+        .variables
+        .insert(AUTOSAVE_TIMER, SettingsValue::Float(timer));
+}
+
+#[system(group=quit)]
+pub fn serialize_quit(world: &mut World, resources: &mut GameResources) {
+    save_to_slot(world, resources, AUTOSAVE_SLOT);
+}
+
 pub fn save_world(_event: &KeyEvent, world: &mut World, resources: &mut GameResources) {
     if resources
         .input_state
         .keys_held
         .contains(&KeyCode::ControlLeft)
     {
-        save_game_package(world, "test.ron");
+        save_to_slot(world, resources, QUICKSAVE_SLOT);
     }
 }
@@ -1,7 +1,8 @@
 use crate::GameResources;
+use crate::resources::GameTime;
 use crate::utils::constants::WORLD_UP;
 use game_settings::{
-    GAME_TIME, LATITUDE, MOON_DIRECTION, MOON_PHASE, MOON_ROTATION, SOLAR_TIME, STAR_ROTATION,
+    LATITUDE, MOON_DIRECTION, MOON_PHASE, MOON_ROTATION, SOLAR_TIME, STAR_ROTATION,
     SUN_DIRECTION, SUN_ROTATION,
 };
 use glam::{Quat, Vec3};
@@ -12,10 +13,9 @@ use std::f32::consts::{PI, TAU};
 const AXIAL_TILT: f32 = 23.4397;
 
 #[system(group=tick)]
-pub fn sky_tick(resources: &mut GameResources) {
-    let game_time = match resources.variables.get(GAME_TIME) {
-        Some(SettingsValue::Time(value)) => *value,
-        _ => return,
+pub fn sky_tick(resources: &mut GameResources, game_time: &GameTime) {
+    let Some(game_time) = game_time.time else {
+        return;
     };
 
     let day_of_year = game_time.day_of_year();
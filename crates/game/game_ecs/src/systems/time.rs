@@ -1,9 +1,10 @@
 use crate::events::KeyEvent;
+use crate::resources::GameTime;
 use crate::utils::constants::*;
 use crate::world::World;
 use crate::{GameEventHandlers, GameResources};
 use game_settings::{
-    DAY_SCALE, GAME_TIME, MOON_PHASE, SOLAR_TIME, START_TIME, SUN_DIRECTION, SUN_ROTATION,
+    DAY_SCALE, MOON_PHASE, SOLAR_TIME, START_TIME, SUN_DIRECTION, SUN_ROTATION,
     TIME_SCALE,
 };
 use sedona_app::KeyCode;
@@ -12,7 +13,11 @@ use sedona_settings::{BASE_TICK_RATE_SCALE, SettingsValue, value_as};
 use sedona_utils::time::Time;
 
 #[system(group=startup)]
-pub fn time_startup(resources: &mut GameResources, event_handlers: &mut GameEventHandlers) {
+pub fn time_startup(
+    resources: &mut GameResources,
+    event_handlers: &mut GameEventHandlers,
+    game_time: &mut GameTime,
+) {
     event_handlers.key_down.register(KeyCode::KeyP, print_time);
     event_handlers.key_down.register(KeyCode::KeyQ, pause_time);
     event_handlers
@@ -26,7 +31,11 @@ pub fn time_startup(resources: &mut GameResources, event_handlers: &mut GameEven
         .variables
         .insert(TIME_SCALE, SettingsValue::Float(1.0));
 
-    let game_time = match resources.config.get(START_TIME) {
+    if game_time.time.is_some() {
+        return;
+    }
+
+    let start_time = match resources.config.get(START_TIME) {
         Some(SettingsValue::Time(value)) => *value,
         _ => Time::new(
             DEFAULT_START_YEAR,
@@ -38,30 +47,25 @@ pub fn time_startup(resources: &mut GameResources, event_handlers: &mut GameEven
         ),
     };
 
-    resources
-        .variables
-        .insert(GAME_TIME, SettingsValue::Time(game_time));
+    game_time.time = Some(start_time);
 }
 
 #[system(group=tick)]
-pub fn time_tick(resources: &mut GameResources) {
+pub fn time_tick(resources: &mut GameResources, game_time: &mut GameTime) {
     let tick_duration = value_as(resources.config.get(BASE_TICK_RATE_SCALE)).unwrap_or(1.0) / 60.0;
 
     let day_scale = value_as(resources.config.get(DAY_SCALE)).unwrap_or(DEFAULT_DAY_SCALE);
 
-    let current_time = match resources.variables.get_mut(GAME_TIME) {
-        Some(SettingsValue::Time(value)) => value,
-        None => panic!("Time System: Global variable game_time not found"),
-        _ => panic!("Time System: Global variable game_time is the wrong type"),
+    let Some(current_time) = &mut game_time.time else {
+        panic!("Time System: Game time was not set at startup");
     };
 
     current_time.increment_seconds_f32(tick_duration * day_scale);
 }
 
-fn print_time(_event: &KeyEvent, _world: &mut World, resources: &mut GameResources) {
-    let game_time = match resources.variables.get(GAME_TIME) {
-        Some(SettingsValue::Time(value)) => *value,
-        _ => return,
+fn print_time(_event: &KeyEvent, world: &mut World, resources: &mut GameResources) {
+    let Some(game_time) = world.resource::<GameTime>().time else {
+        return;
     };
     let solar_time = match value_as(resources.variables.get(SOLAR_TIME)) {
         Some(time) => time,
@@ -1,61 +1,43 @@
 use crate::GameResources;
-use crate::components::{Children, NodeComponent, NodeEntityRef, TransformComponent};
-use crate::world::{Entity, Query, World};
+use crate::components::{NodeComponent, NodeEntityRef, TransformComponent};
+use crate::world::{Changed, Entity, Query, World};
 use glam::Mat4;
 use sedona_ecs::system;
 
-#[system(group=update)]
+/// Moves the root node of every entity whose transform changed. The nodes under it follow
+/// in `propagate_node_component`.
+#[system(group=update, after=player_update)]
 pub fn transform_update(
     world: &mut World,
-    resources: &mut GameResources,
-    world_entities: Query<(&TransformComponent, &NodeEntityRef)>,
-    node_entities: Query<(&mut NodeComponent, &mut Children)>,
+    world_entities: Query<(&TransformComponent, &NodeEntityRef), Changed<TransformComponent>>,
+    node_entities: Query<&mut NodeComponent>,
 ) {
-    let dirty_roots: Vec<(Mat4, Entity)> = world
+    let changed_roots: Vec<(Mat4, Entity)> = world
         .with_query(world_entities)
         .iter()
-        .filter_map(|(transform, node_ref)| {
-            if transform.dirty {
-                node_ref.0.map(|e| (transform.to_matrix(), e))
-            } else {
-                None
-            }
-        })
+        .filter_map(|(transform, node_ref)| node_ref.0.map(|e| (transform.to_matrix(), e)))
         .collect();
 
-    for (root_matrix, root_entity) in dirty_roots {
-        propagate_transform(world, root_entity, &root_matrix, resources, node_entities);
+    for (root_matrix, root_entity) in changed_roots {
+        if let Some(node) = world.with_query_mut(node_entities).get_mut(root_entity) {
+            node.update_global(&root_matrix);
+        }
     }
 }
 
-fn propagate_transform(
-    world: &mut World,
-    node_entity: Entity,
-    parent_transform: &Mat4,
+/// Hands the global transform of every node that moved to its render node.
+#[system(group=update, after=propagate_node_component)]
+pub fn node_render_update(
+    world: &World,
     resources: &mut GameResources,
-    node_entities: Query<(&mut NodeComponent, &mut Children)>,
+    changed_nodes: Query<&NodeComponent, Changed<NodeComponent>>,
 ) {
-    let (global_transform, children_entities) = {
-        if let Some((node, children)) = world.with_query_mut(node_entities).get_mut(node_entity.id()) {
-            node.update_global(parent_transform);
-            let queue = &resources.renderer.queue.borrow();
-            let render_node = resources
-                .renderer
-                .resources
-                .objects
-                .get_node_mut(node.handle)
-                .unwrap();
-            render_node.update_model_matrix(node.global_transform, queue);
-
-            let children_entities: Vec<Entity> = children.0.iter().filter_map(|c| c.0).collect();
-
-            (node.global_transform, children_entities)
-        } else {
-            return;
-        }
-    };
+    let queue = &resources.renderer.queue.borrow();
 
-    for child in children_entities {
-        propagate_transform(world, child, &global_transform, resources, node_entities);
+    for node in world.with_query(changed_nodes).iter() {
+        let Ok(render_node) = resources.renderer.resources.objects.get_node_mut(node.handle) else {
+            continue;
+        };
+        render_node.update_model_matrix(node.global_transform, queue);
     }
 }
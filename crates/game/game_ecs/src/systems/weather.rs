@@ -1,14 +1,13 @@
 use crate::world::EntityType;
 use crate::GameResources;
 use crate::components::{
-    EnvironmentComponent, StringId, WeatherComponent, WeatherEntityRef, WeatherLightingProfile,
-    Weathers,
+    EnvironmentComponent, WeatherComponent, WeatherEntityRef, WeatherLightingProfile, Weathers,
 };
 use crate::entities::{EnvironmentEntity, WeatherEntity};
 use crate::utils::conditions::is_night;
 use crate::utils::constants::*;
 use crate::utils::interpolation::*;
-use crate::world::{Entity, Query, World, WorldCreate};
+use crate::world::{Entity, Query, StringId, World, WorldCreate};
 use game_settings::{ACTIVE_ENVIORNMENT, ACTIVE_WEATHER, MOON_DIRECTION, MOON_PHASE, MOON_ROTATION, SOLAR_TIME, STAR_ROTATION, SUN_DIRECTION, SUN_ROTATION};
 use glam::{Quat, Vec3, Vec4};
 use sedona_ecs::system;
@@ -15,6 +15,8 @@ pub const DEFAULT_START_DAY: u8 = 1;
 pub const DEFAULT_START_HOUR: u8 = 6;
 pub const DEFAULT_START_MINUTE: u8 = 0;
 pub const DEFAULT_START_SECOND: f32 = 0.0;
+pub const DEFAULT_SAVE_DIRECTORY: &str = "saves";
+pub const DEFAULT_AUTOSAVE_INTERVAL: f32 = 300.0; // 5 Minutes
 pub const MIDNIGHT: f32 = 0.0;
 pub const NIGHT: f32 = 0.125;
 pub const DAWN: f32 = 0.2225;
@@ -1,10 +1,8 @@
 use crate::GameResources;
-use crate::components::{
-    ChildEntityRef, Children, MeshComponents, NodeCameraComponent, NodeComponent,
-};
+use crate::components::{MeshComponents, NodeCameraComponent, NodeComponent};
 use crate::entities::NodeEntity;
 use crate::utils::gltf::{process_camera, process_meshes};
-use crate::world::{Entity, World, WorldCreate};
+use crate::world::{Children, Commands, Entity, Parent, StringId};
 use glam::{Mat4, Quat, Vec3};
 use gltf::import;
 use std::error::Error;
@@ -13,7 +11,7 @@ use std::path::Path;
 pub fn load_gltf_scene<P: AsRef<Path>>(
     path: P,
     transform: Mat4,
-    world: &mut World,
+    commands: &mut Commands,
     resources: &mut GameResources,
 ) -> Result<Entity, Box<dyn Error>> {
     // glTF types are prefixed with g_ to distinguish them from game types.
@@ -31,14 +29,16 @@ pub fn load_gltf_scene<P: AsRef<Path>>(
     let mut children = Vec::new();
     for g_node in g_scene.nodes() {
         children.push(process_node_tree(
-            &g_node, &g_buffers, &g_images, transform, world, resources,
+            &g_node, &g_buffers, &g_images, transform, commands, resources,
         ));
     }
 
-    let node = world.create(NodeEntity {
+    let node = commands.create(NodeEntity {
+        string_id: StringId::default(),
         node: node_component,
         meshes: MeshComponents::default(),
         camera: NodeCameraComponent::default(),
+        parent: Parent::default(),
         children: Children(children),
     });
 
@@ -50,9 +50,9 @@ fn process_node_tree(
     g_buffers: &[gltf::buffer::Data],
     g_images: &[gltf::image::Data],
     parent_transform: Mat4,
-    world: &mut World,
+    commands: &mut Commands,
     resources: &mut GameResources,
-) -> ChildEntityRef {
+) -> Entity {
     let local_transform = match g_node.transform() {
         gltf::scene::Transform::Matrix { matrix } => Mat4::from_cols_array_2d(&matrix),
         gltf::scene::Transform::Decomposed {
@@ -82,7 +82,7 @@ fn process_node_tree(
             g_buffers,
             g_images,
             global_transform,
-            world,
+            commands,
             resources,
         ));
     }
@@ -106,12 +106,15 @@ fn process_node_tree(
         }
     };
 
-    let node = world.create(NodeEntity {
+    // Camera nodes are looked up by name, so they go in the world's StringId index.
+    let string_id = g_node.camera().and(g_node.name()).map(str::to_string);
+
+    commands.create(NodeEntity {
+        string_id: StringId(string_id),
         node: node_component,
         meshes: MeshComponents(meshes),
         camera,
+        parent: Parent::default(),
         children: Children(children),
-    });
-
-    ChildEntityRef(Some(node))
+    })
 }
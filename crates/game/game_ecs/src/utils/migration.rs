@@ -0,0 +1,191 @@
+use crate::world::{Children, Entity};
+use ron::value::RawValue;
+use serde::de::{DeserializeOwned, Deserializer, MapAccess, Visitor};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::Formatter;
+
+/// The first versioned package layout. Packages without a `version` are version 0.
+pub const FIRST_PACKAGE_VERSION: u32 = 1;
+
+/// Upgrades the world of a package from one version to the next.
+pub type Migration = fn(&mut RawWorld) -> Result<(), String>;
+
+/// Fields not holding an entity table.
+const WORLD_FIELDS: [&str; 2] = ["resources", "index_lookup"];
+
+/// The fields of a RON struct, each kept as the RON it was written as.
+#[derive(Default)]
+struct RawStruct(BTreeMap<String, Box<RawValue>>);
+
+impl RawStruct {
+    fn to_ron(&self) -> String {
+        let fields: Vec<String> = self
+            .0
+            .iter()
+            .map(|(name, value)| format!("{}:{}", name, value.get_ron()))
+            .collect();
+        format!("({})", fields.join(","))
+    }
+}
+
+impl<'de> Deserialize<'de> for RawStruct {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct RawStructVisitor;
+
+        impl<'de> Visitor<'de> for RawStructVisitor {
+            type Value = RawStruct;
+
+            fn expecting(&self, f: &mut Formatter) -> std::fmt::Result {
+                write!(f, "a struct")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<RawStruct, A::Error> {
+                let mut fields = BTreeMap::new();
+                while let Some((name, value)) = map.next_entry()? {
+                    fields.insert(name, value);
+                }
+                Ok(RawStruct(fields))
+            }
+        }
+
+        deserializer.deserialize_struct("RawStruct", &[], RawStructVisitor)
+    }
+}
+
+/// The world of a package as migrations edit it, before it is read into a `World`.
+///
+/// Only the layout of the world is loose: its entity tables and their columns. A migration
+/// reads the values of a column as the types they had in the package's version, so enum
+/// variants, options, map keys and non-finite floats keep their meaning.
+#[derive(Default, Deserialize)]
+#[serde(transparent)]
+pub struct RawWorld(RawStruct);
+
+impl RawWorld {
+    /// Renames the column `from` of every table holding one.
+    pub fn rename_column(&mut self, from: &str, to: &str) -> Result<(), String> {
+        self.edit_tables(|table| {
+            if let Some(column) = table.0.remove(from) {
+                table.0.insert(to.to_string(), column);
+            }
+            Ok(())
+        })
+    }
+
+    /// Replaces each value of the column `column`, in every table holding one, with what
+    /// `edit` makes of it.
+    pub fn map_column<T, U>(&mut self, column: &str, mut edit: impl FnMut(T) -> U) -> Result<(), String>
+    where
+        T: DeserializeOwned,
+        U: Serialize,
+    {
+        self.edit_tables(|table| {
+            let Some(values) = table.0.get_mut(column) else {
+                return Ok(());
+            };
+
+            let old: Vec<T> = values.into_rust().map_err(|error| format!("{column}: {error}"))?;
+            let new: Vec<U> = old.into_iter().map(&mut edit).collect();
+            *values = RawValue::from_rust(&new).map_err(|error| format!("{column}: {error}"))?;
+            Ok(())
+        })
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.0.0.is_empty()
+    }
+
+    pub(crate) fn remove(&mut self, field: &str) {
+        self.0.0.remove(field);
+    }
+
+    pub(crate) fn to_ron(&self) -> String {
+        self.0.to_ron()
+    }
+
+    fn edit_tables(
+        &mut self,
+        mut edit: impl FnMut(&mut RawStruct) -> Result<(), String>,
+    ) -> Result<(), String> {
+        for (name, value) in self.0.0.iter_mut() {
+            if WORLD_FIELDS.contains(&name.as_str()) {
+                continue;
+            }
+
+            let mut table: RawStruct =
+                value.into_rust().map_err(|error| format!("{name}: {error}"))?;
+            edit(&mut table)?;
+            *value = RawValue::from_boxed_ron(table.to_ron().into_boxed_str())
+                .map_err(|error| format!("{name}: {error}"))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Migrations keyed by the version they upgrade from.
+#[derive(Default)]
+pub struct MigrationRegistry {
+    migrations: BTreeMap<u32, Migration>,
+}
+
+impl MigrationRegistry {
+    /// Registers the migration from `version` to `version + 1`.
+    pub fn register(&mut self, version: u32, migration: Migration) -> &mut Self {
+        self.migrations.insert(version, migration);
+        self
+    }
+
+    /// The version packages are saved in, one past the newest migration.
+    pub fn latest_version(&self) -> u32 {
+        self.migrations
+            .keys()
+            .next_back()
+            .map_or(FIRST_PACKAGE_VERSION, |version| version + 1)
+    }
+
+    /// Runs every migration from `version` up to the latest version, in order.
+    ///
+    /// On failure, returns the version that could not be upgraded and why.
+    pub fn migrate(&self, world: &mut RawWorld, version: u32) -> Result<(), (u32, String)> {
+        for version in version..self.latest_version() {
+            let Some(migration) = self.migrations.get(&version) else {
+                return Err((version, String::from("no migration registered")));
+            };
+
+            migration(world).map_err(|message| (version, message))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The migrations of the game's packages and saves.
+///
+/// Register one here whenever a component change would stop older packages from loading.
+pub fn game_migrations() -> MigrationRegistry {
+    let mut migrations = MigrationRegistry::default();
+    migrations.register(0, add_version);
+    migrations.register(1, unwrap_child_refs);
+    migrations
+}
+
+/// Version 1 only wrapped the world in a versioned package.
+fn add_version(_world: &mut RawWorld) -> Result<(), String> {
+    Ok(())
+}
+
+/// `Children` as versions 0 and 1 held it, a list of optional references.
+#[derive(Deserialize)]
+struct ChildRefs(Vec<ChildRef>);
+
+#[derive(Deserialize)]
+struct ChildRef(Option<Entity>);
+
+/// The built-in `Children` lists the entities themselves.
+fn unwrap_child_refs(world: &mut RawWorld) -> Result<(), String> {
+    world.map_column("children", |ChildRefs(children)| {
+        Children(children.into_iter().filter_map(|ChildRef(child)| child).collect())
+    })
+}
@@ -2,6 +2,8 @@ pub mod conditions;
 pub mod constants;
 pub mod gltf;
 pub mod interpolation;
+pub mod migration;
+pub mod save;
 pub mod serialize;
 pub mod vector_math;
 pub mod view_uniforms;
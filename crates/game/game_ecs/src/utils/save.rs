@@ -0,0 +1,222 @@
+use crate::GameResources;
+use crate::resources::GameTime;
+use crate::utils::constants::{DEFAULT_AUTOSAVE_INTERVAL, DEFAULT_SAVE_DIRECTORY};
+use crate::utils::migration::game_migrations;
+use crate::utils::serialize::{PackageError, load_game_package_into_world, save_game_package};
+use crate::world::World;
+use game_settings::{
+    ACTIVE_PLAYER, ACTIVE_WEATHER, AUTOSAVE_INTERVAL, LOAD_SLOT, PLAY_TIME,
+    SAVE_DIRECTORY, TIME_SCALE,
+};
+use ron::ser::{PrettyConfig, to_string_pretty};
+use sedona_settings::{Settings, SettingsValue, value_as};
+use sedona_utils::time::Time;
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::fs::{create_dir_all, read_dir, read_to_string, write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Slot written by the autosave timer and on quit.
+pub const AUTOSAVE_SLOT: &str = "autosave";
+
+/// Slot written by the quicksave key.
+pub const QUICKSAVE_SLOT: &str = "quicksave";
+
+/// Variables stored with a save. Every other variable is rebuilt by the startup systems, and
+/// state kept in world resources, like the `GameTime`, is saved with the world.
+pub const SAVED_VARIABLES: [u64; 4] = [ACTIVE_PLAYER, ACTIVE_WEATHER, PLAY_TIME, TIME_SCALE];
+
+const METADATA_FILE: &str = "save.ron";
+/// A versioned RON package rather than a snapshot, so `game_migrations` upgrade old saves.
+const WORLD_FILE: &str = "world.ron";
+
+/// What a save slot shows before it is loaded.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SaveMetadata {
+    pub slot: String,
+    /// Seconds since the Unix epoch when the save was written.
+    pub timestamp: u64,
+    /// Seconds played, across every session that led to this save.
+    pub play_time: f64,
+    pub game_time: Option<Time>,
+}
+
+/// The contents of a slot's `save.ron`. The world is stored next to it as a package.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SaveGame {
+    pub metadata: SaveMetadata,
+    pub variables: BTreeMap<u64, SettingsValue>,
+}
+
+impl SaveGame {
+    /// Writes the saved variables back, replacing the values set by the startup systems.
+    pub fn restore_variables(&self, variables: &mut Settings) {
+        for (key, value) in &self.variables {
+            variables.insert(*key, value.clone());
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum SaveError {
+    Io { path: PathBuf, error: std::io::Error },
+    Parse { path: PathBuf, error: ron::error::SpannedError },
+    Serialize { path: PathBuf, error: ron::Error },
+    Package(PackageError),
+}
+
+impl Display for SaveError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SaveError::Io { path, error } => {
+                write!(f, "failed to access save {}: {}", path.display(), error)
+            }
+            SaveError::Parse { path, error } => {
+                write!(f, "failed to parse save {}: {}", path.display(), error)
+            }
+            SaveError::Serialize { path, error } => {
+                write!(f, "failed to serialize save {}: {}", path.display(), error)
+            }
+            SaveError::Package(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl std::error::Error for SaveError {}
+
+impl From<PackageError> for SaveError {
+    fn from(error: PackageError) -> Self {
+        SaveError::Package(error)
+    }
+}
+
+/// The directory holding every save slot, from `SAVE_DIRECTORY`.
+pub fn save_directory(config: &Settings) -> PathBuf {
+    match config.get(SAVE_DIRECTORY) {
+        Some(SettingsValue::String(directory)) => PathBuf::from(directory),
+        _ => PathBuf::from(DEFAULT_SAVE_DIRECTORY),
+    }
+}
+
+/// Writes `world` and the `SAVED_VARIABLES` into `directory/slot`, replacing what was there.
+///
+/// The world goes first, so a slot with metadata always has a complete world.
+pub fn save_game(
+    world: &World,
+    variables: &Settings,
+    directory: &Path,
+    slot: &str,
+) -> Result<SaveMetadata, SaveError> {
+    let slot_path = directory.join(slot);
+    let io_error = |path: &Path| {
+        let path = path.to_path_buf();
+        move |error| SaveError::Io { path, error }
+    };
+
+    create_dir_all(&slot_path).map_err(io_error(&slot_path))?;
+
+    let world_path = slot_path.join(WORLD_FILE);
+    save_game_package(world, &world_path.to_string_lossy())?;
+
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs());
+
+    let save = SaveGame {
+        metadata: SaveMetadata {
+            slot: slot.to_string(),
+            timestamp,
+            play_time: value_as(variables.get(PLAY_TIME)).unwrap_or(0.0),
+            game_time: world.resource::<GameTime>().time,
+        },
+        variables: SAVED_VARIABLES
+            .iter()
+            .filter_map(|key| Some((*key, variables.get(*key)?.clone())))
+            .collect(),
+    };
+
+    let metadata_path = slot_path.join(METADATA_FILE);
+    let content =
+        to_string_pretty(&save, PrettyConfig::default()).map_err(|error| SaveError::Serialize {
+            path: metadata_path.clone(),
+            error,
+        })?;
+    write(&metadata_path, content).map_err(io_error(&metadata_path))?;
+
+    Ok(save.metadata)
+}
+
+fn read_save(slot_path: &Path) -> Result<SaveGame, SaveError> {
+    let path = slot_path.join(METADATA_FILE);
+    let content = read_to_string(&path).map_err(|error| SaveError::Io {
+        path: path.clone(),
+        error,
+    })?;
+
+    ron::from_str(&content).map_err(|error| SaveError::Parse { path, error })
+}
+
+/// Merges the world saved in `directory/slot` into `world` and returns the rest of the save.
+///
+/// Meant for the empty world at startup, in place of the data packages. The variables are
+/// left to the caller, since the startup systems would overwrite them.
+pub fn load_save(world: &mut World, directory: &Path, slot: &str) -> Result<SaveGame, SaveError> {
+    let slot_path = directory.join(slot);
+    let save = read_save(&slot_path)?;
+
+    load_game_package_into_world(world, &slot_path.join(WORLD_FILE), &game_migrations())?;
+
+    Ok(save)
+}
+
+/// The metadata of every readable slot in `directory`, most recent first.
+pub fn list_saves(directory: &Path) -> Vec<SaveMetadata> {
+    let Ok(entries) = read_dir(directory) else {
+        return vec![];
+    };
+
+    let mut saves: Vec<SaveMetadata> = entries
+        .filter_map(Result::ok)
+        .filter(|entry| entry.path().is_dir())
+        .filter_map(|entry| match read_save(&entry.path()) {
+            Ok(save) => Some(save.metadata),
+            Err(error) => {
+                log::warn!("Skipping save slot: {}", error);
+                None
+            }
+        })
+        .collect();
+
+    saves.sort_by_key(|save| Reverse(save.timestamp));
+    saves
+}
+
+/// The slot to load at startup from `LOAD_SLOT`, if one is set.
+pub fn startup_slot(config: &Settings) -> Option<&str> {
+    match config.get(LOAD_SLOT) {
+        Some(SettingsValue::String(slot)) if !slot.is_empty() => Some(slot),
+        _ => None,
+    }
+}
+
+/// Saves into `slot` under the configured save directory, logging the outcome.
+pub fn save_to_slot(world: &World, resources: &GameResources, slot: &str) {
+    let directory = save_directory(&resources.config);
+
+    match save_game(world, &resources.variables, &directory, slot) {
+        Ok(metadata) => log::info!(
+            "Saved slot {} after {:.0}s of play",
+            metadata.slot,
+            metadata.play_time
+        ),
+        Err(error) => log::error!("Failed to save slot {}: {}", slot, error),
+    }
+}
+
+/// Seconds between autosaves from `AUTOSAVE_INTERVAL`. Zero or less disables the timer.
+pub fn autosave_interval(config: &Settings) -> f32 {
+    value_as(config.get(AUTOSAVE_INTERVAL)).unwrap_or(DEFAULT_AUTOSAVE_INTERVAL)
+}
@@ -1,42 +1,364 @@
 use crate::GameResources;
-use crate::world::World;
-use game_settings::{ACTIVE_CAMERA, DATA_PACKAGES};
+use crate::utils::migration::{MigrationRegistry, RawWorld, game_migrations};
+use crate::world::{Entity, Query, StringId, World};
+use game_settings::DATA_PACKAGES;
 use ron::ser::{PrettyConfig, to_string, to_string_pretty};
+use ron::value::RawValue;
+use sedona_ecs::snapshot::{Compression, SnapshotError, read_snapshot, read_snapshot_header, write_snapshot};
 use sedona_settings::{SettingsValue, value_as};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::{Display, Formatter};
 use std::fs::{create_dir_all, write};
 use std::path::{Path, PathBuf};
 
-pub fn save_game_package(world: &World, path: &str) {
-    let string =
-        to_string_pretty(world, PrettyConfig::default()).expect("RON serialization failed");
+/// Extension of packages stored as binary snapshots. Every other extension is read as RON.
+pub const SNAPSHOT_EXTENSION: &str = "snap";
 
+fn is_snapshot(path: &Path) -> bool {
+    path.extension().is_some_and(|extension| extension == SNAPSHOT_EXTENSION)
+}
+
+/// The outer layout of a versioned package, e.g. `(version: 1, world: ( ... ))`.
+#[derive(Serialize)]
+struct PackageFile<'a> {
+    version: u32,
+    world: &'a World,
+}
+
+/// Everything read from a RON package in one pass.
+#[derive(Deserialize)]
+struct PackageBody {
+    #[serde(default)]
+    version: u32,
+    #[serde(default)]
+    world: RawWorld,
+    /// Changes the package makes to the entities of packages loaded before it, e.g.
+    /// `(overrides: [(string_id: "prop_crate", delete: true)])`.
+    #[serde(default)]
+    overrides: Vec<EntityOverride>,
+}
+
+#[derive(Debug)]
+pub enum PackageError {
+    Io { path: PathBuf, error: std::io::Error },
+    Parse { path: PathBuf, error: ron::error::SpannedError },
+    UnsupportedVersion { path: PathBuf, version: u32, latest: u32 },
+    Migration { path: PathBuf, version: u32, message: String },
+    Deserialize { path: PathBuf, error: ron::error::SpannedError },
+    Snapshot { path: PathBuf, error: SnapshotError },
+    Serialize { path: PathBuf, message: String },
+}
+
+impl Display for PackageError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PackageError::Io { path, error } => {
+                write!(f, "failed to access package {}: {}", path.display(), error)
+            }
+            PackageError::Parse { path, error } => {
+                write!(f, "failed to parse package {}: {}", path.display(), error)
+            }
+            PackageError::UnsupportedVersion {
+                path,
+                version,
+                latest,
+            } => write!(
+                f,
+                "package {} is version {}, newer than the supported version {}",
+                path.display(),
+                version,
+                latest
+            ),
+            PackageError::Migration {
+                path,
+                version,
+                message,
+            } => write!(
+                f,
+                "failed to upgrade package {} from version {}: {}",
+                path.display(),
+                version,
+                message
+            ),
+            PackageError::Deserialize { path, error } => {
+                write!(f, "failed to load the world of package {}: {}", path.display(), error)
+            }
+            PackageError::Snapshot { path, error } => {
+                write!(f, "failed to read snapshot {}: {}", path.display(), error)
+            }
+            PackageError::Serialize { path, message } => {
+                write!(f, "failed to serialize package {}: {}", path.display(), message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for PackageError {}
+
+/// Writes `world` to `path`, as a binary snapshot if the path ends in `SNAPSHOT_EXTENSION`
+/// and as a RON package otherwise.
+pub fn save_game_package(world: &World, path: &str) -> Result<(), PackageError> {
     let path_obj = Path::new(path);
+    let version = game_migrations().latest_version();
+    let serialize_error = |message: String| PackageError::Serialize {
+        path: path_obj.to_path_buf(),
+        message,
+    };
+
+    let bytes = if is_snapshot(path_obj) {
+        write_snapshot(world, version, Compression::Deflate)
+            .map_err(|error| serialize_error(error.to_string()))?
+    } else {
+        let package = PackageFile { version, world };
+        to_string_pretty(&package, PrettyConfig::default())
+            .map_err(|error| serialize_error(error.to_string()))?
+            .into_bytes()
+    };
+
+    let io_error = |error| PackageError::Io {
+        path: path_obj.to_path_buf(),
+        error,
+    };
+
     if let Some(parent) = path_obj.parent() {
-        create_dir_all(parent).expect("Failed to create parent directories for save path");
+        create_dir_all(parent).map_err(io_error)?;
+    }
+
+    write(path_obj, bytes).map_err(io_error)
+}
+
+/// Reads the world and overrides of a package, upgrading the world through `migrations`
+/// if it is older than their latest version.
+///
+/// Packages without a version hold a plain `World`, as written before packages were
+/// versioned, which is migrated like the rest.
+fn read_game_package(
+    path: &Path,
+    content: &str,
+    migrations: &MigrationRegistry,
+) -> Result<(World, Vec<EntityOverride>), PackageError> {
+    let parse_error = |error| PackageError::Parse {
+        path: path.to_path_buf(),
+        error,
+    };
+
+    let body: PackageBody = ron::from_str(content).map_err(parse_error)?;
+    let latest = migrations.latest_version();
+    if body.version > latest {
+        return Err(PackageError::UnsupportedVersion {
+            path: path.to_path_buf(),
+            version: body.version,
+            latest,
+        });
+    }
+
+    let mut world = if body.version == 0 {
+        let mut world: RawWorld = ron::from_str(content).map_err(parse_error)?;
+        world.remove("overrides");
+        world
+    } else {
+        body.world
+    };
+
+    if world.is_empty() {
+        return Ok((World::default(), body.overrides));
+    }
+
+    migrations
+        .migrate(&mut world, body.version)
+        .map_err(|(version, message)| PackageError::Migration {
+            path: path.to_path_buf(),
+            version,
+            message,
+        })?;
+
+    let world = ron::from_str(&world.to_ron()).map_err(|error| PackageError::Deserialize {
+        path: path.to_path_buf(),
+        error,
+    })?;
+
+    Ok((world, body.overrides))
+}
+
+/// Reads the world of a binary snapshot.
+///
+/// Snapshots have no RON form to migrate, so only those of the latest version load.
+fn read_snapshot_world(
+    path: &Path,
+    bytes: &[u8],
+    migrations: &MigrationRegistry,
+) -> Result<World, PackageError> {
+    let snapshot_error = |error| PackageError::Snapshot {
+        path: path.to_path_buf(),
+        error,
+    };
+
+    let header = read_snapshot_header(bytes).map_err(snapshot_error)?;
+    let latest = migrations.latest_version();
+    if header.version > latest {
+        return Err(PackageError::UnsupportedVersion {
+            path: path.to_path_buf(),
+            version: header.version,
+            latest,
+        });
+    }
+    if header.version < latest {
+        return Err(PackageError::Migration {
+            path: path.to_path_buf(),
+            version: header.version,
+            message: String::from("binary snapshots can't be migrated"),
+        });
     }
 
-    write(path_obj, string).expect("Failed to write RON file");
+    read_snapshot(bytes).map_err(snapshot_error)
+}
+
+/// Deletes the entities whose `StringId` is `string_id`, or replaces the named components.
+#[derive(Debug, Deserialize)]
+struct EntityOverride {
+    string_id: String,
+    #[serde(default)]
+    delete: bool,
+    #[serde(default)]
+    components: BTreeMap<String, Box<RawValue>>,
+}
+
+/// What loading a single package did to the world.
+#[derive(Debug, Default)]
+pub struct PackageReport {
+    pub path: PathBuf,
+    pub added: usize,
+    pub patched: usize,
+    pub deleted: usize,
+    pub reassigned: usize,
+}
+
+impl Display for PackageReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}: {} added, {} patched, {} deleted, {} ids reassigned",
+            self.path.display(),
+            self.added,
+            self.patched,
+            self.deleted,
+            self.reassigned
+        )
+    }
+}
+
+fn entities_by_string_id(world: &World) -> HashMap<String, Vec<Entity>> {
+    let string_ids: Query<(&Entity, &StringId)> = Query::new();
+    let mut entities: HashMap<String, Vec<Entity>> = HashMap::new();
+
+    for (entity, string_id) in world.with_query(string_ids).iter() {
+        if let Some(string_id) = &string_id.0 {
+            entities.entry(string_id.clone()).or_default().push(*entity);
+        }
+    }
+
+    entities
+}
+
+fn apply_overrides(world: &mut World, overrides: Vec<EntityOverride>, report: &mut PackageReport) {
+    let entities = entities_by_string_id(world);
+
+    for entity_override in overrides {
+        let Some(targets) = entities.get(&entity_override.string_id) else {
+            log::warn!(
+                "{} overrides unknown entity {}",
+                report.path.display(),
+                entity_override.string_id
+            );
+            continue;
+        };
+
+        for entity in targets {
+            if entity_override.delete {
+                if world.despawn(*entity) {
+                    report.deleted += 1;
+                }
+                continue;
+            }
+
+            let mut patched = false;
+            for (component, value) in entity_override.components.iter() {
+                match world.set_component_ron(*entity, component, value.get_ron()) {
+                    Ok(()) => patched = true,
+                    Err(error) => log::warn!(
+                        "{} failed to patch {}: {}",
+                        report.path.display(),
+                        entity_override.string_id,
+                        error
+                    ),
+                }
+            }
+
+            if patched {
+                report.patched += 1;
+            }
+        }
+    }
 }
 
-fn load_game_package(path: &Path) -> World {
-    let file = std::fs::File::open(path).expect("Failed to open game package");
-    let world: World = ron::de::from_reader(file).expect("Failed to deserialize game package");
+/// Applies the overrides of the package at `path` to `world`, then merges in its entities.
+///
+/// The format is picked by extension, like `save_game_package` does.
+pub fn load_game_package_into_world(
+    world: &mut World,
+    path: &Path,
+    migrations: &MigrationRegistry,
+) -> Result<PackageReport, PackageError> {
+    let bytes = std::fs::read(path).map_err(|error| PackageError::Io {
+        path: path.to_path_buf(),
+        error,
+    })?;
 
-    world
+    let (package, overrides) = if is_snapshot(path) {
+        let package = read_snapshot_world(path, &bytes, migrations)?;
+        (package, vec![])
+    } else {
+        let content = String::from_utf8(bytes).map_err(|error| PackageError::Io {
+            path: path.to_path_buf(),
+            error: std::io::Error::new(std::io::ErrorKind::InvalidData, error),
+        })?;
+        read_game_package(path, &content, migrations)?
+    };
+
+    let mut report = PackageReport {
+        path: path.to_path_buf(),
+        ..Default::default()
+    };
+
+    apply_overrides(world, overrides, &mut report);
+
+    let all: Query<&Entity> = Query::new();
+    report.added = package.with_query(all).iter().count();
+    report.reassigned = world.merge(package).len();
+
+    Ok(report)
 }
 
+/// Loads the packages listed under `DATA_PACKAGES` in order, each one able to patch or
+/// delete the entities of the ones before it, and logs what each package did.
 pub fn load_game_packages_into_world(world: &mut World, resources: &mut GameResources) {
     let Some(SettingsValue::Array(array)) = resources.config.get(DATA_PACKAGES) else {
         log::error!("Found no game packages to load");
         return;
     };
 
+    let migrations = game_migrations();
+    let mut reports = vec![];
+
     for (index, value) in array.iter().enumerate() {
         match value {
             SettingsValue::String(file_name) => {
                 let path = Path::new("data").join(file_name);
-                let new_world = load_game_package(&path);
-                world.merge(new_world);
+                match load_game_package_into_world(world, &path, &migrations) {
+                    Ok(report) => reports.push(report),
+                    Err(error) => log::error!("Skipping game package: {}", error),
+                }
             }
             other => {
                 log::warn!(
@@ -48,4 +370,9 @@ pub fn load_game_packages_into_world(world: &mut World, resources: &mut GameReso
             }
         }
     }
+
+    log::info!("Loaded {} game packages", reports.len());
+    for (index, report) in reports.iter().enumerate() {
+        log::info!("  {}. {}", index + 1, report);
+    }
 }
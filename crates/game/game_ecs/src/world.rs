@@ -7,6 +7,8 @@ use crate::game::*;
 #[allow(unused_imports)]
 use crate::game_events::*;
 #[allow(unused_imports)]
+use crate::resources::*;
+#[allow(unused_imports)]
 use crate::systems::*;
 #[allow(unused_imports)]
 use serde::{Deserialize, Serialize};
@@ -16,4 +16,9 @@ define_global_keys! {
     DAY_SCALE => "game.time.day_scale",
     START_TIME => "game.time.start_time",
     LATITUDE => "game.time.latitude",
+
+    // SAVE
+    SAVE_DIRECTORY => "game.save.directory",
+    AUTOSAVE_INTERVAL => "game.save.autosave_interval",
+    LOAD_SLOT => "game.save.load_slot",
 }
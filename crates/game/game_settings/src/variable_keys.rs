@@ -1,16 +1,16 @@
 use sedona_settings::define_global_keys;
 
 define_global_keys! {
-    ACTIVE_CAMERA => "active_camera",
     ACTIVE_ENVIORNMENT => "active_environment",
     ACTIVE_SCENE => "active_scene",
     ACTIVE_PLAYER => "active_player",
     ACTIVE_WEATHER => "active_weather",
     ACTIVE_WORLD_SPACE => "active_world_space",
-    GAME_TIME => "game_time",
+    AUTOSAVE_TIMER => "autosave_timer",
     MOON_DIRECTION => "moon_direction",
     MOON_PHASE => "moon_phase",
     MOON_ROTATION => "moon_rotation",
+    PLAY_TIME => "play_time",
     SOLAR_TIME => "solar_time",
     STAR_ROTATION => "star_rotation",
     SUN_DIRECTION => "sun_direction",
@@ -56,6 +56,7 @@ pub fn create_event_structs(input: TokenStream) -> TokenStream {
 
     let queue_struct = Ident::new(&format!("{}Queues", name), name.span());
     let handler_struct = Ident::new(&format!("{}Handlers", name), name.span());
+    let handler_queue_struct = Ident::new(&format!("{}HandlerQueues", name), name.span());
 
     let queue_fields = events.iter().map(|(field, ty)| {
         quote! { pub #field: EventQueue<#ty>, }
@@ -69,10 +70,16 @@ pub fn create_event_structs(input: TokenStream) -> TokenStream {
         quote! { pub #field: EventHandlers<#ty, #world_ty, #resources_ty>, }
     });
 
+    let handler_queue_fields = events.iter().map(|(field, ty)| {
+        quote! { pub #field: HandlerQueue<#ty, #world_ty, #resources_ty>, }
+    });
+
     let dispatch_calls = events.iter().map(|(field, _)| {
         quote! {
+            self.#field.apply(&mut resources.event_queues.handlers.#field);
             for event in resources.event_queues.#field.drain() {
                 self.#field.dispatch(event, world, resources);
+                self.#field.apply(&mut resources.event_queues.handlers.#field);
             }
         }
     });
@@ -84,8 +91,10 @@ pub fn create_event_structs(input: TokenStream) -> TokenStream {
         }
 
         impl #handler_struct {
+            /// Dispatches every queued event, applying the handler changes queued in
+            /// `event_queues.handlers` before the first and after each one.
             pub fn dispatch_all(
-                &self,
+                &mut self,
                 world: &mut #world_ty,
                 resources: &mut #resources_ty,
             ) {
@@ -93,14 +102,22 @@ pub fn create_event_structs(input: TokenStream) -> TokenStream {
             }
         }
 
+        /// Handler registrations and removals requested from handlers and systems.
+        #[derive(Default)]
+        #visibility struct #handler_queue_struct {
+            #(#handler_queue_fields)*
+        }
+
         #visibility struct #queue_struct {
             #(#queue_fields)*
+            pub handlers: #handler_queue_struct,
         }
 
         impl Default for #queue_struct {
             fn default() -> Self {
                 Self {
                     #(#queue_defaults)*
+                    handlers: #handler_queue_struct::default(),
                 }
             }
         }
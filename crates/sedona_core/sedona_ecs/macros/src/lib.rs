@@ -28,11 +28,32 @@ pub fn component(_: TokenStream, input: TokenStream) -> TokenStream {
     .into()
 }
 
+#[proc_macro_attribute]
+pub fn resource(_: TokenStream, input: TokenStream) -> TokenStream {
+    let input_struct = parse_macro_input!(input as ItemStruct);
+
+    quote! {
+        #[derive(Clone, Debug, Default, serde::Deserialize, serde::Serialize)]
+        #input_struct
+    }
+    .into()
+}
+
 #[proc_macro_attribute]
 pub fn system(_: TokenStream, input: TokenStream) -> TokenStream {
     input
 }
 
+#[proc_macro_attribute]
+pub fn on_create(_: TokenStream, input: TokenStream) -> TokenStream {
+    input
+}
+
+#[proc_macro_attribute]
+pub fn on_destroy(_: TokenStream, input: TokenStream) -> TokenStream {
+    input
+}
+
 #[proc_macro]
 pub fn create_event_structs(input: TokenStream) -> TokenStream {
     event::create_event_structs(input)
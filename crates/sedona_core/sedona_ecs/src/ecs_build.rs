@@ -1,53 +1,130 @@
 use std::io::Write;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::{env, fs};
 use glob::glob;
 use quote::quote;
-use crate::generate::{generate_copy_traits, generate_default_queries, generate_queries, generate_systems, generate_world_rs};
-use crate::parse::{collect_ecs_defs, CollectedData, EntityDefField};
+use crate::generate::{generate_cell_queries, generate_commands, generate_copy_traits, generate_default_queries, generate_diff, generate_hierarchy, generate_imports, generate_prefabs, generate_queries, generate_query_chunks, generate_reflection, generate_remap, generate_resources, generate_string_ids, generate_systems, generate_via_queries, generate_world_rs};
+use crate::parse::{
+    collect_ecs_defs, collect_item_imports, file_module_path, hierarchy_component_defs, order_systems, resolve_resource_params, resolve_run_conditions,
+    validate_collected, CollectedData, Diagnostic, EntityDefField,
+};
+
+/// A source root whose definitions go into the generated world.
+#[derive(Debug, Clone)]
+pub struct EcsSource {
+    /// The root module's directory, relative to the crate being built.
+    pub root: String,
+    /// The pattern of source files, relative to `root`.
+    pub glob: String,
+    /// The path the generated code reaches the root module by: a module of the crate being
+    /// built, e.g. `crate::plugins::audio`, or a dependency, e.g. `sedona_audio`.
+    ///
+    /// Empty for the crate being built, whose items the world module imports itself.
+    pub module: String,
+}
+
+impl EcsSource {
+    pub fn new(root: &str, glob: &str, module: &str) -> Self {
+        Self {
+            root: root.to_string(),
+            glob: glob.to_string(),
+            module: module.to_string(),
+        }
+    }
+
+    /// The sources of the crate being built, e.g. `src/**/*.rs`.
+    pub fn local(glob: &str) -> Self {
+        Self::new("", glob, "")
+    }
+
+    /// Returns `true` if the source is another crate, which can't name the generated types.
+    fn is_dependency(&self) -> bool {
+        !self.module.is_empty() && self.module != "crate" && !self.module.starts_with("crate::")
+    }
+
+    /// The directory the glob can match files in, i.e. its path up to the first wildcard.
+    fn watched_dir(&self, manifest_dir: &str) -> PathBuf {
+        let static_prefix: PathBuf = Path::new(&self.glob)
+            .components()
+            .take_while(|component| !component.as_os_str().to_string_lossy().contains(['*', '?', '[']))
+            .collect();
+        Path::new(manifest_dir).join(&self.root).join(static_prefix)
+    }
+}
 
 /// Build the ECS from entity, component, and system defs matching the source glob pattern.
+///
+/// Problems in the definitions are reported through `cargo::warning` and `cargo::error`
+/// with the file and line they come from. Nothing is generated if there are errors.
 pub fn build_ecs(source_glob: &str) {
+    build_ecs_with_parallel_groups(source_glob, &[]);
+}
+
+/// Build the ECS like `build_ecs`, running the systems of `parallel_groups` on the rayon
+/// pool whenever their declared accesses don't conflict.
+///
+/// Systems taking a `&mut` reference (such as `&mut World`) still run on their own; the
+/// rest access the world through a `WorldCell` and their query parameters.
+pub fn build_ecs_with_parallel_groups(source_glob: &str, parallel_groups: &[&str]) {
+    build_ecs_from_sources(&[EcsSource::local(source_glob)], parallel_groups);
+}
+
+/// Build the ECS like `build_ecs_with_parallel_groups`, from several source roots.
+///
+/// Items of a source with a `module` are imported into the world module by their path,
+/// e.g. `src/plugins/audio/systems/emitter.rs` under `crate::plugins::audio` as
+/// `crate::plugins::audio::systems::emitter::*`.
+///
+/// A dependency crate can only provide components, resources and entities made of them:
+/// the `World`, `Entity`, queries and the built-in components are generated into the crate
+/// being built, so a dependency can't name them, and its systems and hooks are reported as
+/// errors. Engine systems shared between games have to be sources of each game crate.
+///
+/// Run conditions are called by their full path rather than imported, so functions of the
+/// same name may come from several sources; a system names one by path, e.g.
+/// `run_if = conditions::ready`, when its bare name is ambiguous.
+///
+/// Cargo is told to rerun the build when the directories of the sources change.
+pub fn build_ecs_from_sources(sources: &[EcsSource], parallel_groups: &[&str]) {
     let manifest_dir = env::var("CARGO_MANIFEST_DIR").expect("CARGO_MANIFEST_DIR not set");
     let out_dir = env::var("OUT_DIR").expect("OUT_DIR not set");
-    let pattern = format!("{manifest_dir}/{source_glob}");
 
     let mut include_files = Vec::new();
-    let mut collected_data = CollectedData::default();
-
-    for file in match glob(&pattern) {
-        Ok(files) => files,
-        Err(e) => panic!("Failed to glob {pattern}: {e}"),
-    } {
-        match file {
-            Ok(path) => {
-                if path.is_file() {
-                    let path_str = path.display().to_string();
-                    let collected = collect_ecs_defs(&path_str);
-                    collected_data.entities.extend(collected.entities);
-                    collected_data.queries.extend(collected.queries);
-                    collected_data.systems.extend(collected.systems);
-                }
-            }
-            Err(e) => eprintln!("Glob error: {e}"),
+
+    // Without any `rerun-if-changed`, cargo already reruns on changes anywhere in the crate.
+    if sources.iter().any(|source| !source.module.is_empty()) {
+        for source in sources {
+            println!("cargo::rerun-if-changed={}", source.watched_dir(&manifest_dir).display());
         }
     }
 
-    collected_data.entities.iter_mut().for_each(|entity| {
-        entity.fields.push(EntityDefField {
-            name: "entity".into(),
-            data_type: "Entity".into(),
-            serialize: true,
-        })
-    });
+    let collected_data = collect_sources(Path::new(&manifest_dir), sources, parallel_groups);
 
-    collected_data.retain_unique_queries();
+    for diagnostic in collected_data.diagnostics.iter() {
+        diagnostic.emit();
+    }
+
+    if collected_data.diagnostics.iter().any(|diagnostic| diagnostic.is_error()) {
+        return;
+    }
 
     include_files.push(generate_default_queries(&out_dir));
     generate_world_rs(&out_dir, &mut include_files, &collected_data);
+    generate_hierarchy(&out_dir, &mut include_files, &collected_data);
+    generate_string_ids(&out_dir, &mut include_files, &collected_data);
+    generate_resources(&out_dir, &mut include_files, &collected_data);
     generate_queries(&out_dir, &mut include_files, &collected_data);
+    generate_via_queries(&out_dir, &mut include_files, &collected_data);
+    generate_query_chunks(&out_dir, &mut include_files, &collected_data);
+    generate_cell_queries(&out_dir, &mut include_files, &collected_data);
     generate_systems(&out_dir, &mut include_files, &collected_data);
+    generate_commands(&out_dir, &mut include_files, &collected_data);
     generate_copy_traits(&out_dir, &mut include_files, &collected_data);
+    generate_prefabs(&out_dir, &mut include_files, &collected_data);
+    generate_reflection(&out_dir, &mut include_files, &collected_data);
+    generate_remap(&out_dir, &mut include_files, &collected_data);
+    generate_diff(&out_dir, &mut include_files, &collected_data);
+    generate_imports(&out_dir, &mut include_files, &collected_data);
 
     let main_file = Path::new(&out_dir).join("sedona_ecs.rs");
     let mut output = match fs::File::create(main_file) {
@@ -71,3 +148,205 @@ pub fn build_ecs(source_glob: &str) {
         Err(e) => panic!("Error writing ECS data to file: {e}"),
     }
 }
+
+/// Collects, validates and resolves the definitions of every source under `manifest_dir`.
+///
+/// Each file's systems and free functions get the module it defines: the source's `module`
+/// followed by the file's path, or `crate` followed by the file's path under `src` for the
+/// crate being built.
+fn collect_sources(manifest_dir: &Path, sources: &[EcsSource], parallel_groups: &[&str]) -> CollectedData {
+    let mut collected_data = CollectedData {
+        parallel_groups: parallel_groups.iter().map(|group| group.to_string()).collect(),
+        ..Default::default()
+    };
+
+    for source in sources {
+        let root = manifest_dir.join(&source.root);
+        let pattern = format!("{}/{}", root.display(), source.glob);
+        let (module_root, module_prefix) = if source.module.is_empty() {
+            (root.join("src"), "crate")
+        } else {
+            (root.clone(), source.module.as_str())
+        };
+
+        for file in match glob(&pattern) {
+            Ok(files) => files,
+            Err(e) => panic!("Failed to glob {pattern}: {e}"),
+        } {
+            match file {
+                Ok(path) => {
+                    if path.is_file() {
+                        let path_str = path.display().to_string();
+                        let mut collected = collect_ecs_defs(&path_str);
+                        let module = std::iter::once(module_prefix.to_string())
+                            .chain(file_module_path(&module_root, &path).unwrap_or_default())
+                            .collect::<Vec<_>>()
+                            .join("::");
+                        if !source.module.is_empty() {
+                            collected_data.imports.extend(collect_item_imports(&collected, &module));
+                        }
+                        if source.is_dependency() {
+                            let outside_world = collected
+                                .systems
+                                .iter()
+                                .map(|system| (&system.name, &system.location))
+                                .chain(collected.hooks.iter().map(|hook| (&hook.name, &hook.location)));
+                            for (name, location) in outside_world {
+                                collected_data.diagnostics.push(Diagnostic::error(
+                                    location.clone(),
+                                    format!("`{name}` is in the dependency {module}, which can't reach the `World` generated for this crate"),
+                                ));
+                            }
+                        }
+                        for system in collected.systems.iter_mut() {
+                            system.module = module.clone();
+                        }
+                        let functions = collected.functions.into_values().map(|mut function| {
+                            function.module = module.clone();
+                            if !source.module.is_empty() {
+                                function.path = function.qualified_name();
+                            }
+                            (function.qualified_name(), function)
+                        });
+                        collected_data.functions.extend(functions);
+                        collected_data.entities.extend(collected.entities);
+                        collected_data.components.extend(collected.components);
+                        collected_data.resources.extend(collected.resources);
+                        collected_data.queries.extend(collected.queries);
+                        collected_data.systems.extend(collected.systems);
+                        collected_data.hooks.extend(collected.hooks);
+                        collected_data.called_groups.extend(collected.called_groups);
+                        collected_data.diagnostics.extend(collected.diagnostics);
+                    }
+                }
+                Err(e) => eprintln!("Glob error: {e}"),
+            }
+        }
+    }
+
+    collected_data.entities.iter_mut().for_each(|entity| {
+        entity.fields.push(EntityDefField {
+            name: "entity".into(),
+            data_type: "Entity".into(),
+            serialize: true,
+        })
+    });
+
+    collected_data.components.extend(hierarchy_component_defs());
+
+    collected_data.retain_unique_queries();
+
+    let diagnostics = validate_collected(&collected_data);
+    collected_data.diagnostics.extend(diagnostics);
+
+    match order_systems(std::mem::take(&mut collected_data.systems)) {
+        Ok(systems) => collected_data.systems = systems,
+        Err(diagnostic) => collected_data.diagnostics.push(diagnostic),
+    }
+
+    if let Err(diagnostic) =
+        resolve_resource_params(&mut collected_data.systems, &collected_data.resources)
+    {
+        collected_data.diagnostics.push(diagnostic);
+    }
+
+    if let Err(diagnostic) =
+        resolve_run_conditions(&mut collected_data.systems, &collected_data.functions)
+    {
+        collected_data.diagnostics.push(diagnostic);
+    }
+
+    collected_data
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_sources(dir: &Path, files: &[(&str, &str)]) {
+        for (file, content) in files {
+            let path = dir.join(file);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        }
+    }
+
+    #[test]
+    fn sources_key_functions_by_module() {
+        let dir = env::temp_dir().join(format!("sedona_ecs_sources_{}", std::process::id()));
+        write_sources(
+            &dir,
+            &[
+                ("src/systems.rs", "#[system(group=update, run_if=ready)] fn tick(world: &World) {}\nfn ready() -> bool { true }"),
+                ("plugins/audio/conditions.rs", "pub fn ready(world: &World) -> bool { true }"),
+                (
+                    "plugins/audio/systems.rs",
+                    "#[system(group=update, run_if=conditions::ready)] pub fn audio_tick(world: &World) {}",
+                ),
+            ],
+        );
+
+        let sources = [EcsSource::local("src/**/*.rs"), EcsSource::new("plugins/audio", "**/*.rs", "crate::plugins::audio")];
+        let collected = collect_sources(&dir, &sources, &[]);
+        fs::remove_dir_all(&dir).unwrap();
+
+        let errors: Vec<&str> = collected
+            .diagnostics
+            .iter()
+            .filter(|diagnostic| diagnostic.is_error())
+            .map(|diagnostic| diagnostic.message.as_str())
+            .collect();
+        assert!(errors.is_empty(), "{errors:?}");
+
+        let mut functions: Vec<&str> = collected.functions.keys().map(String::as_str).collect();
+        functions.sort();
+        assert!(functions.contains(&"crate::systems::ready"), "{functions:?}");
+        assert!(functions.contains(&"crate::plugins::audio::conditions::ready"), "{functions:?}");
+
+        let condition = |name: &str| {
+            let system = collected.systems.iter().find(|system| system.name == name).unwrap();
+            system.run_if[0].path.clone()
+        };
+        assert_eq!(condition("tick"), "ready");
+        assert_eq!(condition("audio_tick"), "crate::plugins::audio::conditions::ready");
+
+        let imports: Vec<&str> = collected.imports.iter().map(|import| import.path.as_str()).collect();
+        assert_eq!(imports, ["crate::plugins::audio::systems::audio_tick"]);
+    }
+
+    #[test]
+    fn dependencies_only_provide_types() {
+        let dir = env::temp_dir().join(format!("sedona_ecs_dependency_{}", std::process::id()));
+        let game = dir.join("game");
+        write_sources(
+            &game,
+            &[
+                ("src/systems.rs", "#[system(group=update)] fn tick(world: &World) {}"),
+                ("../sedona_audio/src/emitter.rs", "#[component] pub struct Emitter { pub volume: f32 }"),
+                (
+                    "../sedona_audio/src/systems.rs",
+                    "#[system(group=update)] pub fn audio_tick(world: &World) {}",
+                ),
+            ],
+        );
+
+        let sources = [EcsSource::local("src/**/*.rs"), EcsSource::new("../sedona_audio/src", "emitter.rs", "sedona_audio")];
+        let collected = collect_sources(&game, &sources, &[]);
+        assert!(collected.diagnostics.iter().all(|diagnostic| !diagnostic.is_error()));
+        let imports: Vec<&str> = collected.imports.iter().map(|import| import.path.as_str()).collect();
+        assert_eq!(imports, ["sedona_audio::emitter::Emitter"]);
+
+        let sources = [EcsSource::local("src/**/*.rs"), EcsSource::new("../sedona_audio/src", "**/*.rs", "sedona_audio")];
+        let collected = collect_sources(&game, &sources, &[]);
+        fs::remove_dir_all(&dir).unwrap();
+
+        let errors: Vec<&str> = collected
+            .diagnostics
+            .iter()
+            .filter(|diagnostic| diagnostic.is_error())
+            .map(|diagnostic| diagnostic.message.as_str())
+            .collect();
+        assert_eq!(errors.len(), 1, "{errors:?}");
+        assert!(errors[0].starts_with("`audio_tick` is in the dependency sedona_audio::systems"), "{errors:?}");
+    }
+}
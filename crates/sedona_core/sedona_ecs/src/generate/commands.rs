@@ -0,0 +1,140 @@
+use crate::{CollectedData, fident, write_token_stream_to_file};
+use heck::ToSnakeCase;
+use inflector::string::pluralize::to_plural;
+use itertools::Itertools;
+use quote::{format_ident, quote};
+
+pub fn generate_commands(out_dir: &str, include_files: &mut Vec<String>, collected: &CollectedData) {
+    const FILE_NAME: &str = "commands.rs";
+
+    let mut code_rs = vec![];
+
+    code_rs.push(quote! {
+        type Command = Box<dyn FnOnce(&mut World) + Send>;
+
+        /// Deferred world operations recorded by systems.
+        ///
+        /// Systems that take `&mut Commands` can queue entity creation, despawning, and component
+        /// writes while iterating queries. The queue is applied once every system in the group has run.
+        #[derive(Default)]
+        pub struct Commands {
+            queue: Vec<Command>,
+        }
+
+        #[allow(dead_code)]
+        impl Commands {
+            pub fn new() -> Self {
+                Self::default()
+            }
+
+            /// Queues the creation of an entity and returns the handle it will have once applied.
+            pub fn create<T>(&mut self, e: T) -> Entity
+            where
+                T: EntityKind + Send + 'static,
+                World: WorldCreate<T>,
+            {
+                let id = Uuid::new_v4();
+                self.queue.push(Box::new(move |world: &mut World| {
+                    world.create_with_id(id, e);
+                }));
+
+                Entity {
+                    entity_type: T::ENTITY_TYPE,
+                    id,
+                    generation: 0,
+                }
+            }
+
+            /// Queues the removal of an entity.
+            pub fn despawn(&mut self, entity: Entity) {
+                self.queue.push(Box::new(move |world: &mut World| {
+                    world.despawn(entity);
+                }));
+            }
+
+            /// Queues a component write, replacing the entity's current value.
+            pub fn insert<C>(&mut self, entity: Entity, component: C)
+            where
+                C: Send + 'static,
+                World: WorldInsert<C>,
+            {
+                self.queue.push(Box::new(move |world: &mut World| {
+                    world.insert(entity, component);
+                }));
+            }
+
+            pub fn len(&self) -> usize {
+                self.queue.len()
+            }
+
+            pub fn is_empty(&self) -> bool {
+                self.queue.is_empty()
+            }
+
+            /// Applies every queued command in the order it was recorded.
+            pub fn apply(&mut self, world: &mut World) {
+                for command in self.queue.drain(..) {
+                    command(world);
+                }
+            }
+        }
+
+        pub trait WorldInsert<C> {
+            /// Overwrites the component on an entity, returning `false` if the handle is stale
+            /// or the entity type has no such component.
+            fn insert(&mut self, entity: Entity, component: C) -> bool;
+        }
+    });
+
+    let component_types = collected
+        .entities
+        .iter()
+        .flat_map(|entity| entity.fields.iter())
+        .filter(|field| field.data_type != "Entity")
+        .map(|field| field.data_type.clone())
+        .unique();
+
+    for component_type in component_types {
+        let mut match_insert_rs = vec![];
+
+        for entity in collected.entities.iter() {
+            let Some(field) = entity.fields.iter().find(|f| f.data_type == component_type) else {
+                continue;
+            };
+
+            let entity_name = fident!(entity.name);
+            let archetype_field_name = fident!(to_plural(&entity.name.to_snake_case()));
+            let component_field_name = format_ident!("{}", to_plural(&field.name));
+
+            match_insert_rs.push(quote! {
+                EntityType::#entity_name => self.#archetype_field_name.#component_field_name[index] = component,
+            });
+        }
+
+        let component_type: proc_macro2::TokenStream = component_type.parse().unwrap();
+
+        code_rs.push(quote! {
+            impl WorldInsert<#component_type> for World {
+                #[allow(unreachable_patterns)]
+                fn insert(&mut self, entity: Entity, component: #component_type) -> bool {
+                    let Some((index, entity_type)) = entity.locate(self) else {
+                        return false;
+                    };
+
+                    match entity_type {
+                        #(#match_insert_rs)*
+                        _ => return false,
+                    }
+
+                    true
+                }
+            }
+        });
+    }
+
+    let code_rs = quote! {
+        #(#code_rs)*
+    };
+
+    include_files.push(write_token_stream_to_file(out_dir, FILE_NAME, &code_rs.to_string()));
+}
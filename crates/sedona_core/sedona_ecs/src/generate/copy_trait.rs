@@ -1,5 +1,5 @@
-use crate::{CollectedData, fident, write_token_stream_to_file};
-use quote::{format_ident, quote};
+use crate::{CollectedData, query_field_type, query_filter_type, write_token_stream_to_file};
+use quote::quote;
 
 pub fn generate_copy_traits(
     out_dir: &str,
@@ -13,29 +13,21 @@ pub fn generate_copy_traits(
     code_rs.push(quote! {});
 
     for query in collected.queries.iter() {
-        let mut data_types = vec![];
+        let data_types: Vec<_> = query
+            .fields
+            .iter()
+            .map(|field| query_field_type(field, &quote! {}))
+            .collect();
 
-        for field in query.mut_fields.iter() {
-            let field_data_type = fident!(field);
-            data_types.push(quote! {
-                &mut #field_data_type
-            });
-        }
-
-        for field in query.const_fields.iter() {
-            let field_data_type = fident!(field);
-            data_types.push(quote! {
-                &#field_data_type
-            });
-        }
+        let filter_type = query_filter_type(query);
 
         if data_types.len() > 1 {
             code_rs.push(quote! {
-                impl Copy for Query<(#(#data_types),*)> {}
+                impl Copy for Query<(#(#data_types),*), #filter_type> {}
             });
         } else if let Some(data_type) = data_types.first() {
             code_rs.push(quote! {
-                impl Copy for Query<#data_type> {}
+                impl Copy for Query<#data_type, #filter_type> {}
             });
         }
     }
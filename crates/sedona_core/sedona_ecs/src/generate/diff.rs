@@ -0,0 +1,375 @@
+use crate::{CollectedData, fident, write_token_stream_to_file};
+use heck::ToSnakeCase;
+use inflector::string::pluralize::to_plural;
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+
+pub fn generate_diff(out_dir: &str, include_files: &mut Vec<String>, collected: &CollectedData) {
+    const FILE_NAME: &str = "diff.rs";
+
+    let mut snapshot_rs = vec![];
+    let mut match_restore_rs = vec![];
+    let mut match_required_rs = vec![];
+    let mut match_check_rs = vec![];
+
+    for entity in collected.entities.iter() {
+        let entity_name = fident!(entity.name);
+        let archetype_field = format_ident!("{}", to_plural(&entity.name.to_snake_case()));
+
+        let snapshot_fields: Vec<_> = entity
+            .fields
+            .iter()
+            .filter(|field| field.data_type != "Entity" && field.serialize)
+            .collect();
+
+        let insert_components_rs = snapshot_fields.iter().map(|field| {
+            let name = &field.name;
+            let column = format_ident!("{}", to_plural(&field.name));
+            quote! {
+                components.insert(#name.to_string(), sedona_ecs::snapshot::to_bytes(&self.#archetype_field.#column[index])?);
+            }
+        });
+
+        snapshot_rs.push(quote! {
+            for (index, entity) in self.#archetype_field.entities.iter().enumerate() {
+                let mut components = std::collections::BTreeMap::new();
+                #(#insert_components_rs)*
+                entities.insert(entity.id, EntitySnapshot { entity: *entity, components });
+            }
+        });
+
+        let restore_fields_rs = snapshot_fields.iter().map(|field| {
+            let name = &field.name;
+            let field_name = fident!(&field.name);
+            quote! {
+                #field_name: snapshot.component(#name)?,
+            }
+        });
+
+        // Skipped fields are not in the snapshot and start out with their default.
+        let skipped = entity
+            .fields
+            .iter()
+            .any(|field| field.data_type != "Entity" && !field.serialize);
+        let default_rs = skipped.then(|| quote! { ..Default::default() });
+
+        let names = snapshot_fields.iter().map(|field| &field.name);
+        match_required_rs.push(quote! {
+            EntityType::#entity_name => &[#(#names),*],
+        });
+
+        for field in snapshot_fields.iter() {
+            let name = &field.name;
+            let data_type: TokenStream = field.data_type.parse().unwrap();
+            match_check_rs.push(quote! {
+                (EntityType::#entity_name, #name) => {
+                    sedona_ecs::snapshot::from_bytes::<#data_type>(bytes)?;
+                    Ok(())
+                }
+            });
+        }
+
+        match_restore_rs.push(quote! {
+            EntityType::#entity_name => self.create_with_id(id, #entity_name {
+                #(#restore_fields_rs)*
+                #default_rs
+            }),
+        });
+    }
+
+    let code_rs = quote! {
+        /// The components of one entity, each encoded in the binary snapshot layout.
+        #[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+        pub struct EntitySnapshot {
+            pub entity: Entity,
+            pub components: std::collections::BTreeMap<String, Vec<u8>>,
+        }
+
+        #[allow(dead_code)]
+        impl EntitySnapshot {
+            /// Decodes the component called `name`.
+            pub fn component<T: serde::de::DeserializeOwned>(&self, name: &str) -> Result<T, DiffError> {
+                let bytes = self.components.get(name).ok_or_else(|| DiffError::MissingComponent {
+                    entity: self.entity,
+                    component: name.to_string(),
+                })?;
+                Ok(sedona_ecs::snapshot::from_bytes(bytes)?)
+            }
+        }
+
+        /// The state of every entity in a `World` at one point in time.
+        ///
+        /// Fields marked `#[serde(skip)]` are left out, as they are from saves. Resources
+        /// are not captured.
+        #[derive(Clone, Debug, Default, PartialEq, serde::Deserialize, serde::Serialize)]
+        pub struct WorldSnapshot {
+            pub entities: std::collections::BTreeMap<Uuid, EntitySnapshot>,
+        }
+
+        /// A component whose value differs between two snapshots.
+        #[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+        pub struct ComponentChange {
+            pub entity: Entity,
+            pub component: String,
+            pub before: Vec<u8>,
+            pub after: Vec<u8>,
+        }
+
+        /// What changed between two snapshots, which can be applied to go forward or
+        /// reverted to go back.
+        ///
+        /// An id that was reused by a new entity counts as removed and added.
+        #[derive(Clone, Debug, Default, PartialEq, serde::Deserialize, serde::Serialize)]
+        pub struct WorldDiff {
+            pub added: Vec<EntitySnapshot>,
+            pub removed: Vec<EntitySnapshot>,
+            pub changed: Vec<ComponentChange>,
+        }
+
+        #[allow(dead_code)]
+        impl WorldDiff {
+            pub fn between(before: &WorldSnapshot, after: &WorldSnapshot) -> WorldDiff {
+                let mut diff = WorldDiff::default();
+
+                for (id, old) in &before.entities {
+                    match after.entities.get(id) {
+                        Some(new) if new.entity == old.entity => {
+                            for (component, before_bytes) in &old.components {
+                                if let Some(after_bytes) = new.components.get(component)
+                                    && after_bytes != before_bytes
+                                {
+                                    diff.changed.push(ComponentChange {
+                                        entity: old.entity,
+                                        component: component.clone(),
+                                        before: before_bytes.clone(),
+                                        after: after_bytes.clone(),
+                                    });
+                                }
+                            }
+                        }
+                        _ => diff.removed.push(old.clone()),
+                    }
+                }
+
+                for (id, new) in &after.entities {
+                    if before.entities.get(id).is_none_or(|old| old.entity != new.entity) {
+                        diff.added.push(new.clone());
+                    }
+                }
+
+                diff
+            }
+
+            pub fn is_empty(&self) -> bool {
+                self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+            }
+
+            /// Returns the diff that undoes this one.
+            pub fn inverse(&self) -> WorldDiff {
+                WorldDiff {
+                    added: self.removed.clone(),
+                    removed: self.added.clone(),
+                    changed: self
+                        .changed
+                        .iter()
+                        .map(|change| ComponentChange {
+                            entity: change.entity,
+                            component: change.component.clone(),
+                            before: change.after.clone(),
+                            after: change.before.clone(),
+                        })
+                        .collect(),
+                }
+            }
+        }
+
+        #[derive(Debug)]
+        pub enum DiffError {
+            /// The world is not in the state the diff starts from.
+            Conflict { entity: Entity },
+            MissingComponent { entity: Entity, component: String },
+            Snapshot(sedona_ecs::snapshot::SnapshotError),
+            Reflect(ReflectError),
+        }
+
+        impl std::fmt::Display for DiffError {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                match self {
+                    DiffError::Conflict { entity } => {
+                        write!(f, "entity {} does not match the diff", entity.id())
+                    }
+                    DiffError::MissingComponent { entity, component } => {
+                        write!(f, "snapshot of entity {} has no component `{component}`", entity.id())
+                    }
+                    DiffError::Snapshot(error) => write!(f, "{error}"),
+                    DiffError::Reflect(error) => write!(f, "{error}"),
+                }
+            }
+        }
+
+        impl std::error::Error for DiffError {}
+
+        impl From<sedona_ecs::snapshot::SnapshotError> for DiffError {
+            fn from(error: sedona_ecs::snapshot::SnapshotError) -> Self {
+                DiffError::Snapshot(error)
+            }
+        }
+
+        impl From<ReflectError> for DiffError {
+            fn from(error: ReflectError) -> Self {
+                DiffError::Reflect(error)
+            }
+        }
+
+        #[allow(dead_code)]
+        impl World {
+            #[allow(unused_mut)]
+            pub fn snapshot(&self) -> Result<WorldSnapshot, sedona_ecs::snapshot::SnapshotError> {
+                let mut entities = std::collections::BTreeMap::new();
+                #(#snapshot_rs)*
+                Ok(WorldSnapshot { entities })
+            }
+
+            /// Returns what changed since `snapshot` was taken.
+            pub fn diff_since(&self, snapshot: &WorldSnapshot) -> Result<WorldDiff, sedona_ecs::snapshot::SnapshotError> {
+                Ok(WorldDiff::between(snapshot, &self.snapshot()?))
+            }
+
+            /// Moves the world from the state before `diff` to the state after it.
+            ///
+            /// Nothing is changed if the world is not in the state before, e.g. an entity to
+            /// remove is gone or a component holds another value, or if a value to set does
+            /// not decode as its component. Removed entities are
+            /// despawned and added ones created with their old handles, running the
+            /// `#[on_destroy]` and `#[on_create]` hooks as usual.
+            pub fn apply_diff(&mut self, diff: &WorldDiff) -> Result<(), DiffError> {
+                for snapshot in &diff.removed {
+                    for (component, bytes) in &snapshot.components {
+                        self.check_component(snapshot.entity, component, bytes)?;
+                    }
+                }
+
+                for change in &diff.changed {
+                    self.check_component(change.entity, &change.component, &change.before)?;
+                }
+
+                for snapshot in &diff.added {
+                    if let Some(entity) = self.entity(snapshot.entity.id)
+                        && !diff.removed.iter().any(|removed| removed.entity == entity)
+                    {
+                        return Err(DiffError::Conflict { entity });
+                    }
+                    Self::check_snapshot(snapshot)?;
+                }
+
+                for change in &diff.changed {
+                    Self::check_component_bytes(change.entity.entity_type, &change.component, &change.after)?;
+                }
+
+                for snapshot in &diff.removed {
+                    self.despawn(snapshot.entity);
+                }
+
+                for snapshot in &diff.added {
+                    self.restore_entity(snapshot)?;
+                }
+
+                // Hooks and hierarchy links may have touched the new rows, and parents added
+                // after their children were not alive yet, so the snapshot values win.
+                for snapshot in &diff.added {
+                    for (component, bytes) in &snapshot.components {
+                        self.set_component_bytes(snapshot.entity, component, bytes)?;
+                    }
+                }
+
+                for change in &diff.changed {
+                    self.set_component_bytes(change.entity, &change.component, &change.after)?;
+                }
+
+                let string_id_changed = diff.changed.iter().any(|change| {
+                    change
+                        .entity
+                        .entity_type()
+                        .component(&change.component)
+                        .is_some_and(|component| component.type_name == "StringId")
+                });
+                if string_id_changed {
+                    self.reindex_string_ids();
+                }
+
+                Ok(())
+            }
+
+            /// Moves the world from the state after `diff` back to the state before it.
+            pub fn revert_diff(&mut self, diff: &WorldDiff) -> Result<(), DiffError> {
+                self.apply_diff(&diff.inverse())
+            }
+
+            fn check_component(&self, entity: Entity, name: &str, expected: &[u8]) -> Result<(), DiffError> {
+                if !self.is_alive(entity) {
+                    return Err(DiffError::Conflict { entity });
+                }
+
+                let mut serializer = sedona_ecs::snapshot::BinarySerializer { output: vec![] };
+                self.serialize_component(entity, name, &mut serializer)?;
+
+                if serializer.output == expected {
+                    Ok(())
+                } else {
+                    Err(DiffError::Conflict { entity })
+                }
+            }
+
+            /// Checks that `snapshot` holds every component its entity is restored from, and
+            /// nothing else, each in a value that decodes.
+            fn check_snapshot(snapshot: &EntitySnapshot) -> Result<(), DiffError> {
+                let required: &[&str] = match snapshot.entity.entity_type {
+                    #(#match_required_rs)*
+                };
+
+                if let Some(name) = required.iter().find(|name| !snapshot.components.contains_key(**name)) {
+                    return Err(DiffError::MissingComponent {
+                        entity: snapshot.entity,
+                        component: name.to_string(),
+                    });
+                }
+
+                for (component, bytes) in &snapshot.components {
+                    Self::check_component_bytes(snapshot.entity.entity_type, component, bytes)?;
+                }
+
+                Ok(())
+            }
+
+            fn check_component_bytes(entity_type: EntityType, name: &str, bytes: &[u8]) -> Result<(), DiffError> {
+                match (entity_type, name) {
+                    #(#match_check_rs)*
+                    _ => Err(ReflectError::UnknownComponent {
+                        entity_type,
+                        component: name.to_string(),
+                    }
+                    .into()),
+                }
+            }
+
+            fn set_component_bytes(&mut self, entity: Entity, name: &str, bytes: &[u8]) -> Result<(), DiffError> {
+                let mut deserializer = sedona_ecs::snapshot::BinaryDeserializer::new(bytes);
+                self.deserialize_component(entity, name, &mut deserializer)?;
+                Ok(())
+            }
+
+            fn restore_entity(&mut self, snapshot: &EntitySnapshot) -> Result<Entity, DiffError> {
+                let id = snapshot.entity.id;
+                self.generations.insert(id, snapshot.entity.generation);
+
+                let entity = match snapshot.entity.entity_type {
+                    #(#match_restore_rs)*
+                };
+
+                Ok(entity)
+            }
+        }
+    };
+
+    include_files.push(write_token_stream_to_file(out_dir, FILE_NAME, &code_rs.to_string()));
+}
@@ -0,0 +1,399 @@
+use crate::generate::ticks_field_ident;
+use crate::{
+    CollectedData, EntityDef, component_column_arms, fident, propagation_system_name,
+    write_token_stream_to_file,
+};
+use heck::ToSnakeCase;
+use inflector::string::pluralize::to_plural;
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+
+/// Returns `true` if the entity type can take part in the hierarchy, as a parent or a child.
+pub fn has_hierarchy(entity: &EntityDef) -> bool {
+    entity
+        .fields
+        .iter()
+        .any(|field| field.data_type == "Parent" || field.data_type == "Children")
+}
+
+pub fn generate_hierarchy(out_dir: &str, include_files: &mut Vec<String>, collected: &CollectedData) {
+    const FILE_NAME: &str = "hierarchy.rs";
+
+    let (match_parent_rs, match_parent_mut_rs) = component_column_arms(collected, "Parent");
+    let (match_children_rs, match_children_mut_rs) = component_column_arms(collected, "Children");
+
+    let propagation_rs = collected
+        .components
+        .iter()
+        .filter(|component| component.propagate)
+        .map(|component| propagation_rs(collected, &component.name));
+
+    let code_rs = quote! {
+        /// A component carried down the hierarchy, such as a global transform, each entity
+        /// taking its value from what its parent passes on.
+        ///
+        /// Implemented by `#[component(propagate(...))]` components, whose built-in system
+        /// `propagate_<component>` keeps them up to date.
+        pub trait Propagate {
+            /// What an entity passes on to its children.
+            type Carried: Clone;
+
+            fn carried(&self) -> Self::Carried;
+
+            /// Updates this entity's value from what its parent passes on.
+            fn propagate(&mut self, parent: &Self::Carried);
+        }
+
+        #(#propagation_rs)*
+
+        /// The entity this one hangs under.
+        ///
+        /// Kept in step with the parent's `Children` by `create`, `despawn`, `set_parent` and
+        /// `remove_parent`, which mark both sides changed. Writing it through a query or
+        /// `insert` bypasses that.
+        #[derive(Clone, Copy, Debug, Default, PartialEq, serde::Deserialize, serde::Serialize)]
+        pub struct Parent(pub Option<Entity>);
+
+        /// The entities hanging under this one, in the order they were attached.
+        #[derive(Clone, Debug, Default, PartialEq, serde::Deserialize, serde::Serialize)]
+        pub struct Children(pub Vec<Entity>);
+
+        /// The descendants of an entity, each before its own children and after its older siblings' subtrees.
+        pub struct DescendantsDepthFirst<'a> {
+            world: &'a World,
+            stack: Vec<Entity>,
+        }
+
+        impl Iterator for DescendantsDepthFirst<'_> {
+            type Item = Entity;
+
+            fn next(&mut self) -> Option<Entity> {
+                let entity = self.stack.pop()?;
+                self.stack.extend(self.world.children(entity).iter().rev());
+                Some(entity)
+            }
+        }
+
+        /// The descendants of an entity, level by level, so every entity comes after its parent.
+        pub struct DescendantsBreadthFirst<'a> {
+            world: &'a World,
+            queue: std::collections::VecDeque<Entity>,
+        }
+
+        impl Iterator for DescendantsBreadthFirst<'_> {
+            type Item = Entity;
+
+            fn next(&mut self) -> Option<Entity> {
+                let entity = self.queue.pop_front()?;
+                self.queue.extend(self.world.children(entity));
+                Some(entity)
+            }
+        }
+
+        #[allow(dead_code)]
+        impl World {
+            /// Returns the parent of an entity, if it is alive and has one.
+            pub fn parent(&self, entity: Entity) -> Option<Entity> {
+                let (index, entity_type) = entity.locate(self)?;
+                self.parent_at(index, entity_type)?.0
+            }
+
+            /// Returns the children of an entity, or nothing if it is stale or can't have any.
+            pub fn children(&self, entity: Entity) -> &[Entity] {
+                entity
+                    .locate(self)
+                    .and_then(|(index, entity_type)| self.children_at(index, entity_type))
+                    .map_or(&[], |children| &children.0)
+            }
+
+            /// Returns `true` if `ancestor` is `entity` or is above it in the hierarchy.
+            pub fn is_ancestor_of(&self, ancestor: Entity, entity: Entity) -> bool {
+                // Bounded by the entity count, in case a loaded world holds a cycle.
+                std::iter::successors(Some(entity), |current| self.parent(*current))
+                    .take(self.index_lookup.len() + 1)
+                    .any(|current| current == ancestor)
+            }
+
+            /// Hangs `child` under `parent`, taking it out of its previous parent's `Children`.
+            ///
+            /// Returns `false`, changing nothing, if either handle is stale, `child` has no
+            /// `Parent` or `parent` no `Children` component, or `child` is `parent` or above it.
+            pub fn set_parent(&mut self, child: Entity, parent: Entity) -> bool {
+                let (Some(child_location), Some(parent_location)) = (child.locate(self), parent.locate(self)) else {
+                    return false;
+                };
+
+                if self.parent_at(child_location.0, child_location.1).is_none()
+                    || self.children_at(parent_location.0, parent_location.1).is_none()
+                    || self.is_ancestor_of(child, parent)
+                {
+                    return false;
+                }
+
+                if self.parent(child) == Some(parent) {
+                    return true;
+                }
+
+                self.detach_from_parent(child);
+
+                let Some(slot) = self.parent_at_mut(child_location.0, child_location.1) else {
+                    return false;
+                };
+                slot.0 = Some(parent);
+
+                let Some(children) = self.children_at_mut(parent_location.0, parent_location.1) else {
+                    return false;
+                };
+                if !children.0.contains(&child) {
+                    children.0.push(child);
+                }
+
+                true
+            }
+
+            /// Takes `child` out of its parent's `Children`, making it a root.
+            ///
+            /// Returns `false` if the handle is stale or the entity has no parent.
+            pub fn remove_parent(&mut self, child: Entity) -> bool {
+                if self.parent(child).is_none() {
+                    return false;
+                }
+
+                self.detach_from_parent(child);
+                true
+            }
+
+            pub fn iter_descendants_depth_first(&self, entity: Entity) -> DescendantsDepthFirst<'_> {
+                DescendantsDepthFirst {
+                    world: self,
+                    stack: self.children(entity).iter().rev().copied().collect(),
+                }
+            }
+
+            pub fn iter_descendants_breadth_first(&self, entity: Entity) -> DescendantsBreadthFirst<'_> {
+                DescendantsBreadthFirst {
+                    world: self,
+                    queue: self.children(entity).iter().copied().collect(),
+                }
+            }
+
+            /// Despawns an entity and everything under it.
+            ///
+            /// Parents go before their children, so the `#[on_destroy]` hooks of an entity
+            /// still see its children. Returns `false` if the handle is stale.
+            pub fn despawn_recursive(&mut self, entity: Entity) -> bool {
+                let descendants: Vec<Entity> = self.iter_descendants_depth_first(entity).collect();
+
+                if !self.despawn(entity) {
+                    return false;
+                }
+
+                for descendant in descendants {
+                    self.despawn(descendant);
+                }
+
+                true
+            }
+
+            /// Links a new entity with the parent and children it was created with.
+            ///
+            /// Links that `set_parent` would refuse are dropped, and children are taken from
+            /// the parents they had.
+            fn link_created(&mut self, entity: Entity) {
+                let Some((index, entity_type)) = entity.locate(self) else {
+                    return;
+                };
+
+                if let Some(parent) = self.parent_at_mut(index, entity_type).and_then(|parent| parent.0.take()) {
+                    self.set_parent(entity, parent);
+                }
+
+                let children = self
+                    .children_at_mut(index, entity_type)
+                    .map(|children| std::mem::take(&mut children.0))
+                    .unwrap_or_default();
+
+                for child in children {
+                    self.detach_from_parent(child);
+                    self.set_parent(child, entity);
+                }
+            }
+
+            /// Unlinks an entity about to be despawned from its parent and orphans its children.
+            fn unlink_despawned(&mut self, entity: Entity) {
+                self.detach_from_parent(entity);
+
+                for child in self.children(entity).to_vec() {
+                    if let Some((index, entity_type)) = child.locate(self)
+                        && let Some(parent) = self.parent_at_mut(index, entity_type)
+                    {
+                        parent.0 = None;
+                    }
+                }
+            }
+
+            /// Clears the parent of `child` and removes it from the parent's `Children`.
+            fn detach_from_parent(&mut self, child: Entity) {
+                let Some(parent) = self.parent(child) else {
+                    return;
+                };
+                let Some((index, entity_type)) = child.locate(self) else {
+                    return;
+                };
+
+                let Some(slot) = self.parent_at_mut(index, entity_type) else {
+                    return;
+                };
+                slot.0 = None;
+
+                if let Some((index, entity_type)) = parent.locate(self)
+                    && let Some(children) = self.children_at_mut(index, entity_type)
+                {
+                    children.0.retain(|sibling| *sibling != child);
+                }
+            }
+
+            #[allow(unreachable_patterns, unused_variables)]
+            fn parent_at(&self, index: usize, entity_type: EntityType) -> Option<&Parent> {
+                match entity_type {
+                    #(#match_parent_rs)*
+                    _ => None,
+                }
+            }
+
+            #[allow(unreachable_patterns, unused_variables)]
+            fn parent_at_mut(&mut self, index: usize, entity_type: EntityType) -> Option<&mut Parent> {
+                match entity_type {
+                    #(#match_parent_mut_rs)*
+                    _ => None,
+                }
+            }
+
+            #[allow(unreachable_patterns, unused_variables)]
+            fn children_at(&self, index: usize, entity_type: EntityType) -> Option<&Children> {
+                match entity_type {
+                    #(#match_children_rs)*
+                    _ => None,
+                }
+            }
+
+            #[allow(unreachable_patterns, unused_variables)]
+            fn children_at_mut(&mut self, index: usize, entity_type: EntityType) -> Option<&mut Children> {
+                match entity_type {
+                    #(#match_children_mut_rs)*
+                    _ => None,
+                }
+            }
+        }
+    };
+
+    include_files.push(write_token_stream_to_file(out_dir, FILE_NAME, &code_rs.to_string()));
+}
+
+/// The built-in system carrying `component` down the hierarchy, and the lookups it uses.
+fn propagation_rs(collected: &CollectedData, component: &str) -> TokenStream {
+    let component_ident = fident!(component);
+    let system_name = format_ident!("{}", propagation_system_name(component));
+    let component_of = format_ident!("{}_of", component.to_snake_case());
+    let component_of_mut = format_ident!("{}_of_mut", component.to_snake_case());
+    let (match_component_rs, match_component_mut_rs) = component_column_arms(collected, component);
+
+    let changed_rs = collected.entities.iter().filter_map(|entity| {
+        let field = entity.fields.iter().find(|field| field.data_type == component)?;
+        let archetype_field = format_ident!("{}", to_plural(&entity.name.to_snake_case()));
+        let ticks_column = ticks_field_ident(&field.name);
+        let parent_changed_rs = entity
+            .fields
+            .iter()
+            .find(|field| field.data_type == "Parent")
+            .map(|parent| {
+                let parent_ticks_column = ticks_field_ident(&parent.name);
+                quote! { || table.#parent_ticks_column[*index].is_changed(last_run) }
+            });
+
+        Some(quote! {
+            let table = &world.#archetype_field;
+            let last_run = table.system_ticks.last_run;
+            changed.extend(
+                table
+                    .entities
+                    .iter()
+                    .enumerate()
+                    .filter(|(index, _)| table.#ticks_column[*index].is_changed(last_run) #parent_changed_rs)
+                    .map(|(_, entity)| *entity),
+            );
+        })
+    });
+
+    quote! {
+        /// The built-in system carrying a `#[component(propagate(...))]` component down the
+        /// hierarchy.
+        ///
+        /// Every entity whose component or `Parent` changed since the system last ran takes
+        /// its value from its parent, then passes it on to everything under it, parents before
+        /// their children.
+        #[allow(clippy::clone_on_copy)]
+        pub fn #system_name(world: &mut World) {
+            let mut changed: Vec<Entity> = vec![];
+            #(#changed_rs)*
+
+            // Entities under another changed entity are reached from it.
+            let changed_set: std::collections::HashSet<Entity> = changed.iter().copied().collect();
+            let mut queue = std::collections::VecDeque::new();
+            for entity in changed {
+                let mut ancestors = std::iter::successors(world.parent(entity), |ancestor| world.parent(*ancestor))
+                    .take(world.index_lookup.len());
+                if ancestors.any(|ancestor| changed_set.contains(&ancestor)) {
+                    continue;
+                }
+
+                let carried = world
+                    .parent(entity)
+                    .and_then(|parent| world.#component_of(parent))
+                    .map(Propagate::carried);
+                queue.push_back((entity, carried));
+            }
+
+            // Bounded by the visited set, in case a loaded world holds a cycle.
+            let mut visited = std::collections::HashSet::new();
+            while let Some((entity, carried)) = queue.pop_front() {
+                if !visited.insert(entity) {
+                    continue;
+                }
+
+                let Some(value) = world.#component_of_mut(entity) else {
+                    continue;
+                };
+                if let Some(carried) = &carried {
+                    value.propagate(carried);
+                }
+
+                let carried = value.carried();
+                for child in world.children(entity) {
+                    queue.push_back((*child, Some(carried.clone())));
+                }
+            }
+        }
+
+        #[allow(dead_code)]
+        impl World {
+            #[allow(unreachable_patterns, unused_variables)]
+            fn #component_of(&self, entity: Entity) -> Option<&#component_ident> {
+                let (index, entity_type) = entity.locate(self)?;
+                match entity_type {
+                    #(#match_component_rs)*
+                    _ => None,
+                }
+            }
+
+            #[allow(unreachable_patterns, unused_variables)]
+            fn #component_of_mut(&mut self, entity: Entity) -> Option<&mut #component_ident> {
+                let (index, entity_type) = entity.locate(self)?;
+                match entity_type {
+                    #(#match_component_mut_rs)*
+                    _ => None,
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,26 @@
+use crate::{CollectedData, write_token_stream_to_file};
+use proc_macro2::TokenStream;
+use quote::quote;
+
+pub fn generate_imports(out_dir: &str, include_files: &mut Vec<String>, collected: &CollectedData) {
+    const FILE_NAME: &str = "imports.rs";
+
+    let mut paths: Vec<&str> = collected
+        .imports
+        .iter()
+        .map(|import| import.path.as_str())
+        .collect();
+    paths.sort();
+    paths.dedup();
+
+    let paths = paths.into_iter().map(|path| path.parse::<TokenStream>().unwrap());
+
+    let code_rs = quote! {
+        #(
+            #[allow(unused_imports)]
+            use #paths;
+        )*
+    };
+
+    include_files.push(write_token_stream_to_file(out_dir, FILE_NAME, &code_rs.to_string()));
+}
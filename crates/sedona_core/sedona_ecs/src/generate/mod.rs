@@ -1,13 +1,37 @@
+pub mod commands;
 pub mod copy_trait;
+pub mod diff;
 pub mod file;
+pub mod hierarchy;
+pub mod imports;
+pub mod prefab;
 pub mod query;
+pub mod query_cell;
+pub mod query_chunk;
 pub mod query_default;
+pub mod query_via;
+pub mod reflect;
+pub mod remap;
+pub mod resource;
+pub mod string_id;
 pub mod system;
 pub mod world;
 
+pub use commands::*;
 pub use copy_trait::*;
+pub use diff::*;
 pub use file::*;
+pub use hierarchy::*;
+pub use imports::*;
+pub use prefab::*;
 pub use query::*;
+pub use query_cell::*;
+pub use query_chunk::*;
 pub use query_default::*;
+pub use query_via::*;
+pub use reflect::*;
+pub use remap::*;
+pub use resource::*;
+pub use string_id::*;
 pub use system::*;
 pub use world::*;
@@ -0,0 +1,265 @@
+use crate::{CollectedData, fident, write_token_stream_to_file};
+use quote::{format_ident, quote};
+
+pub fn generate_prefabs(out_dir: &str, include_files: &mut Vec<String>, collected: &CollectedData) {
+    const FILE_NAME: &str = "prefab.rs";
+
+    let mut code_rs = vec![];
+    let mut match_spawn_rs = vec![];
+
+    code_rs.push(quote! {
+        /// An entity template read from a RON file.
+        ///
+        /// The entity starts from the `Default` of its `entity_type`, then each entry of
+        /// `components` replaces the field of the same name. A prefab may extend a `base`
+        /// prefab, inheriting its type, components and children, and spawns every prefab in
+        /// `children` along with itself, hung under it through `Parent` and `Children`.
+        ///
+        /// ```ron
+        /// (
+        ///     base: "props/prop.ron",
+        ///     entity_type: PropEntity,
+        ///     components: {
+        ///         "model_path": ModelPath("assets/models/cube.glb"),
+        ///     },
+        ///     children: ["props/ball.ron"],
+        /// )
+        /// ```
+        #[derive(Debug, Default, serde::Deserialize)]
+        pub struct Prefab {
+            #[serde(default)]
+            pub base: Option<String>,
+            #[serde(default)]
+            pub entity_type: Option<EntityType>,
+            #[serde(default)]
+            pub components: std::collections::BTreeMap<String, Box<sedona_ecs::ron::value::RawValue>>,
+            #[serde(default)]
+            pub children: Vec<String>,
+        }
+
+        #[derive(Debug)]
+        pub enum PrefabError {
+            Io { path: std::path::PathBuf, error: std::io::Error },
+            Parse { path: std::path::PathBuf, error: Box<sedona_ecs::ron::error::SpannedError> },
+            Cycle { path: std::path::PathBuf },
+            MissingEntityType { path: std::path::PathBuf },
+            UnknownComponent { path: std::path::PathBuf, entity_type: EntityType, component: String },
+            InvalidComponent { path: std::path::PathBuf, component: String, error: Box<sedona_ecs::ron::error::SpannedError> },
+            /// A nested prefab can't be hung under its parent, which needs a `Children`
+            /// component and the child a `Parent` component.
+            Unlinked { path: std::path::PathBuf, entity_type: EntityType, parent_type: EntityType },
+        }
+
+        impl std::fmt::Display for PrefabError {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                match self {
+                    PrefabError::Io { path, error } => {
+                        write!(f, "failed to read prefab {}: {error}", path.display())
+                    }
+                    PrefabError::Parse { path, error } => {
+                        write!(f, "failed to parse prefab {}: {error}", path.display())
+                    }
+                    PrefabError::Cycle { path } => {
+                        write!(f, "prefab {} includes itself", path.display())
+                    }
+                    PrefabError::MissingEntityType { path } => {
+                        write!(f, "prefab {} names no entity type", path.display())
+                    }
+                    PrefabError::UnknownComponent { path, entity_type, component } => write!(
+                        f,
+                        "prefab {} sets `{component}`, which {entity_type:?} does not have",
+                        path.display(),
+                    ),
+                    PrefabError::InvalidComponent { path, component, error } => write!(
+                        f,
+                        "prefab {} has an invalid `{component}`: {error}",
+                        path.display(),
+                    ),
+                    PrefabError::Unlinked { path, entity_type, parent_type } => write!(
+                        f,
+                        "prefab {} can't hang its {entity_type:?} under a {parent_type:?}, which needs `Children` and it `Parent`",
+                        path.display(),
+                    ),
+                }
+            }
+        }
+
+        impl std::error::Error for PrefabError {}
+
+        impl Prefab {
+            /// Reads the prefab at `path` under `root`, with its `base` prefabs merged in.
+            ///
+            /// `stack` holds the prefabs being resolved, to reject prefabs that include themselves.
+            fn load(
+                root: &std::path::Path,
+                path: &std::path::Path,
+                stack: &mut Vec<std::path::PathBuf>,
+            ) -> Result<Self, PrefabError> {
+                let full_path = root.join(path);
+
+                if stack.contains(&full_path) {
+                    return Err(PrefabError::Cycle { path: full_path });
+                }
+
+                let content = std::fs::read_to_string(&full_path).map_err(|error| PrefabError::Io {
+                    path: full_path.clone(),
+                    error,
+                })?;
+
+                // Lets `base: "props/prop.ron"` stand for `base: Some("props/prop.ron")`.
+                let options = sedona_ecs::ron::Options::default()
+                    .with_default_extension(sedona_ecs::ron::extensions::Extensions::IMPLICIT_SOME);
+
+                let mut prefab: Prefab = options.from_str(&content).map_err(|error| PrefabError::Parse {
+                    path: full_path.clone(),
+                    error: Box::new(error),
+                })?;
+
+                if let Some(base) = prefab.base.take() {
+                    stack.push(full_path);
+                    let mut base = Prefab::load(root, std::path::Path::new(&base), stack)?;
+                    stack.pop();
+
+                    base.entity_type = prefab.entity_type.or(base.entity_type);
+                    base.components.append(&mut prefab.components);
+                    base.children.append(&mut prefab.children);
+                    prefab = base;
+                }
+
+                Ok(prefab)
+            }
+        }
+    });
+
+    for entity in collected.entities.iter() {
+        let entity_name = fident!(entity.name);
+
+        let match_field_rs = entity
+            .fields
+            .iter()
+            .filter(|field| field.data_type != "Entity")
+            .map(|field| {
+                let field_name_str = &field.name;
+                let field_name = fident!(field.name);
+                quote! {
+                    #field_name_str => self.#field_name = value.into_rust()?,
+                }
+            });
+
+        code_rs.push(quote! {
+            #[allow(dead_code)]
+            impl #entity_name {
+                /// Deserializes the RON `value` into the component field called `name`.
+                ///
+                /// Returns `Ok(false)` if the entity has no such field.
+                pub fn set_component_value(
+                    &mut self,
+                    name: &str,
+                    value: &sedona_ecs::ron::value::RawValue,
+                ) -> Result<bool, sedona_ecs::ron::error::SpannedError> {
+                    match name {
+                        #(#match_field_rs)*
+                        _ => return Ok(false),
+                    }
+                    Ok(true)
+                }
+            }
+        });
+
+        match_spawn_rs.push(quote! {
+            EntityType::#entity_name => {
+                let mut entity = #entity_name::default();
+                for (component, value) in prefab.components {
+                    match entity.set_component_value(&component, &value) {
+                        Ok(true) => {}
+                        Ok(false) => {
+                            return Err(PrefabError::UnknownComponent {
+                                path: full_path,
+                                entity_type,
+                                component,
+                            });
+                        }
+                        Err(error) => {
+                            return Err(PrefabError::InvalidComponent {
+                                path: full_path,
+                                component,
+                                error: Box::new(error),
+                            });
+                        }
+                    }
+                }
+                self.create(entity)
+            }
+        });
+    }
+
+    code_rs.push(quote! {
+        #[allow(dead_code)]
+        impl World {
+            /// Sets the directory prefab paths are relative to.
+            pub fn set_prefab_root(&mut self, root: impl Into<std::path::PathBuf>) {
+                self.prefab_root = root.into();
+            }
+
+            /// Creates the entity described by the prefab at `path`, relative to the prefab
+            /// root, along with the entities of its nested prefabs as its children, so
+            /// `despawn_recursive` removes the whole tree.
+            ///
+            /// Returns the entity of the prefab itself. If any prefab of the tree fails to
+            /// load, the entities already created for it are despawned again.
+            pub fn spawn_prefab(&mut self, path: impl AsRef<std::path::Path>) -> Result<Entity, PrefabError> {
+                let mut spawned = vec![];
+                let result = self.spawn_prefab_nested(path.as_ref(), &mut vec![], &mut spawned);
+
+                if result.is_err() {
+                    for entity in spawned.into_iter().rev() {
+                        self.despawn(entity);
+                    }
+                }
+
+                result
+            }
+
+            fn spawn_prefab_nested(
+                &mut self,
+                path: &std::path::Path,
+                stack: &mut Vec<std::path::PathBuf>,
+                spawned: &mut Vec<Entity>,
+            ) -> Result<Entity, PrefabError> {
+                let root = self.prefab_root.clone();
+                let full_path = root.join(path);
+                let prefab = Prefab::load(&root, path, stack)?;
+
+                let Some(entity_type) = prefab.entity_type else {
+                    return Err(PrefabError::MissingEntityType { path: full_path });
+                };
+
+                let entity = match entity_type {
+                    #(#match_spawn_rs)*
+                };
+                spawned.push(entity);
+
+                stack.push(full_path);
+                for child in prefab.children.iter() {
+                    let child_entity = self.spawn_prefab_nested(std::path::Path::new(child), stack, spawned)?;
+                    if !self.set_parent(child_entity, entity) {
+                        return Err(PrefabError::Unlinked {
+                            path: root.join(child),
+                            entity_type: child_entity.entity_type,
+                            parent_type: entity_type,
+                        });
+                    }
+                }
+                stack.pop();
+
+                Ok(entity)
+            }
+        }
+    });
+
+    let code_rs = quote! {
+        #(#code_rs)*
+    };
+
+    include_files.push(write_token_stream_to_file(out_dir, FILE_NAME, &code_rs.to_string()));
+}
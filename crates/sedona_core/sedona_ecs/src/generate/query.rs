@@ -1,6 +1,10 @@
-use crate::{CollectedData, EntityDef, fident, write_token_stream_to_file};
+use crate::{
+    CollectedData, EntityDef, QueryDef, QueryDefField, QueryFilter, fident, ticks_field_ident,
+    write_token_stream_to_file,
+};
 use heck::ToSnakeCase;
 use inflector::string::pluralize::to_plural;
+use proc_macro2::{Ident, TokenStream};
 use quote::{format_ident, quote};
 
 pub fn generate_queries(out_dir: &str, include_files: &mut Vec<String>, collected: &CollectedData) {
@@ -9,7 +13,7 @@ pub fn generate_queries(out_dir: &str, include_files: &mut Vec<String>, collecte
     let mut code_rs = vec![];
 
     code_rs.push(quote! {
-        pub trait LenFrom<'a, T>
+        pub trait LenFrom<'a, T, F = ()>
         where
             T: 'a + Send
         {
@@ -19,7 +23,7 @@ pub fn generate_queries(out_dir: &str, include_files: &mut Vec<String>, collecte
             }
         }
 
-        pub trait QueryFrom<'a, T>
+        pub trait QueryFrom<'a, T, F = ()>
         where
             T: 'a + Send
         {
@@ -29,7 +33,7 @@ pub fn generate_queries(out_dir: &str, include_files: &mut Vec<String>, collecte
             fn at(&'a self, index: usize) -> Option<T>;
         }
 
-        pub trait QueryMutFrom<'a, T>
+        pub trait QueryMutFrom<'a, T, F = ()>
         where
             T: 'a + Send
         {
@@ -40,203 +44,165 @@ pub fn generate_queries(out_dir: &str, include_files: &mut Vec<String>, collecte
         }
     });
 
-    for query in collected.queries.iter() {
-        let mutable = !query.mut_fields.is_empty();
+    // Joins need the whole world, see `generate_via_queries`.
+    for query in collected.queries.iter().filter(|query| !query.has_via()) {
+        let mutable = query.is_mutable();
 
         let matching_entities: Vec<&EntityDef> = collected
             .entities
             .iter()
-            .filter(|entity| {
-                let mut all_fields_present = true;
-
-                for query_field in query.mut_fields.iter() {
-                    if !entity
-                        .fields
-                        .iter()
-                        .any(|entity_field| entity_field.data_type == *query_field)
-                    {
-                        all_fields_present = false;
-                        break;
-                    }
-                }
-
-                for query_field in query.const_fields.iter() {
-                    if !entity
-                        .fields
-                        .iter()
-                        .any(|entity_field| entity_field.data_type == *query_field)
-                    {
-                        all_fields_present = false;
-                        break;
-                    }
-                }
-
-                all_fields_present
-            })
+            .filter(|entity| query.matches(entity))
             .collect();
 
-        let mut data_types = vec![];
-
-        for field in query.mut_fields.iter() {
-            let field_data_type = fident!(field);
-            data_types.push(quote! {
-                &'a mut #field_data_type
-            });
-        }
-
-        for field in query.const_fields.iter() {
-            let field_data_type = fident!(field);
-
-            data_types.push(quote! {
-                &'a #field_data_type
-            });
-        }
+        let data_types = query.fields.iter().map(|field| query_field_type(field, &quote! { 'a }));
+        let query_type = quote! { (#(#data_types),*) };
+        let filter_type = query_filter_type(query);
 
         let mut match_get_rs = vec![];
 
         for entity in matching_entities.iter() {
             let entity_name = fident!(entity.name);
+            let archetype_type = fident!(to_plural(&entity.name));
+            let archetype_field_name = fident!(to_plural(&entity.name.to_snake_case()));
 
-            let mut field_quotes = vec![];
-            let mut par_field_quotes = vec![];
-            let mut get_quotes = vec![];
-
-            for field in query.mut_fields.iter() {
-                let field_name = fident!(to_plural(
-                    entity
-                        .fields
-                        .iter()
-                        .find(|f| f.data_type == *field)
-                        .unwrap()
-                        .name
-                        .as_str()
-                ));
-
-                field_quotes.push(quote! {
-                    self.#field_name.iter_mut()
-                });
-
-                par_field_quotes.push(quote! {
-                    self.#field_name.par_iter_mut()
-                });
+            let columns = QueryColumns::new(query, entity);
 
-                get_quotes.push(quote! {
-                    self.#field_name.get_mut(index)?
-                });
-            }
-            for field in query.const_fields.iter() {
-                let field_name = fident!(to_plural(
-                    entity
-                        .fields
-                        .iter()
-                        .find(|f| f.data_type == *field)
-                        .unwrap()
-                        .name
-                        .as_str()
-                ));
-
-                field_quotes.push(quote! {
-                    self.#field_name.iter()
-                });
-
-                par_field_quotes.push(quote! {
-                    self.#field_name.par_iter()
-                });
+            let iter_rs = columns.iter_rs(&quote! { self }, false);
+            let par_iter_rs = columns.iter_rs(&quote! { self }, true);
+            let get_rs = columns.get_rs(&quote! { self });
+            let filter_check_rs = columns.filter_check_rs(&quote! { self });
 
-                get_quotes.push(quote! {
-                    self.#field_name.get(index)?
-                });
-            }
-
-            let archetype_type = fident!(to_plural(&entity.name));
-            let archetype_field_name = fident!(to_plural(&entity.name.to_snake_case()));
+            let (len_rs, at_index_rs) = if !query.has_tick_filters() {
+                (quote! { self.entities.len() }, quote! {})
+            } else {
+                (
+                    quote! {
+                        (0..self.entities.len())
+                            .filter(|&index| #filter_check_rs)
+                            .count()
+                    },
+                    quote! {
+                        let index = (0..self.entities.len())
+                            .filter(|&index| #filter_check_rs)
+                            .nth(index)?;
+                    },
+                )
+            };
+
+            let get_guard_rs = if !query.has_tick_filters() {
+                quote! {}
+            } else {
+                quote! {
+                    if !(#filter_check_rs) {
+                        return None;
+                    }
+                }
+            };
 
             code_rs.push(quote! {
                 #[allow(unused_parens)]
-                impl<'a> LenFrom<'a, (#(#data_types),*)> for #archetype_type {
+                impl<'a> LenFrom<'a, #query_type, #filter_type> for #archetype_type {
                     fn len(&'a self) -> usize {
-                        self.entities.len()
+                        #len_rs
                     }
                 }
             });
 
             if mutable {
                 code_rs.push(quote! {
-                    #[allow(unused_parens, clippy::needless_question_mark, clippy::double_parens)]
-                    impl<'a> QueryMutFrom<'a, (#(#data_types),*)> for #archetype_type {
-                        fn query_mut_from(&'a mut self) -> impl Iterator<Item = (#(#data_types),*)> {
-                            izip!(#(#field_quotes),*)
+                    #[allow(unused_parens, unused_variables, clippy::needless_question_mark, clippy::double_parens)]
+                    impl<'a> QueryMutFrom<'a, #query_type, #filter_type> for #archetype_type {
+                        fn query_mut_from(&'a mut self) -> impl Iterator<Item = #query_type> {
+                            #iter_rs
                         }
 
-                        fn par_query_mut_from(&'a mut self) -> impl ParallelIterator<Item = (#(#data_types),*)> {
-                            izip_par!(#(#par_field_quotes),*)
+                        fn par_query_mut_from(&'a mut self) -> impl ParallelIterator<Item = #query_type> {
+                            #par_iter_rs
                         }
 
-                        fn get_mut_from(&'a mut self, index: usize, _entity_type: EntityType) -> Option<(#(#data_types),*)> {
-                            Some((#(#get_quotes),*))
+                        fn get_mut_from(&'a mut self, index: usize, _entity_type: EntityType) -> Option<#query_type> {
+                            #get_guard_rs
+                            #get_rs
                         }
 
-                        fn at_mut(&'a mut self, index: usize) -> Option<(#(#data_types),*)>
+                        fn at_mut(&'a mut self, index: usize) -> Option<#query_type>
                         {
-                            Some((#(#get_quotes),*))
+                            #at_index_rs
+                            #get_rs
                         }
                     }
                 });
 
                 match_get_rs.push(quote! {
-                    EntityType::#entity_name => self.#archetype_field_name.get_mut_from(index, entity_type),
+                    EntityType::#entity_name => <#archetype_type as QueryMutFrom<'a, #query_type, #filter_type>>::get_mut_from(&mut self.#archetype_field_name, index, entity_type),
                 });
             } else {
                 code_rs.push(quote! {
-                    #[allow(unused_parens, clippy::needless_question_mark, clippy::double_parens)]
-                    impl<'a> QueryFrom<'a, (#(#data_types),*)> for #archetype_type {
-                        fn query_from(&'a self) -> impl Iterator<Item = (#(#data_types),*)> {
-                            izip!(#(#field_quotes),*)
+                    #[allow(unused_parens, unused_variables, clippy::needless_question_mark, clippy::double_parens)]
+                    impl<'a> QueryFrom<'a, #query_type, #filter_type> for #archetype_type {
+                        fn query_from(&'a self) -> impl Iterator<Item = #query_type> {
+                            #iter_rs
                         }
 
-                        fn par_query_from(&'a self) -> impl ParallelIterator<Item = (#(#data_types),*)> {
-                            izip_par!(#(#par_field_quotes),*)
+                        fn par_query_from(&'a self) -> impl ParallelIterator<Item = #query_type> {
+                            #par_iter_rs
                         }
 
-                        fn get_from(&'a self, index: usize, _entity_type: EntityType) -> Option<(#(#data_types),*)> {
-                            Some((#(#get_quotes),*))
+                        fn get_from(&'a self, index: usize, _entity_type: EntityType) -> Option<#query_type> {
+                            #get_guard_rs
+                            #get_rs
                         }
 
-                        fn at(&'a self, index: usize) -> Option<(#(#data_types),*)>
+                        fn at(&'a self, index: usize) -> Option<#query_type>
                         {
-                            Some((#(#get_quotes),*))
+                            #at_index_rs
+                            #get_rs
                         }
                     }
                 });
 
                 match_get_rs.push(quote! {
-                    EntityType::#entity_name => self.#archetype_field_name.get_from(index, entity_type),
+                    EntityType::#entity_name => <#archetype_type as QueryFrom<'a, #query_type, #filter_type>>::get_from(&self.#archetype_field_name, index, entity_type),
                 });
             }
         }
 
-        let sum_args: Vec<_> = matching_entities
+        // Row counts per matching archetype, honouring filters. These avoid the `'a` borrow of
+        // `LenFrom::len` so they can be used alongside mutable access in `at_mut`.
+        let len_args: Vec<_> = matching_entities
             .iter()
             .map(|entity| {
                 let property_name =
                     format_ident!("{}", to_plural(&(&entity.name.to_snake_case())));
-                quote! { self.#property_name.len() }
+
+                if !query.has_tick_filters() {
+                    quote! { self.#property_name.len() }
+                } else {
+                    let filter_check_rs = QueryColumns::new(query, entity)
+                        .filter_check_rs(&quote! { self.#property_name });
+
+                    quote! {
+                        (0..self.#property_name.len())
+                            .filter(|&index| #filter_check_rs)
+                            .count()
+                    }
+                }
             })
             .collect();
 
-        if !sum_args.is_empty() {
+        if !len_args.is_empty() {
             code_rs.push(quote! {
                 #[allow(unused_parens, unused_variables, unused_assignments)]
-                impl<'a> LenFrom<'a, (#(#data_types),*)> for World {
+                impl<'a> LenFrom<'a, #query_type, #filter_type> for World {
                     fn len(&'a self) -> usize {
-                        sum!(#(#sum_args),*)
+                        sum!(#(#len_args),*)
                     }
                 }
             });
         } else {
             code_rs.push(quote! {
                 #[allow(unused_parens, unused_variables, unused_assignments)]
-                impl<'a> LenFrom<'a, (#(#data_types),*)> for World {
+                impl<'a> LenFrom<'a, #query_type, #filter_type> for World {
                     fn len(&'a self) -> usize {
                         0
                     }
@@ -248,31 +214,35 @@ pub fn generate_queries(out_dir: &str, include_files: &mut Vec<String>, collecte
             let chain_args: Vec<_> = matching_entities
                 .iter()
                 .map(|entity| {
+                    let archetype_type = fident!(to_plural(&entity.name));
                     let property_name =
                         format_ident!("{}", to_plural(&(&entity.name.to_snake_case())));
-                    quote! { self.#property_name.query_mut() }
+                    quote! { <#archetype_type as QueryMutFrom<'a, #query_type, #filter_type>>::query_mut_from(&mut self.#property_name) }
                 })
                 .collect();
 
             let par_chain_args: Vec<_> = matching_entities
                 .iter()
                 .map(|entity| {
+                    let archetype_type = fident!(to_plural(&entity.name));
                     let property_name =
                         format_ident!("{}", to_plural(&(&entity.name.to_snake_case())));
-                    quote! { self.#property_name.par_query_mut() }
+                    quote! { <#archetype_type as QueryMutFrom<'a, #query_type, #filter_type>>::par_query_mut_from(&mut self.#property_name) }
                 })
                 .collect();
 
             let at_mut_args: Vec<_> = matching_entities
                 .iter()
-                .map(|entity| {
+                .zip(len_args.iter())
+                .map(|(entity, len_rs)| {
+                    let archetype_type = fident!(to_plural(&entity.name));
                     let property_name =
                         format_ident!("{}", to_plural(&(&entity.name.to_snake_case())));
                     quote! {
                         {
-                            let len = self.#property_name.len();
+                            let len = #len_rs;
                             if index < len {
-                                return self.#property_name.at_mut(index);
+                                return <#archetype_type as QueryMutFrom<'a, #query_type, #filter_type>>::at_mut(&mut self.#property_name, index);
                             }
                             index -= len;
                         }
@@ -282,17 +252,17 @@ pub fn generate_queries(out_dir: &str, include_files: &mut Vec<String>, collecte
 
             code_rs.push(quote! {
                 #[allow(unused_parens, unused_variables, unused_assignments)]
-                impl<'a> QueryMutFrom<'a, (#(#data_types),*)> for World {
-                    fn query_mut_from(&'a mut self) -> impl Iterator<Item = (#(#data_types),*)> {
+                impl<'a> QueryMutFrom<'a, #query_type, #filter_type> for World {
+                    fn query_mut_from(&'a mut self) -> impl Iterator<Item = #query_type> {
                         chain!(#(#chain_args),*)
                     }
 
-                    fn par_query_mut_from(&'a mut self) -> impl ParallelIterator<Item = (#(#data_types),*)> {
+                    fn par_query_mut_from(&'a mut self) -> impl ParallelIterator<Item = #query_type> {
                         chain_par!(#(#par_chain_args),*)
                     }
 
                     #[allow(unreachable_patterns, clippy::match_single_binding)]
-                    fn get_mut_from(&'a mut self, index: usize, entity_type: EntityType) -> Option<(#(#data_types),*)> {
+                    fn get_mut_from(&'a mut self, index: usize, entity_type: EntityType) -> Option<#query_type> {
                         match entity_type {
                             #(#match_get_rs)*
                             _ => None
@@ -300,7 +270,7 @@ pub fn generate_queries(out_dir: &str, include_files: &mut Vec<String>, collecte
                     }
 
                     #[allow(unused_mut)]
-                    fn at_mut(&'a mut self, index: usize) -> Option<(#(#data_types),*)>
+                    fn at_mut(&'a mut self, index: usize) -> Option<#query_type>
                     {
                         let mut index = index;
                         #(#at_mut_args)*
@@ -312,31 +282,35 @@ pub fn generate_queries(out_dir: &str, include_files: &mut Vec<String>, collecte
             let chain_args: Vec<_> = matching_entities
                 .iter()
                 .map(|entity| {
+                    let archetype_type = fident!(to_plural(&entity.name));
                     let property_name =
                         format_ident!("{}", to_plural(&entity.name.to_snake_case()));
-                    quote! { self.#property_name.query() }
+                    quote! { <#archetype_type as QueryFrom<'a, #query_type, #filter_type>>::query_from(&self.#property_name) }
                 })
                 .collect();
 
             let par_chain_args: Vec<_> = matching_entities
                 .iter()
                 .map(|entity| {
+                    let archetype_type = fident!(to_plural(&entity.name));
                     let property_name =
                         format_ident!("{}", to_plural(&entity.name.to_snake_case()));
-                    quote! { self.#property_name.par_query() }
+                    quote! { <#archetype_type as QueryFrom<'a, #query_type, #filter_type>>::par_query_from(&self.#property_name) }
                 })
                 .collect();
 
             let at_args: Vec<_> = matching_entities
                 .iter()
-                .map(|entity| {
+                .zip(len_args.iter())
+                .map(|(entity, len_rs)| {
+                    let archetype_type = fident!(to_plural(&entity.name));
                     let property_name =
                         format_ident!("{}", to_plural(&(&entity.name.to_snake_case())));
                     quote! {
                         {
-                            let len = self.#property_name.len();
+                            let len = #len_rs;
                             if index < len {
-                                return self.#property_name.at(index);
+                                return <#archetype_type as QueryFrom<'a, #query_type, #filter_type>>::at(&self.#property_name, index);
                             }
                             index -= len;
                         }
@@ -346,17 +320,17 @@ pub fn generate_queries(out_dir: &str, include_files: &mut Vec<String>, collecte
 
             code_rs.push(quote! {
                 #[allow(unused_parens, unused_variables, unused_assignments)]
-                impl<'a> QueryFrom<'a, (#(#data_types),*)> for World {
-                    fn query_from(&'a self) -> impl Iterator<Item = (#(#data_types),*)> {
+                impl<'a> QueryFrom<'a, #query_type, #filter_type> for World {
+                    fn query_from(&'a self) -> impl Iterator<Item = #query_type> {
                         chain!(#(#chain_args),*)
                     }
 
-                    fn par_query_from(&'a self) -> impl ParallelIterator<Item = (#(#data_types),*)> {
+                    fn par_query_from(&'a self) -> impl ParallelIterator<Item = #query_type> {
                         chain_par!(#(#par_chain_args),*)
                     }
 
                     #[allow(unreachable_patterns, clippy::match_single_binding)]
-                    fn get_from(&'a self, index: usize, entity_type: EntityType) -> Option<(#(#data_types),*)> {
+                    fn get_from(&'a self, index: usize, entity_type: EntityType) -> Option<#query_type> {
                         match entity_type {
                             #(#match_get_rs)*
                             _ => None
@@ -364,7 +338,7 @@ pub fn generate_queries(out_dir: &str, include_files: &mut Vec<String>, collecte
                     }
 
                     #[allow(unused_mut)]
-                    fn at(&'a self, index: usize) -> Option<(#(#data_types),*)>
+                    fn at(&'a self, index: usize) -> Option<#query_type>
                     {
                         let mut index = index;
                         #(#at_args)*
@@ -381,3 +355,344 @@ pub fn generate_queries(out_dir: &str, include_files: &mut Vec<String>, collecte
 
     include_files.push(write_token_stream_to_file(out_dir, FILE_NAME, &code_rs.to_string()));
 }
+
+/// Returns the type of a query element as written in `Query<T, F>`, borrowing for `lifetime`.
+pub fn query_field_type(field: &QueryDefField, lifetime: &TokenStream) -> TokenStream {
+    let data_type = fident!(field.data_type);
+
+    let reference = if field.mutable {
+        quote! { & #lifetime mut #data_type }
+    } else {
+        quote! { & #lifetime #data_type }
+    };
+
+    if let Some(via) = &field.via {
+        let via = fident!(via);
+        quote! { Via<#via, #reference> }
+    } else if field.optional {
+        quote! { Option<#reference> }
+    } else {
+        reference
+    }
+}
+
+/// Returns the filter type of a query as written in `Query<T, F>`, or `()` if it has none.
+pub fn query_filter_type(query: &QueryDef) -> TokenStream {
+    let filters: Vec<_> = query
+        .filters
+        .iter()
+        .map(|filter| match filter {
+            QueryFilter::Added(component) => {
+                let component = fident!(component);
+                quote! { Added<#component> }
+            }
+            QueryFilter::Changed(component) => {
+                let component = fident!(component);
+                quote! { Changed<#component> }
+            }
+            QueryFilter::With(component) => {
+                let component = fident!(component);
+                quote! { With<#component> }
+            }
+            QueryFilter::Without(component) => {
+                let component = fident!(component);
+                quote! { Without<#component> }
+            }
+        })
+        .collect();
+
+    match filters.as_slice() {
+        [filter] => filter.clone(),
+        _ => quote! { (#(#filters),*) },
+    }
+}
+
+/// The archetype columns a query touches for a single matching entity.
+///
+/// A `Via<R, &T>` field reads the `R` column of the row; the join is left to the caller.
+pub(crate) struct QueryColumns<'q> {
+    query: &'q QueryDef,
+    /// Component columns in query order.
+    fields: Vec<QueryColumn>,
+    /// Tick columns read by filters or written by mutable access, each listed once.
+    ticks: Vec<QueryTicksColumn>,
+    /// Borrow each column explicitly, for a `table` reached through a raw pointer.
+    explicit_borrows: bool,
+}
+
+struct QueryColumn {
+    /// The archetype column, or `None` for an optional component the entity does not have.
+    column: Option<Ident>,
+    mutable: bool,
+    optional: bool,
+}
+
+struct QueryTicksColumn {
+    column: Ident,
+    component: String,
+    mutable: bool,
+}
+
+impl<'q> QueryColumns<'q> {
+    pub(crate) fn new(query: &'q QueryDef, entity: &EntityDef) -> Self {
+        let find_field = |data_type: &str| entity.fields.iter().find(|f| f.data_type == data_type);
+
+        let mut fields = vec![];
+        let mut ticks: Vec<QueryTicksColumn> = vec![];
+
+        for query_field in query.fields.iter() {
+            let field = find_field(query_field.row_component());
+            let joined = query_field.via.is_some();
+
+            fields.push(QueryColumn {
+                column: field.map(|field| fident!(to_plural(field.name.as_str()))),
+                mutable: query_field.mutable && !joined,
+                optional: query_field.optional,
+            });
+
+            if let Some(field) = field
+                && query_field.mutable
+                && !joined
+                && query_field.data_type != "Entity"
+            {
+                ticks.push(QueryTicksColumn {
+                    column: ticks_field_ident(&field.name),
+                    component: query_field.data_type.clone(),
+                    mutable: true,
+                });
+            }
+        }
+
+        for filter in query.filters.iter().filter(|f| f.is_tick_filter()) {
+            if !ticks.iter().any(|t| t.component == filter.component()) {
+                ticks.push(QueryTicksColumn {
+                    column: ticks_field_ident(&find_field(filter.component()).unwrap().name),
+                    component: filter.component().to_string(),
+                    mutable: false,
+                });
+            }
+        }
+
+        Self {
+            query,
+            fields,
+            ticks,
+            explicit_borrows: false,
+        }
+    }
+
+    /// Borrows each column on its own rather than through `table`, for a `table` that is
+    /// the dereference of a raw pointer, such as `(*world).player_entities`.
+    pub(crate) fn with_explicit_borrows(mut self) -> Self {
+        self.explicit_borrows = true;
+        self
+    }
+
+    fn column_rs(&self, table: &TokenStream, column: &Ident, mutable: bool) -> TokenStream {
+        match (self.explicit_borrows, mutable) {
+            (false, _) => quote! { #table.#column },
+            (true, false) => quote! { (&#table.#column) },
+            (true, true) => quote! { (&mut #table.#column) },
+        }
+    }
+
+    fn ticks_binding(index: usize) -> Ident {
+        format_ident!("ticks_{}", index)
+    }
+
+    /// Iterator over the matching rows of `table`, skipping rows rejected by filters and
+    /// stamping the change tick on every mutably accessed component that is yielded.
+    pub(crate) fn iter_rs(&self, table: &TokenStream, parallel: bool) -> TokenStream {
+        let entities = self.column_rs(table, &format_ident!("entities"), false);
+        let field_iters = self.fields.iter().map(|field| {
+            let Some(column) = &field.column else {
+                return if parallel {
+                    quote! { (0..#entities.len()).into_par_iter().map(|_| None) }
+                } else {
+                    quote! { (0..#entities.len()).map(|_| None) }
+                };
+            };
+
+            let column = self.column_rs(table, column, field.mutable);
+            let iter = match (parallel, field.mutable) {
+                (false, true) => quote! { #column.iter_mut() },
+                (false, false) => quote! { #column.iter() },
+                (true, true) => quote! { #column.par_iter_mut() },
+                (true, false) => quote! { #column.par_iter() },
+            };
+
+            if field.optional {
+                quote! { #iter.map(Some) }
+            } else {
+                iter
+            }
+        });
+
+        let zip_rs = |iters: Vec<TokenStream>| {
+            if parallel {
+                quote! { izip_par!(#(#iters),*) }
+            } else {
+                quote! { izip!(#(#iters),*) }
+            }
+        };
+
+        if self.ticks.is_empty() {
+            return zip_rs(field_iters.collect());
+        }
+
+        let ticks_iters = self.ticks.iter().map(|ticks| {
+            let column = self.column_rs(table, &ticks.column, ticks.mutable);
+            match (parallel, ticks.mutable) {
+                (false, true) => quote! { #column.iter_mut() },
+                (false, false) => quote! { #column.iter() },
+                (true, true) => quote! { #column.par_iter_mut() },
+                (true, false) => quote! { #column.par_iter() },
+            }
+        });
+
+        let field_bindings: Vec<_> = (0..self.fields.len())
+            .map(|index| format_ident!("column_{}", index))
+            .collect();
+        let ticks_bindings: Vec<_> = (0..self.ticks.len()).map(Self::ticks_binding).collect();
+
+        let zipped = zip_rs(field_iters.chain(ticks_iters).collect());
+
+        let filter_rs = if !self.query.has_tick_filters() {
+            quote! {}
+        } else {
+            let checks = self.query.filters.iter().filter(|f| f.is_tick_filter()).map(|filter| {
+                let position = self
+                    .ticks
+                    .iter()
+                    .position(|t| t.component == filter.component())
+                    .unwrap();
+                let binding = Self::ticks_binding(position);
+
+                match filter {
+                    QueryFilter::Added(_) => quote! { #binding.is_added(system_ticks.last_run) },
+                    _ => quote! { #binding.is_changed(system_ticks.last_run) },
+                }
+            });
+
+            quote! {
+                .filter(move |(#(#field_bindings,)* #(#ticks_bindings),*)| #(#checks)&&*)
+            }
+        };
+
+        let mark_rs = self
+            .ticks
+            .iter()
+            .enumerate()
+            .filter(|(_, ticks)| ticks.mutable)
+            .map(|(index, _)| {
+                let binding = Self::ticks_binding(index);
+                quote! { #binding.changed = system_ticks.this_run; }
+            });
+
+        quote! {
+            {
+                let system_ticks = #table.system_ticks;
+                #zipped
+                    #filter_rs
+                    .map(move |(#(#field_bindings,)* #(#ticks_bindings),*)| {
+                        #(#mark_rs)*
+                        (#(#field_bindings),*)
+                    })
+            }
+        }
+    }
+
+    /// Fetches the row at `index` of `table`, stamping the change tick on mutably accessed components.
+    pub(crate) fn get_rs(&self, table: &TokenStream) -> TokenStream {
+        let get_quotes = self.fields.iter().map(|field| {
+            let Some(column) = &field.column else {
+                return quote! { None };
+            };
+
+            let column = self.column_rs(table, column, field.mutable);
+            match (field.optional, field.mutable) {
+                (false, true) => quote! { #column.get_mut(index)? },
+                (false, false) => quote! { #column.get(index)? },
+                (true, true) => quote! { #column.get_mut(index) },
+                (true, false) => quote! { #column.get(index) },
+            }
+        });
+
+        let mark_rs = self.ticks.iter().filter(|t| t.mutable).map(|ticks| {
+            let column = self.column_rs(table, &ticks.column, true);
+            quote! {
+                if let Some(ticks) = #column.get_mut(index) {
+                    ticks.changed = #table.system_ticks.this_run;
+                }
+            }
+        });
+
+        quote! {
+            #(#mark_rs)*
+            Some((#(#get_quotes),*))
+        }
+    }
+
+    /// Statements stamping the change tick on every row of the mutably accessed columns of
+    /// `table`, and the columns as one tuple of slices. Filters are not applied.
+    pub(crate) fn chunk_rs(&self, table: &TokenStream) -> (TokenStream, TokenStream) {
+        let slices = self.fields.iter().map(|field| {
+            let Some(column) = &field.column else {
+                return quote! { None };
+            };
+
+            let slice = if field.mutable {
+                quote! { #table.#column.as_mut_slice() }
+            } else {
+                quote! { #table.#column.as_slice() }
+            };
+
+            if field.optional {
+                quote! { Some(#slice) }
+            } else {
+                slice
+            }
+        });
+
+        let mark_rs = self.ticks.iter().filter(|t| t.mutable).map(|ticks| {
+            let column = &ticks.column;
+            quote! {
+                for ticks in #table.#column.iter_mut() {
+                    ticks.changed = #table.system_ticks.this_run;
+                }
+            }
+        });
+
+        (quote! { #(#mark_rs)* }, quote! { (#(#slices),*) })
+    }
+
+    /// Boolean expression testing the query filters against the row `index` of `table`.
+    ///
+    /// Rows without ticks have not been merged into a live world yet and always pass.
+    pub(crate) fn filter_check_rs(&self, table: &TokenStream) -> TokenStream {
+        if !self.query.has_tick_filters() {
+            return quote! { true };
+        }
+
+        let checks = self.query.filters.iter().filter(|f| f.is_tick_filter()).map(|filter| {
+            let column = &self
+                .ticks
+                .iter()
+                .find(|t| t.component == filter.component())
+                .unwrap()
+                .column;
+            let column = self.column_rs(table, column, false);
+
+            match filter {
+                QueryFilter::Added(_) => quote! {
+                    #column.get(index).is_none_or(|ticks| ticks.is_added(#table.system_ticks.last_run))
+                },
+                _ => quote! {
+                    #column.get(index).is_none_or(|ticks| ticks.is_changed(#table.system_ticks.last_run))
+                },
+            }
+        });
+
+        quote! { #(#checks)&&* }
+    }
+}
@@ -0,0 +1,168 @@
+use crate::{
+    CollectedData, EntityDef, QueryColumns, QueryDef, fident, query_field_type, query_filter_type,
+    write_token_stream_to_file,
+};
+use heck::ToSnakeCase;
+use inflector::string::pluralize::to_plural;
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+
+/// Implements query access through a `WorldCell` for every query without `Via` joins.
+///
+/// The generated code reaches the columns through a raw `World` pointer and only ever borrows
+/// the columns a query names, so systems running side by side never hold overlapping
+/// references.
+pub fn generate_cell_queries(out_dir: &str, include_files: &mut Vec<String>, collected: &CollectedData) {
+    const FILE_NAME: &str = "cell_queries.rs";
+
+    let mut code_rs = vec![];
+
+    code_rs.push(quote! {
+        pub trait QueryCellFrom<'a, T, F = ()>
+        where
+            T: 'a + Send
+        {
+            /// The components whose columns the query reads, including those of its
+            /// `Added`/`Changed` filters.
+            const READS: &'static [&'static str];
+            /// The components whose columns the query writes.
+            const WRITES: &'static [&'static str];
+
+            /// # Safety
+            /// `world` must be valid for `'a`, and while the items are alive nothing else may
+            /// write the columns of `READS` or access the columns of `WRITES`.
+            unsafe fn cell_query_from(world: *mut World) -> impl Iterator<Item = T> + 'a;
+
+            /// # Safety
+            /// As for `cell_query_from`.
+            unsafe fn cell_par_query_from(world: *mut World) -> impl ParallelIterator<Item = T> + 'a;
+
+            /// # Safety
+            /// As for `cell_query_from`.
+            unsafe fn cell_get_from(world: *mut World, index: usize, entity_type: EntityType) -> Option<T>;
+        }
+    });
+
+    for query in collected.queries.iter().filter(|query| !query.has_via()) {
+        code_rs.push(query_cell_rs(query, collected));
+    }
+
+    let code_rs = quote! {
+        #(#code_rs)*
+    };
+
+    include_files.push(write_token_stream_to_file(out_dir, FILE_NAME, &code_rs.to_string()));
+}
+
+fn query_cell_rs(query: &QueryDef, collected: &CollectedData) -> TokenStream {
+    let data_types = query.fields.iter().map(|field| query_field_type(field, &quote! { 'a }));
+    let query_type = quote! { (#(#data_types),*) };
+    let filter_type = query_filter_type(query);
+
+    let (reads, writes) = query_access(query);
+
+    let matching_entities: Vec<&EntityDef> = collected
+        .entities
+        .iter()
+        .filter(|entity| query.matches(entity))
+        .collect();
+
+    let mut iters_rs = vec![];
+    let mut par_iters_rs = vec![];
+    let mut match_get_rs = vec![];
+
+    for entity in matching_entities {
+        let entity_name = fident!(entity.name);
+        let archetype_field_name = fident!(to_plural(&entity.name.to_snake_case()));
+        let table = quote! { (*world).#archetype_field_name };
+
+        let columns = QueryColumns::new(query, entity).with_explicit_borrows();
+        let get_rs = columns.get_rs(&table);
+
+        let get_guard_rs = if !query.has_tick_filters() {
+            quote! {}
+        } else {
+            let filter_check_rs = columns.filter_check_rs(&table);
+            quote! {
+                if !(#filter_check_rs) {
+                    return None;
+                }
+            }
+        };
+
+        iters_rs.push(columns.iter_rs(&table, false));
+        par_iters_rs.push(columns.iter_rs(&table, true));
+        match_get_rs.push(quote! {
+            EntityType::#entity_name => {
+                #get_guard_rs
+                #get_rs
+            }
+        });
+    }
+
+    // Columns are borrowed explicitly, as implicit autorefs through a raw pointer are denied.
+    quote! {
+        #[allow(
+            unused_parens,
+            unused_variables,
+            clippy::needless_borrow,
+            clippy::needless_question_mark,
+            clippy::double_parens
+        )]
+        impl<'a> QueryCellFrom<'a, #query_type, #filter_type> for World {
+            const READS: &'static [&'static str] = &[#(#reads),*];
+            const WRITES: &'static [&'static str] = &[#(#writes),*];
+
+            unsafe fn cell_query_from(world: *mut World) -> impl Iterator<Item = #query_type> + 'a {
+                unsafe { chain!(#(#iters_rs),*) }
+            }
+
+            unsafe fn cell_par_query_from(world: *mut World) -> impl ParallelIterator<Item = #query_type> + 'a {
+                unsafe { chain_par!(#(#par_iters_rs),*) }
+            }
+
+            #[allow(unreachable_patterns, clippy::match_single_binding)]
+            unsafe fn cell_get_from(world: *mut World, index: usize, entity_type: EntityType) -> Option<#query_type> {
+                unsafe {
+                    match entity_type {
+                        #(#match_get_rs)*
+                        _ => None,
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// The components a query reads and writes, each listed once.
+///
+/// `Added`/`Changed` filters read the tick column of their component, so they count as
+/// reads. `With`/`Without` only look at which columns an archetype has, which never changes.
+pub(crate) fn query_access(query: &QueryDef) -> (Vec<String>, Vec<String>) {
+    let mut reads: Vec<String> = vec![];
+    let mut writes: Vec<String> = vec![];
+
+    for field in query.fields.iter() {
+        if let Some(reference) = &field.via {
+            reads.push(reference.clone());
+        }
+
+        if field.mutable {
+            writes.push(field.data_type.clone());
+        } else {
+            reads.push(field.data_type.clone());
+        }
+    }
+
+    for filter in query.filters.iter().filter(|filter| filter.is_tick_filter()) {
+        reads.push(filter.component().to_string());
+    }
+
+    reads.retain(|component| !writes.contains(component));
+    reads.sort();
+    reads.dedup();
+    writes.sort();
+    writes.dedup();
+
+    (reads, writes)
+}
@@ -0,0 +1,114 @@
+use crate::{
+    CollectedData, EntityDef, QueryColumns, QueryDef, fident, query_field_type, query_filter_type,
+    write_token_stream_to_file,
+};
+use heck::ToSnakeCase;
+use inflector::string::pluralize::to_plural;
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+
+/// Implements chunked access for the queries whose rows map to whole archetype columns.
+///
+/// Queries with `Added`/`Changed` filters skip rows and `Via` joins look up other entities,
+/// so neither can be handed out as column slices.
+pub fn generate_query_chunks(out_dir: &str, include_files: &mut Vec<String>, collected: &CollectedData) {
+    const FILE_NAME: &str = "query_chunks.rs";
+
+    let mut code_rs = vec![];
+
+    code_rs.push(quote! {
+        pub trait ChunksFrom<'a, T, F = ()>
+        where
+            T: 'a + Send
+        {
+            type Chunk: 'a + Send;
+
+            fn chunks_from(&'a self) -> impl Iterator<Item = Self::Chunk>;
+        }
+
+        pub trait ChunksMutFrom<'a, T, F = ()>
+        where
+            T: 'a + Send
+        {
+            type Chunk: 'a + Send;
+
+            fn chunks_mut_from(&'a mut self) -> impl Iterator<Item = Self::Chunk>;
+        }
+    });
+
+    for query in collected
+        .queries
+        .iter()
+        .filter(|query| !query.has_tick_filters() && !query.has_via())
+    {
+        code_rs.push(query_chunks_rs(query, collected));
+    }
+
+    let code_rs = quote! {
+        #(#code_rs)*
+    };
+
+    include_files.push(write_token_stream_to_file(out_dir, FILE_NAME, &code_rs.to_string()));
+}
+
+fn query_chunks_rs(query: &QueryDef, collected: &CollectedData) -> TokenStream {
+    let mutable = query.is_mutable();
+
+    let data_types = query.fields.iter().map(|field| query_field_type(field, &quote! { 'a }));
+    let query_type = quote! { (#(#data_types),*) };
+    let filter_type = query_filter_type(query);
+
+    let chunk_types = query.fields.iter().map(|field| {
+        let data_type = fident!(field.data_type);
+        let slice = if field.mutable {
+            quote! { &'a mut [#data_type] }
+        } else {
+            quote! { &'a [#data_type] }
+        };
+
+        if field.optional {
+            quote! { Option<#slice> }
+        } else {
+            slice
+        }
+    });
+    let chunk_type = quote! { (#(#chunk_types),*) };
+
+    let matching_entities: Vec<&EntityDef> = collected
+        .entities
+        .iter()
+        .filter(|entity| query.matches(entity))
+        .collect();
+
+    let push_chunks_rs = matching_entities.iter().map(|entity| {
+        let archetype_field_name = fident!(to_plural(&entity.name.to_snake_case()));
+        let (mark_rs, chunk_rs) = QueryColumns::new(query, entity).chunk_rs(&quote! { self.#archetype_field_name });
+
+        quote! {
+            if !self.#archetype_field_name.entities.is_empty() {
+                #mark_rs
+                chunks.push(#chunk_rs);
+            }
+        }
+    });
+
+    let (chunks_trait, chunks_fn, self_type) = if mutable {
+        (quote! { ChunksMutFrom }, quote! { chunks_mut_from }, quote! { &'a mut self })
+    } else {
+        (quote! { ChunksFrom }, quote! { chunks_from }, quote! { &'a self })
+    };
+
+    quote! {
+        #[allow(unused_parens, clippy::double_parens)]
+        impl<'a> #chunks_trait<'a, #query_type, #filter_type> for World {
+            type Chunk = #chunk_type;
+
+            fn #chunks_fn(#self_type) -> impl Iterator<Item = Self::Chunk> {
+                #[allow(unused_mut)]
+                let mut chunks: Vec<Self::Chunk> = Vec::new();
+                #(#push_chunks_rs)*
+                chunks.into_iter()
+            }
+        }
+    }
+}
@@ -8,21 +8,121 @@ pub fn generate_default_queries(out_dir: &str) -> String {
         use std::marker::PhantomData;
 
         #[derive(Default, Debug)]
-        pub struct Query<T> {
+        pub struct Query<T, F = ()> {
+            phantom: PhantomData<(T, F)>,
+        }
+
+        /// Query filter matching rows whose `T` was added since the system last ran.
+        #[derive(Default, Debug)]
+        pub struct Added<T> {
             phantom: PhantomData<T>,
         }
-        
-        pub struct WithQuery<'a, T> {
-            query: Query<T>,
+
+        /// Query filter matching rows whose `T` was added or mutably accessed since the system last ran.
+        #[derive(Default, Debug)]
+        pub struct Changed<T> {
+            phantom: PhantomData<T>,
+        }
+
+        /// Query filter matching rows of entities that have a `T` component, without borrowing it.
+        #[derive(Default, Debug)]
+        pub struct With<T> {
+            phantom: PhantomData<T>,
+        }
+
+        /// Query filter matching rows of entities that have no `T` component.
+        #[derive(Default, Debug)]
+        pub struct Without<T> {
+            phantom: PhantomData<T>,
+        }
+
+        /// Query element following the `R` component of a row, an `Option<Entity>`, to the
+        /// entity it points at and fetching `T` from there, e.g. `Via<NodeEntityRef, &NodeComponent>`.
+        ///
+        /// Derefs to `None` if the reference is empty or stale, or the target has no such
+        /// component. While iterating, a target reached through `&mut` from several rows is only
+        /// handed to the first of them.
+        pub struct Via<R, T> {
+            target: Option<T>,
+            phantom: PhantomData<fn() -> R>,
+        }
+
+        impl<R, T> Via<R, T> {
+            pub fn new(target: Option<T>) -> Self {
+                Self {
+                    target,
+                    phantom: PhantomData,
+                }
+            }
+
+            pub fn into_inner(self) -> Option<T> {
+                self.target
+            }
+        }
+
+        impl<R, T> std::ops::Deref for Via<R, T> {
+            type Target = Option<T>;
+
+            fn deref(&self) -> &Option<T> {
+                &self.target
+            }
+        }
+
+        impl<R, T> std::ops::DerefMut for Via<R, T> {
+            fn deref_mut(&mut self) -> &mut Option<T> {
+                &mut self.target
+            }
+        }
+
+        impl<R, T: std::fmt::Debug> std::fmt::Debug for Via<R, T> {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.debug_tuple("Via").field(&self.target).finish()
+            }
+        }
+
+        /// Ticks at which a single component value was added and last mutably accessed.
+        #[derive(Clone, Copy, Debug, Default, PartialEq)]
+        pub struct ComponentTicks {
+            pub added: u64,
+            pub changed: u64,
+        }
+
+        impl ComponentTicks {
+            pub fn new(tick: u64) -> Self {
+                Self {
+                    added: tick,
+                    changed: tick,
+                }
+            }
+
+            /// A system that has never run sees every component as added.
+            pub fn is_added(&self, last_run: Option<u64>) -> bool {
+                last_run.is_none_or(|last_run| self.added > last_run)
+            }
+
+            pub fn is_changed(&self, last_run: Option<u64>) -> bool {
+                last_run.is_none_or(|last_run| self.changed > last_run)
+            }
+        }
+
+        /// The tick of the running system and the tick at which it last ran.
+        #[derive(Clone, Copy, Debug, Default, PartialEq)]
+        pub struct SystemTicks {
+            pub this_run: u64,
+            pub last_run: Option<u64>,
+        }
+
+        pub struct WithQuery<'a, T, F = ()> {
+            query: Query<T, F>,
             world: &'a World,
         }
-        
-        pub struct WithQueryMut<'a, T> {
-            query: Query<T>,
+
+        pub struct WithQueryMut<'a, T, F = ()> {
+            query: Query<T, F>,
             world: &'a mut World,
         }
 
-        impl<T> Clone for Query<T> {
+        impl<T, F> Clone for Query<T, F> {
             fn clone(&self) -> Self {
                 Query {
                     phantom: PhantomData,
@@ -31,105 +131,105 @@ pub fn generate_default_queries(out_dir: &str) -> String {
         }
 
         #[allow(dead_code)]
-        impl<T> Query<T> {
-            pub fn new() -> Query<T> {
+        impl<T, F> Query<T, F> {
+            pub fn new() -> Query<T, F> {
                 Query {
                     phantom: PhantomData,
                 }
             }
         }
 
-        impl<'a, T: 'a + Send> Query<T>
+        impl<'a, T: 'a + Send, F: 'a> Query<T, F>
         {
             pub fn iter(&self, world: &'a World) -> impl Iterator<Item = T> + 'a
             where
-                World: QueryFrom<'a, T>,
+                World: QueryFrom<'a, T, F>,
             {
-                world.query_from()
+                <World as QueryFrom<'a, T, F>>::query_from(world)
             }
         }
         
-        impl<'a, T: 'a + Send> Query<T>
+        impl<'a, T: 'a + Send, F: 'a> Query<T, F>
         {
             pub fn par_iter(&self, world: &'a World) -> impl ParallelIterator<Item = T> + 'a
             where
-                World: QueryFrom<'a, T>,
+                World: QueryFrom<'a, T, F>,
             {
-                world.par_query_from()
+                <World as QueryFrom<'a, T, F>>::par_query_from(world)
             }
         }
         
-        impl<'a, T: 'a + Send> Query<T> {
+        impl<'a, T: 'a + Send, F: 'a> Query<T, F> {
             pub fn iter_mut(&self, world: &'a mut World) -> impl Iterator<Item = T> + 'a
             where
-                World: QueryMutFrom<'a, T>,
+                World: QueryMutFrom<'a, T, F>,
             {
-                world.query_mut_from()
+                <World as QueryMutFrom<'a, T, F>>::query_mut_from(world)
             }
         }
         
-        impl<'a, T: 'a + Send> Query<T>
+        impl<'a, T: 'a + Send, F: 'a> Query<T, F>
         {
             pub fn par_iter_mut(&self, world: &'a mut World) -> impl ParallelIterator<Item = T> + 'a
             where
-                World: QueryMutFrom<'a, T>,
+                World: QueryMutFrom<'a, T, F>,
             {
-                world.par_query_mut_from()
+                <World as QueryMutFrom<'a, T, F>>::par_query_mut_from(world)
             }
         }
         
-        impl<'a, T: 'a + Send> Query<T> {
+        impl<'a, T: 'a + Send, F: 'a> Query<T, F> {
             pub fn get(&self, world: &'a World, index: usize, entity_type: EntityType) -> Option<T>
             where
-                World: QueryFrom<'a, T>,
+                World: QueryFrom<'a, T, F>,
             {
-                world.get_from(index, entity_type)
+                <World as QueryFrom<'a, T, F>>::get_from(world, index, entity_type)
             }
         }
 
-        impl<'a, T: 'a + Send> Query<T> {
+        impl<'a, T: 'a + Send, F: 'a> Query<T, F> {
             pub fn get_mut(&self, world: &'a mut World, index: usize, entity_type: EntityType) -> Option<T>
             where
-                World: QueryMutFrom<'a, T>,
+                World: QueryMutFrom<'a, T, F>,
             {
-                world.get_mut_from(index, entity_type)
+                <World as QueryMutFrom<'a, T, F>>::get_mut_from(world, index, entity_type)
             }
         }
 
         // Implement len
-        impl<'a, T: 'a + Send> Query<T> {
+        impl<'a, T: 'a + Send, F: 'a> Query<T, F> {
             pub fn len(&self, world: &'a World) -> usize
             where
-                World: LenFrom<'a, T>,
+                World: LenFrom<'a, T, F>,
             {
-                world.len()
+                <World as LenFrom<'a, T, F>>::len(world)
             }
         }
 
         // Impl at_mut
-        impl<'a, T: 'a + Send> Query<T> {
+        impl<'a, T: 'a + Send, F: 'a> Query<T, F> {
             pub fn at_mut(&self, world: &'a mut World, index: usize) -> Option<T>
             where
-                World: QueryMutFrom<'a, T>,
+                World: QueryMutFrom<'a, T, F>,
             {
-                world.at_mut(index)
+                <World as QueryMutFrom<'a, T, F>>::at_mut(world, index)
             }
         }
 
         // Impl at
-        impl<'a, T: 'a + Send> Query<T> {
+        impl<'a, T: 'a + Send, F: 'a> Query<T, F> {
             pub fn at(&self, world: &'a World, index: usize) -> Option<T>
             where
-                World: QueryFrom<'a, T>,
+                World: QueryFrom<'a, T, F>,
             {
-                world.at(index)
+                <World as QueryFrom<'a, T, F>>::at(world, index)
             }
         }
         
         #[allow(dead_code)]
-        impl<'a, T> WithQuery<'a, T>
-            where World: QueryFrom<'a, T>,
-                World: LenFrom<'a, T>,
+        impl<'a, T, F: 'a> WithQuery<'a, T, F>
+            where World: QueryFrom<'a, T, F>,
+                World: LenFrom<'a, T, F>,
                 T: 'a + Send,
         {
             pub fn iter(&'a self) -> impl Iterator<Item = T> + 'a {
@@ -140,8 +240,8 @@ pub fn generate_default_queries(out_dir: &str) -> String {
                 self.query.par_iter(self.world)
             }
             
-            pub fn get(&'a self, id: Uuid) -> Option<T> {
-                if let Some(&(index, entity_type)) = self.world.index_lookup.get(&id) {
+            pub fn get(&'a self, handle: impl EntityHandle) -> Option<T> {
+                if let Some((index, entity_type)) = handle.locate(self.world) {
                     self.query.get(self.world, index, entity_type)
                 } else {
                     None
@@ -162,9 +262,34 @@ pub fn generate_default_queries(out_dir: &str) -> String {
         }
 
         #[allow(dead_code)]
-        impl<'a, T> WithQueryMut<'a, T>
-            where World: QueryMutFrom<'a, T>,
-                World: LenFrom<'a, T>,
+        impl<'a, T, F: 'a> WithQuery<'a, T, F>
+            where World: ChunksFrom<'a, T, F>,
+                T: 'a + Send,
+        {
+            /// The matching rows as column slices, one chunk per archetype with any rows,
+            /// for loops that vectorize or split the work by slice.
+            pub fn chunks(&'a self) -> impl Iterator<Item = <World as ChunksFrom<'a, T, F>>::Chunk> + 'a {
+                <World as ChunksFrom<'a, T, F>>::chunks_from(self.world)
+            }
+        }
+
+        #[allow(dead_code)]
+        impl<'a, T, F: 'a> WithQueryMut<'a, T, F>
+            where World: ChunksMutFrom<'a, T, F>,
+                T: 'a + Send,
+        {
+            /// Like `WithQuery::chunks`, with mutable slices for the `&mut` components.
+            ///
+            /// Every row of a mutable slice counts as changed, whether it is written or not.
+            pub fn chunks_mut(&'a mut self) -> impl Iterator<Item = <World as ChunksMutFrom<'a, T, F>>::Chunk> + 'a {
+                <World as ChunksMutFrom<'a, T, F>>::chunks_mut_from(self.world)
+            }
+        }
+
+        #[allow(dead_code)]
+        impl<'a, T, F: 'a> WithQueryMut<'a, T, F>
+            where World: QueryMutFrom<'a, T, F>,
+                World: LenFrom<'a, T, F>,
                 T: 'a + Send,
         {
             pub fn iter_mut(&'a mut self) -> impl Iterator<Item = T> + 'a {
@@ -175,8 +300,8 @@ pub fn generate_default_queries(out_dir: &str) -> String {
                 self.query.par_iter_mut(self.world)
             }
             
-            pub fn get_mut(&'a mut self, id: Uuid) -> Option<T> {
-                if let Some(&(index, entity_type)) = self.world.index_lookup.get(&id) {
+            pub fn get_mut(&'a mut self, handle: impl EntityHandle) -> Option<T> {
+                if let Some((index, entity_type)) = handle.locate(self.world) {
                     self.query.get_mut(self.world, index, entity_type)
                 } else {
                     None
@@ -198,9 +323,9 @@ pub fn generate_default_queries(out_dir: &str) -> String {
         
         #[allow(dead_code)]
         impl World {
-            pub fn with_query<'a, T: 'a + Send>(&'a self, query: Query<T>) -> WithQuery<'a, T>
+            pub fn with_query<'a, T: 'a + Send, F>(&'a self, query: Query<T, F>) -> WithQuery<'a, T, F>
             where
-                World: QueryFrom<'a, T>,
+                World: QueryFrom<'a, T, F>,
             {
                 WithQuery {
                     query,
@@ -211,9 +336,9 @@ pub fn generate_default_queries(out_dir: &str) -> String {
 
         #[allow(dead_code)]
         impl World {
-            pub fn with_query_mut<'a, T: 'a + Send>(&'a mut self, query: Query<T>) -> WithQueryMut<'a, T>
+            pub fn with_query_mut<'a, T: 'a + Send, F>(&'a mut self, query: Query<T, F>) -> WithQueryMut<'a, T, F>
             where
-                World: QueryMutFrom<'a, T>,
+                World: QueryMutFrom<'a, T, F>,
             {
                 WithQueryMut {
                     query,
@@ -221,6 +346,201 @@ pub fn generate_default_queries(out_dir: &str) -> String {
                 }
             }
         }
+
+        /// World access for systems in a parallel group that may run side by side.
+        ///
+        /// A cell made with `new` owns the world like a `&mut World`. The group runner splits
+        /// one cell per system off a batch's cell, each limited to the components and resources
+        /// the system declares through its parameters; the scheduler only runs systems side by
+        /// side when those don't conflict. Touching anything else through a split cell panics.
+        ///
+        /// Queries through a cell only borrow the columns they name, never the whole world.
+        pub struct WorldCell<'w> {
+            world: *mut World,
+            access: Option<&'static CellAccess>,
+            phantom: PhantomData<&'w mut World>,
+        }
+
+        /// What a system running in a parallel batch declares it touches.
+        #[derive(Debug)]
+        pub struct CellAccess {
+            pub system: &'static str,
+            /// Takes `&World`, so it may read anything.
+            pub reads_world: bool,
+            pub reads: &'static [&'static str],
+            pub writes: &'static [&'static str],
+        }
+
+        // SAFETY: a cell only hands out the columns and resources its access allows, and the
+        // cells split off a batch's cell have been checked not to conflict.
+        unsafe impl Send for WorldCell<'_> {}
+        unsafe impl Sync for WorldCell<'_> {}
+
+        #[allow(dead_code)]
+        impl<'w> WorldCell<'w> {
+            pub fn new(world: &'w mut World) -> Self {
+                Self {
+                    world,
+                    access: None,
+                    phantom: PhantomData,
+                }
+            }
+
+            /// Makes a cell limited to `access`, for a system running alongside others.
+            ///
+            /// # Safety
+            /// `self` must not be used while the cell is alive, and the accesses of all cells
+            /// split off it and alive at the same time must not conflict: a component or
+            /// resource one writes must not be read or written by another, and a cell reading
+            /// the whole world must not run alongside one that writes anything.
+            pub unsafe fn split(&self, access: &'static CellAccess) -> WorldCell<'w> {
+                WorldCell {
+                    world: self.world,
+                    access: Some(access),
+                    phantom: PhantomData,
+                }
+            }
+
+            pub fn world(&self) -> &World {
+                if let Some(access) = self.access {
+                    assert!(
+                        access.reads_world,
+                        "system `{}` reads the world without taking `&World`",
+                        access.system,
+                    );
+                }
+
+                // SAFETY: the cell owns the world, or its system reads it with no writer alongside.
+                unsafe { &*self.world }
+            }
+
+            /// Reads through a query taking no `&mut` components; those go through
+            /// `with_query_mut`, which borrows the cell mutably so the writes can't alias.
+            pub fn with_query<'c, T: 'c + Send, F>(&'c self, _query: Query<T, F>) -> CellQuery<'c, T, F>
+            where
+                World: QueryCellFrom<'c, T, F>,
+            {
+                let writes = <World as QueryCellFrom<'c, T, F>>::WRITES;
+                assert!(
+                    writes.is_empty(),
+                    "`WorldCell::with_query` can't write {writes:?}, use `with_query_mut`",
+                );
+                self.check_access(<World as QueryCellFrom<'c, T, F>>::READS, &[]);
+                CellQuery {
+                    world: self.world,
+                    phantom: PhantomData,
+                }
+            }
+
+            pub fn with_query_mut<'c, T: 'c + Send, F>(&'c mut self, _query: Query<T, F>) -> CellQueryMut<'c, T, F>
+            where
+                World: QueryCellFrom<'c, T, F>,
+            {
+                self.check_access(
+                    <World as QueryCellFrom<'c, T, F>>::READS,
+                    <World as QueryCellFrom<'c, T, F>>::WRITES,
+                );
+                CellQueryMut {
+                    world: self.world,
+                    phantom: PhantomData,
+                }
+            }
+
+            pub fn resource<R: Resource>(&self) -> &R {
+                self.check_access(&[R::NAME], &[]);
+                // SAFETY: only this resource is borrowed, and the access check allows reading it.
+                unsafe { &*R::from_world_ptr(self.world) }
+            }
+
+            pub fn resource_mut<R: Resource>(&mut self) -> &mut R {
+                self.check_access(&[], &[R::NAME]);
+                // SAFETY: only this resource is borrowed, and the access check allows writing it.
+                unsafe { &mut *R::from_world_ptr(self.world) }
+            }
+
+            fn check_access(&self, reads: &[&str], writes: &[&str]) {
+                let Some(access) = self.access else {
+                    return;
+                };
+
+                for component in reads {
+                    assert!(
+                        access.reads_world || access.reads.contains(component) || access.writes.contains(component),
+                        "system `{}` reads `{component}` without declaring it",
+                        access.system,
+                    );
+                }
+
+                for component in writes {
+                    assert!(
+                        access.writes.contains(component),
+                        "system `{}` writes `{component}` without declaring it",
+                        access.system,
+                    );
+                }
+            }
+        }
+
+        /// A query through a `WorldCell`, see `WorldCell::with_query`.
+        pub struct CellQuery<'c, T, F = ()> {
+            world: *mut World,
+            phantom: PhantomData<(&'c World, Query<T, F>)>,
+        }
+
+        #[allow(dead_code)]
+        impl<'c, T: 'c + Send, F> CellQuery<'c, T, F>
+        where
+            World: QueryCellFrom<'c, T, F>,
+        {
+            pub fn iter(&self) -> impl Iterator<Item = T> + 'c {
+                // SAFETY: the cell this came from was checked to allow reading the query's columns.
+                unsafe { <World as QueryCellFrom<'c, T, F>>::cell_query_from(self.world) }
+            }
+
+            pub fn par_iter(&self) -> impl ParallelIterator<Item = T> + 'c {
+                // SAFETY: as for `iter`.
+                unsafe { <World as QueryCellFrom<'c, T, F>>::cell_par_query_from(self.world) }
+            }
+
+            pub fn get(&self, entity: Entity) -> Option<T> {
+                // SAFETY: as for `iter`; no cell writes the id index or the `entities` columns.
+                unsafe {
+                    let (index, entity_type) = World::locate_ptr(self.world, entity)?;
+                    <World as QueryCellFrom<'c, T, F>>::cell_get_from(self.world, index, entity_type)
+                }
+            }
+        }
+
+        /// A mutable query through a `WorldCell`, see `WorldCell::with_query_mut`.
+        pub struct CellQueryMut<'c, T, F = ()> {
+            world: *mut World,
+            phantom: PhantomData<(&'c mut World, Query<T, F>)>,
+        }
+
+        #[allow(dead_code)]
+        impl<'c, T: 'c + Send, F> CellQueryMut<'c, T, F>
+        where
+            World: QueryCellFrom<'c, T, F>,
+        {
+            pub fn iter_mut(&'c mut self) -> impl Iterator<Item = T> + 'c {
+                // SAFETY: the cell this came from is borrowed mutably for `'c` and was checked
+                // to allow the query's columns.
+                unsafe { <World as QueryCellFrom<'c, T, F>>::cell_query_from(self.world) }
+            }
+
+            pub fn par_iter_mut(&'c mut self) -> impl ParallelIterator<Item = T> + 'c {
+                // SAFETY: as for `iter_mut`.
+                unsafe { <World as QueryCellFrom<'c, T, F>>::cell_par_query_from(self.world) }
+            }
+
+            pub fn get_mut(&'c mut self, entity: Entity) -> Option<T> {
+                // SAFETY: as for `iter_mut`; no cell writes the id index or the `entities` columns.
+                unsafe {
+                    let (index, entity_type) = World::locate_ptr(self.world, entity)?;
+                    <World as QueryCellFrom<'c, T, F>>::cell_get_from(self.world, index, entity_type)
+                }
+            }
+        }
     };
     
     write_token_stream_to_file(out_dir, FILE_NAME, &code_rs.to_string())
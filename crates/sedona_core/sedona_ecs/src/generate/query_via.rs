@@ -0,0 +1,421 @@
+use crate::{
+    CollectedData, EntityDef, QueryColumns, QueryDef, QueryDefField, fident, query_field_type,
+    query_filter_type, ticks_field_ident, write_token_stream_to_file,
+};
+use heck::ToSnakeCase;
+use inflector::string::pluralize::to_plural;
+use proc_macro2::{Ident, TokenStream};
+use quote::{format_ident, quote};
+
+/// Implements the queries with `Via<R, &T>` joins for the `World`.
+///
+/// Rows come from the archetypes matching the rest of the query, like any other query. Each
+/// join then looks the `R` reference up in the index and borrows `T` from the archetype of
+/// the target, which is a different column than anything else the query borrows mutably.
+pub fn generate_via_queries(out_dir: &str, include_files: &mut Vec<String>, collected: &CollectedData) {
+    const FILE_NAME: &str = "via_queries.rs";
+
+    let code_rs = collected
+        .queries
+        .iter()
+        .filter(|query| query.has_via())
+        .map(|query| via_query_rs(query, collected));
+
+    let code_rs = quote! {
+        #(#code_rs)*
+    };
+
+    include_files.push(write_token_stream_to_file(out_dir, FILE_NAME, &code_rs.to_string()));
+}
+
+fn via_query_rs(query: &QueryDef, collected: &CollectedData) -> TokenStream {
+    let mutable = query.is_mutable();
+
+    let data_types = query.fields.iter().map(|field| query_field_type(field, &quote! { 'a }));
+    let query_type = quote! { (#(#data_types),*) };
+    let filter_type = query_filter_type(query);
+
+    let matching_entities: Vec<&EntityDef> = collected
+        .entities
+        .iter()
+        .filter(|entity| query.matches(entity))
+        .collect();
+
+    let joins: Vec<Join> = query
+        .fields
+        .iter()
+        .enumerate()
+        .filter(|(_, field)| field.via.is_some())
+        .map(|(position, field)| Join::new(position, field, collected))
+        .collect();
+
+    let row_bindings: Vec<Ident> = (0..query.fields.len())
+        .map(|position| format_ident!("field_{}", position))
+        .collect();
+
+    let joined_row_rs = |inline: bool| {
+        let items = query.fields.iter().zip(row_bindings.iter()).enumerate().map(|(position, (field, binding))| {
+            let Some(join) = joins.iter().find(|join| join.position == position) else {
+                return quote! { #binding };
+            };
+
+            if inline {
+                let target_rs = join.target_rs(&quote! { #binding.0 }, field.mutable.then_some(JoinBorrow::Unique));
+                quote! { Via::new(#target_rs) }
+            } else {
+                let closure = join.closure();
+                quote! { Via::new(#closure(#binding.0)) }
+            }
+        });
+
+        quote! { (#(#items),*) }
+    };
+
+    let iter_joins_rs = joins.iter().map(|join| {
+        let borrow = join.field.mutable.then_some(JoinBorrow::Slots);
+        let bindings_rs = join.bindings_rs(borrow);
+        let target_rs = join.target_rs(&quote! { reference }, borrow);
+        let target_type = query_field_type(
+            &QueryDefField {
+                via: None,
+                ..join.field.clone()
+            },
+            &quote! { 'a },
+        );
+        let closure = join.closure();
+
+        quote! {
+            #bindings_rs
+            let mut #closure = move |reference: Option<Entity>| -> Option<#target_type> {
+                #target_rs
+            };
+        }
+    });
+    let iter_joins_rs = quote! {
+        let index_lookup = &self.index_lookup;
+        #(#iter_joins_rs)*
+    };
+
+    let get_joins_rs = joins
+        .iter()
+        .map(|join| join.bindings_rs(join.field.mutable.then_some(JoinBorrow::Unique)));
+    let get_joins_rs = quote! {
+        let index_lookup = &self.index_lookup;
+        #(#get_joins_rs)*
+    };
+
+    let iter_rows_rs = |parallel: bool| -> Vec<TokenStream> {
+        matching_entities
+            .iter()
+            .map(|entity| {
+                let archetype_field_name = fident!(to_plural(&entity.name.to_snake_case()));
+                QueryColumns::new(query, entity).iter_rs(&quote! { self.#archetype_field_name }, parallel)
+            })
+            .collect()
+    };
+    let iter_rows_rs_sequential = iter_rows_rs(false);
+    let iter_rows_rs_parallel = iter_rows_rs(true);
+    let joined_closure_row_rs = joined_row_rs(false);
+    let joined_inline_row_rs = joined_row_rs(true);
+
+    let match_get_rs = matching_entities.iter().map(|entity| {
+        let entity_name = fident!(entity.name);
+        let archetype_field_name = fident!(to_plural(&entity.name.to_snake_case()));
+        let table = quote! { self.#archetype_field_name };
+        let columns = QueryColumns::new(query, entity);
+        let get_rs = columns.get_rs(&table);
+
+        let get_guard_rs = if !query.has_tick_filters() {
+            quote! {}
+        } else {
+            let filter_check_rs = columns.filter_check_rs(&table);
+            quote! {
+                if !(#filter_check_rs) {
+                    return None;
+                }
+            }
+        };
+
+        quote! {
+            EntityType::#entity_name => {
+                #get_guard_rs
+                #get_rs
+            }
+        }
+    });
+
+    let len_args: Vec<TokenStream> = matching_entities
+        .iter()
+        .map(|entity| {
+            let archetype_field_name = fident!(to_plural(&entity.name.to_snake_case()));
+
+            if !query.has_tick_filters() {
+                quote! { self.#archetype_field_name.len() }
+            } else {
+                let filter_check_rs = QueryColumns::new(query, entity)
+                    .filter_check_rs(&quote! { self.#archetype_field_name });
+
+                quote! {
+                    (0..self.#archetype_field_name.len())
+                        .filter(|&index| #filter_check_rs)
+                        .count()
+                }
+            }
+        })
+        .collect();
+
+    let (query_trait, get_fn, at_fn, iter_fn, par_iter_fn, self_type) = if mutable {
+        (
+            quote! { QueryMutFrom },
+            quote! { get_mut_from },
+            quote! { at_mut },
+            quote! { query_mut_from },
+            quote! { par_query_mut_from },
+            quote! { &'a mut self },
+        )
+    } else {
+        (
+            quote! { QueryFrom },
+            quote! { get_from },
+            quote! { at },
+            quote! { query_from },
+            quote! { par_query_from },
+            quote! { &'a self },
+        )
+    };
+
+    let at_args = matching_entities.iter().zip(len_args.iter()).map(|(entity, len_rs)| {
+        let entity_name = fident!(entity.name);
+        let archetype_field_name = fident!(to_plural(&entity.name.to_snake_case()));
+
+        let row_rs = if !query.has_tick_filters() {
+            quote! {}
+        } else {
+            let filter_check_rs = QueryColumns::new(query, entity)
+                .filter_check_rs(&quote! { self.#archetype_field_name });
+            quote! {
+                let index = (0..self.#archetype_field_name.len())
+                    .filter(|&index| #filter_check_rs)
+                    .nth(index)?;
+            }
+        };
+
+        quote! {
+            {
+                let len = #len_rs;
+                if index < len {
+                    #row_rs
+                    return <World as #query_trait<'a, #query_type, #filter_type>>::#get_fn(self, index, EntityType::#entity_name);
+                }
+                index -= len;
+            }
+        }
+    });
+
+    // Mutable joins hand each target out once, which doesn't split across threads.
+    let par_iter_rs = if joins.iter().any(|join| join.field.mutable) {
+        quote! {
+            <World as #query_trait<'a, #query_type, #filter_type>>::#iter_fn(self)
+                .collect::<Vec<_>>()
+                .into_par_iter()
+        }
+    } else {
+        quote! {
+            #iter_joins_rs
+            chain_par!(#(#iter_rows_rs_parallel),*)
+                .map(move |(#(#row_bindings),*)| #joined_closure_row_rs)
+        }
+    };
+
+    quote! {
+        #[allow(unused_parens, unused_variables, unused_assignments, clippy::identity_op)]
+        impl<'a> LenFrom<'a, #query_type, #filter_type> for World {
+            fn len(&'a self) -> usize {
+                0 #(+ #len_args)*
+            }
+        }
+
+        #[allow(
+            unused_parens,
+            unused_variables,
+            unused_assignments,
+            unused_mut,
+            unreachable_patterns,
+            clippy::match_single_binding,
+            clippy::needless_question_mark,
+            clippy::double_parens
+        )]
+        impl<'a> #query_trait<'a, #query_type, #filter_type> for World {
+            fn #iter_fn(#self_type) -> impl Iterator<Item = #query_type> {
+                #iter_joins_rs
+                chain!(#(#iter_rows_rs_sequential),*)
+                    .map(move |(#(#row_bindings),*)| #joined_closure_row_rs)
+            }
+
+            fn #par_iter_fn(#self_type) -> impl ParallelIterator<Item = #query_type> {
+                #par_iter_rs
+            }
+
+            fn #get_fn(#self_type, index: usize, entity_type: EntityType) -> Option<#query_type> {
+                let (#(#row_bindings),*) = match entity_type {
+                    #(#match_get_rs)*
+                    _ => None,
+                }?;
+
+                #get_joins_rs
+                Some(#joined_inline_row_rs)
+            }
+
+            fn #at_fn(#self_type, index: usize) -> Option<#query_type> {
+                let mut index = index;
+                #(#at_args)*
+                None
+            }
+        }
+    }
+}
+
+/// How a join borrows the component it fetches. `None` borrows it shared.
+#[derive(Clone, Copy, PartialEq)]
+enum JoinBorrow {
+    /// `&mut` into a single row.
+    Unique,
+    /// `&mut` while iterating, each target taken out of a slot at most once.
+    Slots,
+}
+
+/// A `Via<R, &T>` field and the archetypes its target may be found in.
+struct Join<'q> {
+    position: usize,
+    field: &'q QueryDefField,
+    targets: Vec<JoinTarget>,
+}
+
+struct JoinTarget {
+    entity_name: Ident,
+    archetype_field_name: Ident,
+    column: Ident,
+    /// `None` for the `Entity` column, which has no change ticks.
+    ticks_column: Option<Ident>,
+}
+
+impl<'q> Join<'q> {
+    fn new(position: usize, field: &'q QueryDefField, collected: &CollectedData) -> Self {
+        let targets = collected
+            .entities
+            .iter()
+            .filter_map(|entity| {
+                let target_field = entity.fields.iter().find(|f| f.data_type == field.data_type)?;
+
+                Some(JoinTarget {
+                    entity_name: fident!(entity.name),
+                    archetype_field_name: fident!(to_plural(&entity.name.to_snake_case())),
+                    column: fident!(to_plural(target_field.name.as_str())),
+                    ticks_column: (field.data_type != "Entity").then(|| ticks_field_ident(&target_field.name)),
+                })
+            })
+            .collect();
+
+        Self {
+            position,
+            field,
+            targets,
+        }
+    }
+
+    /// The closure following the join while iterating.
+    fn closure(&self) -> Ident {
+        format_ident!("via_{}", self.position)
+    }
+
+    fn binding(&self, target: &JoinTarget, name: &str) -> Ident {
+        format_ident!("via_{}_{}_{}", self.position, target.archetype_field_name, name)
+    }
+
+    /// Borrows the columns of every archetype the join may lead to.
+    fn bindings_rs(&self, borrow: Option<JoinBorrow>) -> TokenStream {
+        let data_type = fident!(self.field.data_type);
+
+        let bindings_rs = self.targets.iter().map(|target| {
+            let archetype_field_name = &target.archetype_field_name;
+            let column = &target.column;
+            let entities = self.binding(target, "entities");
+            let components = self.binding(target, "components");
+
+            let components_rs = match borrow {
+                None => quote! { let #components = &self.#archetype_field_name.#column; },
+                Some(JoinBorrow::Unique) => quote! { let #components = &mut self.#archetype_field_name.#column; },
+                Some(JoinBorrow::Slots) => quote! {
+                    let mut #components: Vec<Option<&'a mut #data_type>> =
+                        self.#archetype_field_name.#column.iter_mut().map(Some).collect();
+                },
+            };
+
+            let ticks_rs = match (&target.ticks_column, borrow) {
+                (Some(ticks_column), Some(_)) => {
+                    let ticks = self.binding(target, "ticks");
+                    let this_run = self.binding(target, "this_run");
+                    quote! {
+                        let #ticks = &mut self.#archetype_field_name.#ticks_column;
+                        let #this_run = self.#archetype_field_name.system_ticks.this_run;
+                    }
+                }
+                _ => quote! {},
+            };
+
+            quote! {
+                let #entities = &self.#archetype_field_name.entities;
+                #components_rs
+                #ticks_rs
+            }
+        });
+
+        quote! { #(#bindings_rs)* }
+    }
+
+    /// Expression fetching the target of `reference`, an `Option<Entity>`, through the
+    /// columns borrowed by `bindings_rs`, stamping its change tick if borrowed mutably.
+    fn target_rs(&self, reference: &TokenStream, borrow: Option<JoinBorrow>) -> TokenStream {
+        let arms_rs = self.targets.iter().map(|target| {
+            let entity_name = &target.entity_name;
+            let entities = self.binding(target, "entities");
+            let components = self.binding(target, "components");
+
+            let fetch_rs = match borrow {
+                None => quote! { #components.get(index) },
+                Some(JoinBorrow::Unique) => quote! { #components.get_mut(index) },
+                Some(JoinBorrow::Slots) => quote! { #components.get_mut(index).and_then(Option::take) },
+            };
+
+            let fetch_rs = match (&target.ticks_column, borrow) {
+                (Some(_), Some(_)) => {
+                    let ticks = self.binding(target, "ticks");
+                    let this_run = self.binding(target, "this_run");
+                    quote! {
+                        {
+                            let component = #fetch_rs;
+                            if component.is_some()
+                                && let Some(ticks) = #ticks.get_mut(index)
+                            {
+                                ticks.changed = #this_run;
+                            }
+                            component
+                        }
+                    }
+                }
+                _ => fetch_rs,
+            };
+
+            quote! {
+                Some((entity, (index, EntityType::#entity_name))) if #entities.get(index) == Some(&entity) => #fetch_rs,
+            }
+        });
+
+        quote! {
+            match #reference.and_then(|entity| Some((entity, *index_lookup.get(&entity.id)?))) {
+                #(#arms_rs)*
+                _ => None,
+            }
+        }
+    }
+}
@@ -0,0 +1,245 @@
+use crate::{CollectedData, fident, write_token_stream_to_file};
+use heck::ToSnakeCase;
+use inflector::string::pluralize::to_plural;
+use quote::{format_ident, quote};
+
+pub fn generate_reflection(out_dir: &str, include_files: &mut Vec<String>, collected: &CollectedData) {
+    const FILE_NAME: &str = "reflect.rs";
+
+    let mut code_rs = vec![];
+    let mut entity_types_rs = vec![];
+    let mut match_name_rs = vec![];
+    let mut match_components_rs = vec![];
+    let mut match_serialize_rs = vec![];
+    let mut match_deserialize_rs = vec![];
+
+    code_rs.push(quote! {
+        /// Name and type of a component field of an entity type.
+        #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+        pub struct ComponentInfo {
+            pub name: &'static str,
+            pub type_name: &'static str,
+        }
+
+        #[derive(Debug)]
+        pub enum ReflectError {
+            StaleEntity { entity: Entity },
+            UnknownComponent { entity_type: EntityType, component: String },
+            Value { component: String, error: String },
+        }
+
+        impl std::fmt::Display for ReflectError {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                match self {
+                    ReflectError::StaleEntity { entity } => {
+                        write!(f, "entity {} is not alive", entity.id())
+                    }
+                    ReflectError::UnknownComponent { entity_type, component } => {
+                        write!(f, "{entity_type:?} has no component `{component}`")
+                    }
+                    ReflectError::Value { component, error } => {
+                        write!(f, "failed to convert `{component}`: {error}")
+                    }
+                }
+            }
+        }
+
+        impl std::error::Error for ReflectError {}
+    });
+
+    for entity in collected.entities.iter() {
+        let entity_name_str = &entity.name;
+        let entity_name = fident!(entity.name);
+        let archetype_field = format_ident!("{}", to_plural(&entity.name.to_snake_case()));
+
+        let component_fields: Vec<_> = entity
+            .fields
+            .iter()
+            .filter(|field| field.data_type != "Entity")
+            .collect();
+
+        let component_infos_rs = component_fields.iter().map(|field| {
+            let name = &field.name;
+            let type_name = &field.data_type;
+            quote! {
+                ComponentInfo { name: #name, type_name: #type_name },
+            }
+        });
+
+        let match_field_serialize_rs = component_fields.iter().map(|field| {
+            let name = &field.name;
+            let column = format_ident!("{}", to_plural(&field.name));
+            quote! {
+                #name => serde::Serialize::serialize(&self.#archetype_field.#column[index], serializer),
+            }
+        });
+
+        let match_field_deserialize_rs = component_fields.iter().map(|field| {
+            let name = &field.name;
+            let column = format_ident!("{}", to_plural(&field.name));
+            let ticks_column = crate::ticks_field_ident(&field.name);
+            quote! {
+                #name => {
+                    let value = serde::Deserialize::deserialize(&mut *deserializer)
+                        .map_err(|error| value_error(error.to_string()))?;
+                    finish(deserializer)?;
+                    self.#archetype_field.#column[index] = value;
+                    if let Some(ticks) = self.#archetype_field.#ticks_column.get_mut(index) {
+                        ticks.changed = self.change_tick;
+                    }
+                }
+            }
+        });
+
+        entity_types_rs.push(quote! { EntityType::#entity_name, });
+
+        match_name_rs.push(quote! {
+            EntityType::#entity_name => #entity_name_str,
+        });
+
+        match_components_rs.push(quote! {
+            EntityType::#entity_name => &[#(#component_infos_rs)*],
+        });
+
+        match_serialize_rs.push(quote! {
+            EntityType::#entity_name => match name {
+                #(#match_field_serialize_rs)*
+                _ => return Err(unknown_component()),
+            },
+        });
+
+        match_deserialize_rs.push(quote! {
+            EntityType::#entity_name => match name {
+                #(#match_field_deserialize_rs)*
+                _ => return Err(unknown_component()),
+            },
+        });
+    }
+
+    code_rs.push(quote! {
+        #[allow(dead_code)]
+        impl EntityType {
+            pub const ALL: &'static [EntityType] = &[#(#entity_types_rs)*];
+
+            pub fn name(&self) -> &'static str {
+                match self {
+                    #(#match_name_rs)*
+                }
+            }
+
+            /// The component fields of the entity type, in declaration order.
+            pub fn components(&self) -> &'static [ComponentInfo] {
+                match self {
+                    #(#match_components_rs)*
+                }
+            }
+
+            pub fn component(&self, name: &str) -> Option<&'static ComponentInfo> {
+                self.components().iter().find(|component| component.name == name)
+            }
+        }
+    });
+
+    code_rs.push(quote! {
+        #[allow(dead_code)]
+        impl World {
+            /// Serializes the component called `name` of `entity` with any serde serializer.
+            pub fn serialize_component<S: serde::Serializer>(
+                &self,
+                entity: Entity,
+                name: &str,
+                serializer: S,
+            ) -> Result<S::Ok, ReflectError> {
+                let (index, entity_type) = entity.locate(self).ok_or(ReflectError::StaleEntity { entity })?;
+                let unknown_component = || ReflectError::UnknownComponent {
+                    entity_type,
+                    component: name.to_string(),
+                };
+
+                let result = match entity_type {
+                    #(#match_serialize_rs)*
+                };
+
+                result.map_err(|error| ReflectError::Value {
+                    component: name.to_string(),
+                    error: error.to_string(),
+                })
+            }
+
+            /// Replaces the component called `name` of `entity` with a value read from any
+            /// serde deserializer, marking it as changed.
+            pub fn deserialize_component<'de, D>(
+                &mut self,
+                entity: Entity,
+                name: &str,
+                deserializer: &mut D,
+            ) -> Result<(), ReflectError>
+            where
+                for<'a> &'a mut D: serde::Deserializer<'de>,
+            {
+                self.deserialize_component_then(entity, name, deserializer, |_| Ok(()))
+            }
+
+            /// Like `deserialize_component`, running `finish` on the deserializer once the
+            /// value is read. The component is only replaced if `finish` succeeds.
+            fn deserialize_component_then<'de, D>(
+                &mut self,
+                entity: Entity,
+                name: &str,
+                deserializer: &mut D,
+                finish: impl FnOnce(&mut D) -> Result<(), ReflectError>,
+            ) -> Result<(), ReflectError>
+            where
+                for<'a> &'a mut D: serde::Deserializer<'de>,
+            {
+                let (index, entity_type) = entity.locate(self).ok_or(ReflectError::StaleEntity { entity })?;
+                let unknown_component = || ReflectError::UnknownComponent {
+                    entity_type,
+                    component: name.to_string(),
+                };
+                let value_error = |error: String| ReflectError::Value {
+                    component: name.to_string(),
+                    error,
+                };
+
+                match entity_type {
+                    #(#match_deserialize_rs)*
+                }
+
+                Ok(())
+            }
+
+            /// Returns the component called `name` of `entity` as RON text.
+            pub fn component_ron(&self, entity: Entity, name: &str) -> Result<String, ReflectError> {
+                let mut ron = String::new();
+                let mut serializer = sedona_ecs::ron::Serializer::new(&mut ron, None).map_err(|error| ReflectError::Value {
+                    component: name.to_string(),
+                    error: error.to_string(),
+                })?;
+                self.serialize_component(entity, name, &mut serializer)?;
+                Ok(ron)
+            }
+
+            /// Replaces the component called `name` of `entity` with the value in the RON text.
+            pub fn set_component_ron(&mut self, entity: Entity, name: &str, ron: &str) -> Result<(), ReflectError> {
+                let value_error = |error: sedona_ecs::ron::error::SpannedError| ReflectError::Value {
+                    component: name.to_string(),
+                    error: error.to_string(),
+                };
+
+                let mut deserializer = sedona_ecs::ron::Deserializer::from_str(ron).map_err(value_error)?;
+                self.deserialize_component_then(entity, name, &mut deserializer, |deserializer| {
+                    deserializer
+                        .end()
+                        .map_err(|error| value_error(deserializer.span_error(error)))
+                })
+            }
+        }
+    });
+
+    let code_rs = quote! {
+        #(#code_rs)*
+    };
+
+    include_files.push(write_token_stream_to_file(out_dir, FILE_NAME, &code_rs.to_string()));
+}
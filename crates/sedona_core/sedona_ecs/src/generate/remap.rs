@@ -0,0 +1,174 @@
+use crate::{CollectedData, components_with_entity_refs, fident, type_mentions, write_token_stream_to_file};
+use heck::ToSnakeCase;
+use inflector::string::pluralize::to_plural;
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+
+/// A struct member by name, or by index for tuple structs.
+fn member_rs(member: &str) -> TokenStream {
+    match member.parse::<usize>() {
+        Ok(index) => {
+            let index = syn::Index::from(index);
+            quote! { #index }
+        }
+        Err(_) => {
+            let member = fident!(member);
+            quote! { #member }
+        }
+    }
+}
+
+pub fn generate_remap(out_dir: &str, include_files: &mut Vec<String>, collected: &CollectedData) {
+    const FILE_NAME: &str = "remap.rs";
+
+    let ref_components = components_with_entity_refs(&collected.components);
+    let mut ref_names: Vec<&str> = ref_components.iter().map(String::as_str).collect();
+    ref_names.push("Entity");
+
+    let mut code_rs = vec![];
+    let mut world_map_rs = vec![];
+
+    code_rs.push(quote! {
+        /// Rewrites the entity ids held by a value.
+        ///
+        /// Implemented for `Entity`, `Option` and `Vec` of implementors, and every `#[component]`
+        /// and `#[resource]` with a field of those types. References to ids missing from the map are left alone.
+        pub trait MapEntities {
+            fn map_entities(&mut self, map: &HashMap<Uuid, Uuid>);
+        }
+
+        impl MapEntities for Entity {
+            fn map_entities(&mut self, map: &HashMap<Uuid, Uuid>) {
+                if let Some(id) = map.get(&self.id) {
+                    self.id = *id;
+                }
+            }
+        }
+
+        impl<T: MapEntities> MapEntities for Option<T> {
+            fn map_entities(&mut self, map: &HashMap<Uuid, Uuid>) {
+                if let Some(value) = self {
+                    value.map_entities(map);
+                }
+            }
+        }
+
+        impl<T: MapEntities> MapEntities for Vec<T> {
+            fn map_entities(&mut self, map: &HashMap<Uuid, Uuid>) {
+                for value in self.iter_mut() {
+                    value.map_entities(map);
+                }
+            }
+        }
+    });
+
+    for component in collected
+        .components
+        .iter()
+        .filter(|component| ref_components.contains(&component.name))
+    {
+        let component_name = fident!(component.name);
+        let members = component
+            .fields
+            .iter()
+            .filter(|field| field.mentions(&ref_names))
+            .map(|field| member_rs(&field.member));
+
+        code_rs.push(quote! {
+            impl MapEntities for #component_name {
+                fn map_entities(&mut self, map: &HashMap<Uuid, Uuid>) {
+                    #(self.#members.map_entities(map);)*
+                }
+            }
+        });
+    }
+
+    for resource in collected
+        .resources
+        .iter()
+        .filter(|resource| resource.fields.iter().any(|field| field.mentions(&ref_names)))
+    {
+        let resource_name = fident!(resource.name);
+        let field_name = fident!(resource.field_name());
+        let members = resource
+            .fields
+            .iter()
+            .filter(|field| field.mentions(&ref_names))
+            .map(|field| member_rs(&field.member));
+
+        code_rs.push(quote! {
+            impl MapEntities for #resource_name {
+                fn map_entities(&mut self, map: &HashMap<Uuid, Uuid>) {
+                    #(self.#members.map_entities(map);)*
+                }
+            }
+        });
+
+        world_map_rs.push(quote! {
+            self.resources.#field_name.map_entities(map);
+        });
+    }
+
+    for entity in collected.entities.iter() {
+        let archetype_type = fident!(to_plural(&entity.name));
+        let field_name = format_ident!("{}", to_plural(&entity.name.to_snake_case()));
+
+        let columns = entity
+            .fields
+            .iter()
+            .filter(|field| type_mentions(&field.data_type, &ref_names))
+            .map(|field| format_ident!("{}", to_plural(&field.name)));
+
+        code_rs.push(quote! {
+            impl MapEntities for #archetype_type {
+                fn map_entities(&mut self, map: &HashMap<Uuid, Uuid>) {
+                    #(self.#columns.map_entities(map);)*
+                }
+            }
+        });
+
+        world_map_rs.push(quote! {
+            self.#field_name.map_entities(map);
+        });
+    }
+
+    code_rs.push(quote! {
+        impl MapEntities for World {
+            fn map_entities(&mut self, map: &HashMap<Uuid, Uuid>) {
+                #(#world_map_rs)*
+
+                self.index_lookup = self
+                    .index_lookup
+                    .drain()
+                    .map(|(id, location)| (map.get(&id).copied().unwrap_or(id), location))
+                    .collect();
+            }
+        }
+
+        #[allow(dead_code)]
+        impl World {
+            /// Gives a fresh id to every entity whose id is live or was used in `existing`,
+            /// rewriting references to them, and returns the replaced ids, old to new.
+            fn reassign_colliding_ids(&mut self, existing: &World) -> HashMap<Uuid, Uuid> {
+                let map: HashMap<Uuid, Uuid> = self
+                    .index_lookup
+                    .keys()
+                    .filter(|id| existing.index_lookup.contains_key(id) || existing.generations.contains_key(id))
+                    .map(|id| (*id, Uuid::new_v4()))
+                    .collect();
+
+                if !map.is_empty() {
+                    self.map_entities(&map);
+                }
+
+                map
+            }
+        }
+    });
+
+    let code_rs = quote! {
+        #(#code_rs)*
+    };
+
+    include_files.push(write_token_stream_to_file(out_dir, FILE_NAME, &code_rs.to_string()));
+}
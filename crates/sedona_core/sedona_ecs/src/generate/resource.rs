@@ -0,0 +1,145 @@
+use crate::{CollectedData, fident, write_token_stream_to_file};
+use quote::{format_ident, quote};
+
+pub fn generate_resources(
+    out_dir: &str,
+    include_files: &mut Vec<String>,
+    collected: &CollectedData,
+) {
+    const FILE_NAME: &str = "resources.rs";
+
+    let mut code_rs = vec![];
+    let mut fields_rs = vec![];
+    let mut merge_rs = vec![];
+    let mut data_fields_rs = vec![];
+    let mut from_data_rs = vec![];
+
+    for resource in collected.resources.iter() {
+        let resource_name = fident!(resource.name);
+        let field_name = fident!(resource.field_name());
+        let name = &resource.name;
+
+        fields_rs.push(quote! {
+            pub #field_name: #resource_name,
+        });
+
+        merge_rs.push(quote! {
+            if other.set.contains(#name) {
+                self.#field_name = other.#field_name;
+            }
+        });
+
+        data_fields_rs.push(quote! {
+            #[serde(deserialize_with = "deserialize_set")]
+            #field_name: Option<#resource_name>,
+        });
+
+        from_data_rs.push(quote! {
+            if let Some(resource) = data.#field_name {
+                resources.#field_name = resource;
+                resources.set.insert(#name);
+            }
+        });
+
+        code_rs.push(quote! {
+            impl Resource for #resource_name {
+                const NAME: &'static str = #name;
+
+                unsafe fn from_world_ptr(world: *mut World) -> *mut Self {
+                    unsafe { &raw mut (*world).resources.#field_name }
+                }
+
+                fn from_resources(resources: &WorldResources) -> &Self {
+                    &resources.#field_name
+                }
+
+                fn from_resources_mut(resources: &mut WorldResources) -> &mut Self {
+                    &mut resources.#field_name
+                }
+            }
+        });
+    }
+
+    code_rs.push(quote! {
+        /// A `#[resource]`: a single value stored in the `World` rather than per entity.
+        ///
+        /// Systems take resources as `&T` or `&mut T` parameters; elsewhere they are reached
+        /// through `World::resource` and `World::resource_mut`.
+        pub trait Resource {
+            const NAME: &'static str;
+
+            /// Points at this resource without borrowing the rest of the world.
+            ///
+            /// # Safety
+            /// `world` must point at a live `World`.
+            unsafe fn from_world_ptr(world: *mut World) -> *mut Self;
+
+            fn from_resources(resources: &WorldResources) -> &Self;
+            fn from_resources_mut(resources: &mut WorldResources) -> &mut Self;
+        }
+
+        /// One of each `#[resource]`, starting out at its default.
+        #[derive(Default, Debug, serde::Deserialize, serde::Serialize)]
+        #[serde(default, from = "WorldResourcesData")]
+        pub struct WorldResources {
+            #(#fields_rs)*
+            /// The resources this world sets, by name: the ones its file holds and the ones
+            /// reached through `World::resource_mut`.
+            #[serde(skip)]
+            set: std::collections::HashSet<&'static str>,
+        }
+
+        /// The serialized resources, each `None` when the file leaves it out.
+        #[derive(Default, serde::Deserialize)]
+        #[serde(default)]
+        struct WorldResourcesData {
+            #(#data_fields_rs)*
+        }
+
+        #[allow(dead_code)]
+        fn deserialize_set<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+        where
+            D: serde::Deserializer<'de>,
+            T: serde::Deserialize<'de>,
+        {
+            T::deserialize(deserializer).map(Some)
+        }
+
+        impl From<WorldResourcesData> for WorldResources {
+            fn from(data: WorldResourcesData) -> WorldResources {
+                let mut resources = WorldResources::default();
+                #(#from_data_rs)*
+                resources
+            }
+        }
+
+        #[allow(dead_code)]
+        impl WorldResources {
+            /// Takes the resources `other` sets, so merging a package only overwrites the
+            /// resources it holds, even ones it sets back to their default.
+            fn merge(&mut self, other: WorldResources) {
+                #(#merge_rs)*
+                self.set.extend(other.set);
+            }
+        }
+
+        #[allow(dead_code)]
+        impl World {
+            pub fn resource<R: Resource>(&self) -> &R {
+                R::from_resources(&self.resources)
+            }
+
+            /// Also marks the resource as set, so merging this world into another takes it.
+            pub fn resource_mut<R: Resource>(&mut self) -> &mut R {
+                self.resources.set.insert(R::NAME);
+                R::from_resources_mut(&mut self.resources)
+            }
+        }
+    });
+
+    let code_rs = quote! {
+        #(#code_rs)*
+    };
+
+    include_files.push(write_token_stream_to_file(out_dir, FILE_NAME, &code_rs.to_string()));
+}
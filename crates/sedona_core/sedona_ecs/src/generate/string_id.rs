@@ -0,0 +1,113 @@
+use crate::{CollectedData, EntityDef, component_column_arms, write_token_stream_to_file};
+use heck::ToSnakeCase;
+use inflector::string::pluralize::to_plural;
+use quote::{format_ident, quote};
+
+/// Returns `true` if the entity type has a `StringId`, so the world indexes it.
+pub fn has_string_id(entity: &EntityDef) -> bool {
+    entity.fields.iter().any(|field| field.data_type == "StringId")
+}
+
+pub fn generate_string_ids(out_dir: &str, include_files: &mut Vec<String>, collected: &CollectedData) {
+    const FILE_NAME: &str = "string_id.rs";
+
+    let (match_string_id_rs, _) = component_column_arms(collected, "StringId");
+
+    let reindex_rs = collected.entities.iter().filter(|entity| has_string_id(entity)).map(|entity| {
+        let archetype_field = format_ident!("{}", to_plural(&entity.name.to_snake_case()));
+        quote! {
+            for entity in self.#archetype_field.entities.clone() {
+                self.index_string_id(entity);
+            }
+        }
+    });
+
+    let code_rs = quote! {
+        /// The name content uses for an entity, e.g. `weather_default`.
+        ///
+        /// The `World` indexes every entity with one, see `World::find_by_string_id`.
+        #[derive(Clone, Debug, Default, PartialEq, Eq, Hash, serde::Deserialize, serde::Serialize)]
+        pub struct StringId(pub Option<String>);
+
+        #[allow(dead_code)]
+        impl World {
+            /// Returns the entity with the given `StringId`, without scanning the world.
+            ///
+            /// If several entities share the id, the first one indexed is found, then the next
+            /// once it is despawned. The index follows `create`, `despawn` and `merge`; after
+            /// writing `StringId`s through queries, call `reindex_string_ids`.
+            pub fn find_by_string_id(&self, id: &str) -> Option<Entity> {
+                self.find_all_by_string_id(id).next()
+            }
+
+            /// Returns every entity with the given `StringId`, in the order they were indexed,
+            /// e.g. the same node in each instance of a model.
+            pub fn find_all_by_string_id<'a>(&'a self, id: &'a str) -> impl Iterator<Item = Entity> + 'a {
+                self.string_ids
+                    .get(id)
+                    .into_iter()
+                    .flatten()
+                    .copied()
+                    .filter(move |entity| self.string_id(*entity) == Some(id))
+            }
+
+            /// Returns the `StringId` of an entity, if it is alive and has one.
+            pub fn string_id(&self, entity: Entity) -> Option<&str> {
+                let (index, entity_type) = entity.locate(self)?;
+                self.string_id_at(index, entity_type)?.0.as_deref()
+            }
+
+            /// Rebuilds the `StringId` index from scratch, warning about duplicate ids.
+            pub fn reindex_string_ids(&mut self) {
+                self.string_ids.clear();
+                #(#reindex_rs)*
+            }
+
+            fn index_string_id(&mut self, entity: Entity) {
+                let Some(id) = self.string_id(entity).map(str::to_string) else {
+                    return;
+                };
+
+                if let Some(existing) = self.find_by_string_id(&id)
+                    && existing != entity
+                {
+                    sedona_ecs::log::warn!(
+                        "StringId `{id}` of {:?} {} is already used by {:?} {}",
+                        entity.entity_type,
+                        entity.id,
+                        existing.entity_type,
+                        existing.id,
+                    );
+                }
+
+                let entities = self.string_ids.entry(id).or_default();
+                if !entities.contains(&entity) {
+                    entities.push(entity);
+                }
+            }
+
+            fn unindex_string_id(&mut self, entity: Entity) {
+                let Some(id) = self.string_id(entity).map(str::to_string) else {
+                    return;
+                };
+
+                if let Some(entities) = self.string_ids.get_mut(&id) {
+                    entities.retain(|indexed| *indexed != entity);
+                    if entities.is_empty() {
+                        self.string_ids.remove(&id);
+                    }
+                }
+            }
+
+            #[allow(unreachable_patterns, unused_variables)]
+            fn string_id_at(&self, index: usize, entity_type: EntityType) -> Option<&StringId> {
+                match entity_type {
+                    #(#match_string_id_rs)*
+                    _ => None,
+                }
+            }
+        }
+    };
+
+    include_files.push(write_token_stream_to_file(out_dir, FILE_NAME, &code_rs.to_string()));
+}
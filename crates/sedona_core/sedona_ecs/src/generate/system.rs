@@ -1,10 +1,14 @@
+use crate::generate::query_access;
 use crate::parse::SystemDefParamValue;
 use crate::{
-    CollectedData, SystemDefParam, SystemDefParamReference, fident, write_token_stream_to_file,
+    CollectedData, SystemDef, SystemDefParam, SystemDefParamReference, fident,
+    write_token_stream_to_file,
 };
+use heck::ToSnakeCase;
 use itertools::Itertools;
+use proc_macro2::{Ident, TokenStream};
 use quote::{format_ident, quote};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 pub fn generate_systems(out_dir: &str, include_files: &mut Vec<String>, collected: &CollectedData) {
     const FILE_NAME: &str = "systems.rs";
@@ -21,43 +25,44 @@ pub fn generate_systems(out_dir: &str, include_files: &mut Vec<String>, collecte
     
     for group in groups.iter() {
         let mut calls = vec![];
+        let mut uses_commands = false;
+
+        // The group's world parameter, used to advance change ticks around each system.
+        let world_name = collected
+            .systems
+            .iter()
+            .filter(|s| &s.group == *group)
+            .flat_map(|s| s.params.iter())
+            .find_map(|param| match param {
+                SystemDefParam::Reference(reference) if reference.ty == "World" => {
+                    Some(reference.name.clone())
+                }
+                SystemDefParam::Commands(_)
+                | SystemDefParam::Resource(_)
+                | SystemDefParam::WorldCell(_) => Some("world".into()),
+                _ => None,
+            });
 
         let mut call_params: HashMap<(String, String), SystemDefParamReference> = HashMap::new();
         let mut call_params_values: HashMap<(String, String), SystemDefParamValue> = HashMap::new();
 
-        for system in collected
+        // Systems are already in execution order, see `order_systems`.
+        let systems: Vec<&SystemDef> = collected
             .systems
             .iter()
             .filter(|s| &s.group == *group)
-            .sorted_by(|a, b| a.name.cmp(&b.name))
-        {
-            let mut params_rs = vec![];
+            .collect();
 
+        for system in systems.iter() {
             for param in system.params.iter() {
                 match param {
-                    SystemDefParam::Query(_name) => {
-                        params_rs.push(quote! {
-                            Query::new(),
-                        });
-                    }
+                    SystemDefParam::Commands(_) => uses_commands = true,
                     SystemDefParam::Value(value) => {
-                        let name = fident!(value.name);
-
-                        params_rs.push(quote! {
-                            #name,
-                        });
-
                         let key = (value.name.clone(), value.ty.clone());
                         let item = value.clone();
                         call_params_values.entry(key).or_insert(item);
                     }
                     SystemDefParam::Reference(reference) => {
-                        let name = fident!(reference.name);
-
-                        params_rs.push(quote! {
-                            #name,
-                        });
-
                         let key = (reference.name.clone(), reference.ty.clone());
                         let item = reference.clone();
                         call_params
@@ -69,18 +74,86 @@ pub fn generate_systems(out_dir: &str, include_files: &mut Vec<String>, collecte
                             })
                             .or_insert(item);
                     }
+                    SystemDefParam::Query(_)
+                    | SystemDefParam::Resource(_)
+                    | SystemDefParam::WorldCell(_) => {}
                 }
             }
+        }
 
-            let system_function_name = fident!(&system.name);
+        let batches = if collected.parallel_groups.contains(*group) {
+            schedule_parallel_batches(&systems)
+        } else {
+            systems.iter().map(|system| vec![*system]).collect()
+        };
 
-            calls.push(quote! {
-                #system_function_name(#(#params_rs)*);
-            })
+        for batch in batches.iter() {
+            let world_name = world_name.as_ref().map(|world_name| fident!(world_name));
+
+            if let [system] = batch.as_slice() {
+                // A skipped system keeps its last run, so it sees every change once it runs.
+                let begin_system_rs = world_name.as_ref().map(|world_name| {
+                    let system_name = &system.name;
+                    quote! { #world_name.begin_system(#system_name); }
+                });
+
+                calls.push(system_call_rs(
+                    system,
+                    world_name.as_ref(),
+                    None,
+                    quote! { #begin_system_rs },
+                ));
+            } else {
+                let system_names = batch.iter().map(|system| &system.name);
+
+                let spawns_rs: Vec<_> = batch
+                    .iter()
+                    .map(|system| {
+                        let access = SystemAccess::new(system);
+                        let call_rs = system_call_rs(system, None, Some(&access), quote! {});
+                        quote! {
+                            scope.spawn(|_| {
+                                #call_rs
+                            });
+                        }
+                    })
+                    .collect();
+
+                let (begin_systems_rs, world_cell_init_rs) = match &world_name {
+                    Some(world_name) => (
+                        quote! { #world_name.begin_systems(&[#(#system_names),*]); },
+                        quote! { let world_cell = WorldCell::new(#world_name); },
+                    ),
+                    None => (quote! {}, quote! {}),
+                };
+
+                calls.push(quote! {
+                    #begin_systems_rs
+                    {
+                        #world_cell_init_rs
+                        sedona_ecs::rayon::scope(|scope| {
+                            #(#spawns_rs)*
+                        });
+                    }
+                });
+            }
         }
 
         let function_name = format_ident!("systems_{}", group);
 
+        // Commands are flushed into the world once the whole group has run, and change ticks
+        // are advanced before each system, so both need the world mutably.
+        if let Some(world_name) = &world_name {
+            call_params
+                .entry((world_name.clone(), "World".into()))
+                .and_modify(|e| e.mutable = true)
+                .or_insert(SystemDefParamReference {
+                    name: world_name.clone(),
+                    ty: "World".into(),
+                    mutable: true,
+                });
+        }
+
         // get values of call_params, ignoring the key
         let call_params: Vec<_> = call_params.values().collect();
         let call_params_values: Vec<_> = call_params_values.values().collect();
@@ -122,10 +195,28 @@ pub fn generate_systems(out_dir: &str, include_files: &mut Vec<String>, collecte
 
         let all_call_params_rs = call_params_rs.chain(call_params_values_rs);
 
+        let (commands_init_rs, commands_apply_rs) = if uses_commands {
+            (
+                quote! { let mut commands = Commands::new(); },
+                quote! { commands.apply(world); },
+            )
+        } else {
+            (quote! {}, quote! {})
+        };
+
+        // Anything written between groups is seen as changed by every system.
+        let increment_change_tick_rs = world_name.as_ref().map(|world_name| {
+            let world_name = fident!(world_name);
+            quote! { #world_name.increment_change_tick(); }
+        });
+
         code_rs.push(quote! {
-            #[allow(private_interfaces)]
+            #[allow(private_interfaces, clippy::clone_on_copy)]
             pub fn #function_name(#(#all_call_params_rs),*) {
+                #commands_init_rs
                 #(#calls)*
+                #commands_apply_rs
+                #increment_change_tick_rs
             }
         });
     }
@@ -136,3 +227,298 @@ pub fn generate_systems(out_dir: &str, include_files: &mut Vec<String>, collecte
 
     include_files.push(write_token_stream_to_file(out_dir, FILE_NAME, &code_rs.to_string()));
 }
+
+/// Builds the call of a single system from the group runner's parameters, guarded by its
+/// run conditions if it has any.
+///
+/// `batch_access` is set for a system running alongside others in a parallel batch. Its
+/// `WorldCell`, `&World` and resource arguments are then split off the batch's `world_cell`
+/// and limited to the system's declared access, instead of borrowed from `world_name`.
+/// `prelude_rs` runs right before the system, once its conditions have passed.
+fn system_call_rs(
+    system: &SystemDef,
+    world_name: Option<&Ident>,
+    batch_access: Option<&SystemAccess>,
+    prelude_rs: TokenStream,
+) -> TokenStream {
+    let split_rs = batch_access.map(|access| {
+        let system_name = &system.name;
+        let reads_world = access.reads_world;
+        let reads = access.reads.iter().sorted();
+        let writes = access.writes.iter().sorted();
+        quote! {
+            // SAFETY: the systems of a batch were scheduled together because their declared
+            // accesses don't conflict.
+            unsafe {
+                world_cell.split(&CellAccess {
+                    system: #system_name,
+                    reads_world: #reads_world,
+                    reads: &[#(#reads),*],
+                    writes: &[#(#writes),*],
+                })
+            }
+        }
+    });
+
+    let param_rs = |param: &SystemDefParam| match param {
+        SystemDefParam::Commands(_) => quote! { &mut commands },
+        SystemDefParam::Query(_) => quote! { Query::new() },
+        SystemDefParam::WorldCell(_) => match &split_rs {
+            Some(split_rs) => split_rs.clone(),
+            None => quote! { WorldCell::new(#world_name) },
+        },
+        SystemDefParam::Value(value) => {
+            let name = fident!(value.name);
+            quote! { #name.clone() }
+        }
+        SystemDefParam::Reference(reference) => match &split_rs {
+            Some(split_rs) if reference.ty == "World" => quote! { #split_rs.world() },
+            _ => {
+                let name = fident!(reference.name);
+                quote! { #name }
+            }
+        },
+        SystemDefParam::Resource(resource) => {
+            let ty = fident!(resource.ty);
+            let field = fident!(resource.ty.to_snake_case());
+
+            match (&split_rs, resource.mutable) {
+                (Some(split_rs), false) => quote! { #split_rs.resource::<#ty>() },
+                (Some(split_rs), true) => quote! { #split_rs.resource_mut::<#ty>() },
+                (None, false) => quote! { &#world_name.resources.#field },
+                (None, true) => quote! { &mut #world_name.resources.#field },
+            }
+        }
+    };
+
+    let params_rs = system.params.iter().map(param_rs);
+    let system_function_name = fident!(&system.name);
+
+    let call_rs = quote! {
+        #prelude_rs
+        #system_function_name(#(#params_rs),*);
+    };
+
+    if system.run_if.is_empty() {
+        return call_rs;
+    }
+
+    let conditions_rs = system.run_if.iter().map(|condition| {
+        let condition_path: TokenStream = condition.path.parse().unwrap();
+        let params_rs = condition.params.iter().filter_map(|name| {
+            system
+                .params
+                .iter()
+                .find(|param| param.name() == name)
+                .map(param_rs)
+        });
+        quote! { #condition_path(#(#params_rs),*) }
+    });
+
+    quote! {
+        if #(#conditions_rs)&&* {
+            #call_rs
+        }
+    }
+}
+
+/// The world and resource accesses a system declares through its parameters.
+///
+/// Components and `#[resource]` types are tracked together in `reads` and `writes`.
+struct SystemAccess {
+    /// Takes a `&mut` reference, such as `&mut World`, so it always runs on its own.
+    exclusive: bool,
+    uses_commands: bool,
+    /// Takes `&World`, so it may read any component.
+    reads_world: bool,
+    reads: HashSet<String>,
+    writes: HashSet<String>,
+}
+
+impl SystemAccess {
+    fn new(system: &SystemDef) -> Self {
+        let mut access = SystemAccess {
+            exclusive: false,
+            uses_commands: false,
+            reads_world: false,
+            reads: HashSet::new(),
+            writes: HashSet::new(),
+        };
+
+        for param in system.params.iter() {
+            match param {
+                SystemDefParam::Reference(reference) if reference.mutable => {
+                    access.exclusive = true;
+                }
+                SystemDefParam::Reference(reference) if reference.ty == "World" => {
+                    access.reads_world = true;
+                }
+                SystemDefParam::Commands(_) => access.uses_commands = true,
+                SystemDefParam::Resource(resource) if resource.mutable => {
+                    access.writes.insert(resource.ty.clone());
+                }
+                SystemDefParam::Resource(resource) => {
+                    access.reads.insert(resource.ty.clone());
+                }
+                SystemDefParam::Query(query) => {
+                    let (reads, writes) = query_access(&query.query);
+                    access.reads.extend(reads);
+                    access.writes.extend(writes);
+                }
+                _ => {}
+            }
+        }
+
+        // Reading the whole world while writing part of it would alias within the system.
+        if access.reads_world && !access.writes.is_empty() {
+            access.exclusive = true;
+        }
+
+        access.reads.retain(|component| !access.writes.contains(component));
+        access
+    }
+
+    fn conflicts_with(&self, other: &SystemAccess) -> bool {
+        let writes_into = |a: &SystemAccess, b: &SystemAccess| {
+            (b.reads_world && !a.writes.is_empty())
+                || a
+                    .writes
+                    .iter()
+                    .any(|component| b.reads.contains(component) || b.writes.contains(component))
+        };
+
+        self.exclusive
+            || other.exclusive
+            || (self.uses_commands && other.uses_commands)
+            || writes_into(self, other)
+            || writes_into(other, self)
+    }
+}
+
+/// Splits the ordered systems of a parallel group into batches that run one after another.
+///
+/// Each system joins the earliest batch after every earlier system it conflicts with or is
+/// ordered against, so systems within a batch can run concurrently.
+fn schedule_parallel_batches<'s>(systems: &[&'s SystemDef]) -> Vec<Vec<&'s SystemDef>> {
+    let accesses: Vec<SystemAccess> = systems.iter().map(|s| SystemAccess::new(s)).collect();
+    let mut batch_of: Vec<usize> = vec![];
+    let mut batches: Vec<Vec<&SystemDef>> = vec![];
+
+    for (index, system) in systems.iter().enumerate() {
+        let ordered_against = |other: &SystemDef| {
+            system.after.contains(&other.name) || other.before.contains(&system.name)
+        };
+
+        let batch = (0..index)
+            .filter(|&earlier| {
+                accesses[index].conflicts_with(&accesses[earlier])
+                    || ordered_against(systems[earlier])
+            })
+            .map(|earlier| batch_of[earlier] + 1)
+            .max()
+            .unwrap_or(0);
+
+        batch_of.push(batch);
+        match batches.get_mut(batch) {
+            Some(systems) => systems.push(system),
+            None => batches.push(vec![system]),
+        }
+    }
+
+    batches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_system_def;
+
+    fn system(source: &str) -> SystemDef {
+        let source = if source.starts_with("#[") {
+            source.to_string()
+        } else {
+            format!("#[system(group=update)] {source}")
+        };
+        let item_fn = syn::parse_str(&source).unwrap();
+        let mut systems = vec![];
+        parse_system_def(item_fn, &mut systems, &mut vec![], &mut vec![]);
+        systems.pop().unwrap()
+    }
+
+    fn conflicts(a: &str, b: &str) -> bool {
+        SystemAccess::new(&system(a)).conflicts_with(&SystemAccess::new(&system(b)))
+    }
+
+    fn batches(sources: &[&str]) -> Vec<Vec<String>> {
+        let systems: Vec<SystemDef> = sources.iter().map(|source| system(source)).collect();
+        let systems: Vec<&SystemDef> = systems.iter().collect();
+        schedule_parallel_batches(&systems)
+            .iter()
+            .map(|batch| batch.iter().map(|system| system.name.clone()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn readers_do_not_conflict() {
+        assert!(!conflicts("fn a(q: Query<&A>) {}", "fn b(q: Query<(&A, &B)>) {}"));
+        assert!(!conflicts("fn a(world: &World) {}", "fn b(q: Query<&A>) {}"));
+    }
+
+    #[test]
+    fn writers_conflict_with_readers_and_writers() {
+        assert!(conflicts("fn a(q: Query<&mut A>) {}", "fn b(q: Query<&A>) {}"));
+        assert!(conflicts("fn a(q: Query<&mut A>) {}", "fn b(q: Query<Option<&mut A>>) {}"));
+        assert!(!conflicts("fn a(q: Query<&mut A>) {}", "fn b(q: Query<&mut B>) {}"));
+    }
+
+    #[test]
+    fn tick_filters_count_as_reads() {
+        assert!(conflicts("fn a(q: Query<&mut A>) {}", "fn b(q: Query<&B, Changed<A>>) {}"));
+        assert!(conflicts("fn a(q: Query<&mut A>) {}", "fn b(q: Query<&B, Added<A>>) {}"));
+        assert!(!conflicts("fn a(q: Query<&mut A>) {}", "fn b(q: Query<&B, With<A>>) {}"));
+    }
+
+    #[test]
+    fn world_readers_conflict_with_writers() {
+        assert!(conflicts("fn a(world: &World) {}", "fn b(q: Query<&mut A>) {}"));
+    }
+
+    #[test]
+    fn mutable_references_and_commands_run_alone() {
+        assert!(conflicts("fn a(world: &mut World) {}", "fn b(q: Query<&B>) {}"));
+        assert!(conflicts("fn a(commands: &mut Commands) {}", "fn b(commands: &mut Commands) {}"));
+        assert!(!conflicts("fn a(commands: &mut Commands) {}", "fn b(q: Query<&B>) {}"));
+    }
+
+    #[test]
+    fn systems_join_the_earliest_batch_after_their_conflicts() {
+        let batches = batches(&[
+            "fn a(q: Query<&mut A>) {}",
+            "fn b(q: Query<&A>) {}",
+            "fn c(q: Query<&mut C>) {}",
+            "fn d(q: Query<&B, Changed<C>>) {}",
+        ]);
+
+        assert_eq!(batches, [vec!["a", "c"], vec!["b", "d"]]);
+    }
+
+    #[test]
+    fn ordering_constraints_split_batches() {
+        let batches = batches(&["fn a(q: Query<&A>) {}", "#[system(group=update, after=a)] fn b(q: Query<&A>) {}"]);
+
+        assert_eq!(batches, [vec!["a"], vec!["b"]]);
+    }
+
+    #[test]
+    fn run_conditions_guard_the_call() {
+        let mut system = system(
+            "#[system(group=update, run_if=ready)] fn a(world: &World, resources: &GameResources) {}",
+        );
+        system.run_if[0].path = "audio::conditions::ready".to_string();
+        system.run_if[0].params = vec!["resources".to_string()];
+
+        let call = system_call_rs(&system, Some(&format_ident!("world")), None, quote! {}).to_string();
+
+        assert_eq!(call, "if audio :: conditions :: ready (resources) { a (world , resources) ; }");
+    }
+}
@@ -1,8 +1,52 @@
-use crate::{CollectedData, fident, write_token_stream_to_file};
+use crate::{
+    CollectedData, HookKind, fident, has_hierarchy, has_string_id, write_token_stream_to_file,
+};
 use heck::ToSnakeCase;
 use inflector::string::pluralize::to_plural;
+use proc_macro2::{Ident, TokenStream};
 use quote::{format_ident, quote};
 
+/// Name of the archetype column holding the change ticks of a component field.
+pub fn ticks_field_ident(field_name: &str) -> Ident {
+    format_ident!("{}_ticks", field_name)
+}
+
+/// Match arms returning the `component` column of each entity type that has one, by reference
+/// and by mutable reference. The mutable arms mark the component as changed.
+pub fn component_column_arms(
+    collected: &CollectedData,
+    component: &str,
+) -> (Vec<TokenStream>, Vec<TokenStream>) {
+    let mut match_rs = vec![];
+    let mut match_mut_rs = vec![];
+
+    for entity in collected.entities.iter() {
+        let Some(field) = entity.fields.iter().find(|field| field.data_type == component) else {
+            continue;
+        };
+
+        let entity_name = fident!(entity.name);
+        let archetype_field = format_ident!("{}", to_plural(&entity.name.to_snake_case()));
+        let column = format_ident!("{}", to_plural(&field.name));
+        let ticks_column = ticks_field_ident(&field.name);
+
+        match_rs.push(quote! {
+            EntityType::#entity_name => self.#archetype_field.#column.get(index),
+        });
+
+        match_mut_rs.push(quote! {
+            EntityType::#entity_name => {
+                if let Some(ticks) = self.#archetype_field.#ticks_column.get_mut(index) {
+                    ticks.changed = self.change_tick;
+                }
+                self.#archetype_field.#column.get_mut(index)
+            }
+        });
+    }
+
+    (match_rs, match_mut_rs)
+}
+
 pub fn generate_world_rs(
     out_dir: &str,
     include_files: &mut Vec<String>,
@@ -24,16 +68,24 @@ pub fn generate_world_rs(
         use sedona_ecs::*;
         use std::collections::HashMap;
 
-        #[derive(Clone, Copy, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+        #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, serde::Deserialize, serde::Serialize)]
         pub enum EntityType {
             #(#entity_types),*
         }
 
+        /// Handle to an entity in the `World`.
+        ///
+        /// The generation is bumped whenever a despawned id is reused, so a handle that outlived
+        /// its entity never resolves to the entity that replaced it. Ids `create` picks are
+        /// random and never reused, so this only matters for ids that came from data; an
+        /// entity saved, despawned and then merged back from the save resolves its old handles.
         #[allow(dead_code)]
-        #[derive(Clone, Copy, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+        #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, serde::Deserialize, serde::Serialize)]
         pub struct Entity {
             entity_type: EntityType,
             id: Uuid,
+            #[serde(default)]
+            generation: u32,
         }
 
         #[allow(dead_code)]
@@ -45,6 +97,36 @@ pub fn generate_world_rs(
             pub fn entity_type(&self) -> EntityType {
                 self.entity_type
             }
+
+            pub fn generation(&self) -> u32 {
+                self.generation
+            }
+        }
+
+        /// Anything that can be resolved to a row in the `World`.
+        ///
+        /// A `Uuid` resolves to whichever entity currently owns that id, while an `Entity`
+        /// only resolves if its generation still matches the live entity.
+        pub trait EntityHandle {
+            fn locate(&self, world: &World) -> Option<(usize, EntityType)>;
+        }
+
+        impl EntityHandle for Uuid {
+            fn locate(&self, world: &World) -> Option<(usize, EntityType)> {
+                world.index_lookup.get(self).copied()
+            }
+        }
+
+        impl EntityHandle for Entity {
+            fn locate(&self, world: &World) -> Option<(usize, EntityType)> {
+                let (index, entity_type) = world.index_lookup.get(&self.id).copied()?;
+
+                if world.entity_at(index, entity_type) == Some(*self) {
+                    Some((index, entity_type))
+                } else {
+                    None
+                }
+            }
         }
 
         #[allow(dead_code)]
@@ -95,8 +177,20 @@ pub fn generate_world_rs(
             }
         }
 
+        /// Adds entities to the `World`, running their `#[on_create]` hooks once they are in.
+        ///
+        /// A `Parent` or `Children` the entity is created with is linked from the other side.
         pub trait WorldCreate<T> {
-            fn create(&mut self, e: T) -> Entity;
+            fn create(&mut self, e: T) -> Entity {
+                self.create_with_id(Uuid::new_v4(), e)
+            }
+
+            fn create_with_id(&mut self, id: Uuid, e: T) -> Entity;
+        }
+
+        /// Associates an entity struct with its `EntityType`.
+        pub trait EntityKind {
+            const ENTITY_TYPE: EntityType;
         }
     });
 
@@ -104,6 +198,12 @@ pub fn generate_world_rs(
     let mut table_merges = vec![];
     let mut match_merge_index = vec![];
     let mut match_destroy_rs = vec![];
+    let mut match_on_destroy_rs = vec![];
+    let mut match_entity_at_rs = vec![];
+    let mut match_entity_at_ptr_rs = vec![];
+    let mut set_system_ticks_rs = vec![];
+    let mut index_merged_rs = vec![];
+    let mut archetype_field_names = vec![];
 
     for entity in collected.entities.iter() {
         let entity_name = &entity.name;
@@ -111,6 +211,8 @@ pub fn generate_world_rs(
         let archetype_type = fident!(to_plural(entity_name));
         let serialize = entity.serialize;
 
+        archetype_field_names.push(field_name.clone());
+
         if serialize {
             world_fields.push(quote! {
                 #field_name: #archetype_type,
@@ -138,11 +240,28 @@ pub fn generate_world_rs(
             }
         });
 
+        let component_fields: Vec<_> = entity
+            .fields
+            .iter()
+            .filter(|f| f.data_type != "Entity")
+            .collect();
+
+        let ticks_fields: Vec<_> = component_fields
+            .iter()
+            .map(|field| ticks_field_ident(&field.name))
+            .collect();
+
         code_rs.push(quote! {
 
             #[derive(Default, Debug, serde::Deserialize, serde::Serialize)]
             struct #archetype_type {
                 #(#archetype_fields)*
+                #(
+                    #[serde(skip)]
+                    #ticks_fields: Vec<ComponentTicks>,
+                )*
+                #[serde(skip)]
+                system_ticks: SystemTicks,
             }
 
             #[allow(dead_code)]
@@ -239,58 +358,118 @@ pub fn generate_world_rs(
                 }
             });
 
+        let skipped_columns: Vec<_> = entity
+            .fields
+            .iter()
+            .filter(|f| f.data_type != "Entity" && !f.serialize)
+            .map(|f| format_ident!("{}", to_plural(&f.name)))
+            .collect();
+
         let merge_entities_line = quote! {
             self.entities.extend(table_to_merge.entities.drain(..));
         };
 
+        let push_ticks_lines = ticks_fields.iter().map(|ticks_field| {
+            quote! {
+                self.#field_name.#ticks_field.push(ComponentTicks::new(self.change_tick));
+            }
+        });
+
+        let hooks = |kind: HookKind| {
+            let mut names: Vec<&str> = collected
+                .hooks
+                .iter()
+                .filter(|hook| hook.kind == kind && hook.applies_to(entity))
+                .map(|hook| hook.name.as_str())
+                .collect();
+            names.sort();
+            names.into_iter().map(|name| fident!(name)).collect::<Vec<_>>()
+        };
+        let on_create_hooks = hooks(HookKind::Create);
+        let on_destroy_hooks = hooks(HookKind::Destroy);
+
+        let link_created_rs = has_hierarchy(entity).then(|| quote! {
+            self.link_created(entity);
+        });
+
+        let index_created_rs = has_string_id(entity).then(|| quote! {
+            self.index_string_id(entity);
+        });
+
         let entity_name = fident!(entity_name);
 
+        match_on_destroy_rs.push(quote! {
+            EntityType::#entity_name => {
+                #(#on_destroy_hooks(self, entity);)*
+            }
+        });
+
         code_rs.push(quote! {
+            impl EntityKind for #entity_name {
+                const ENTITY_TYPE: EntityType = EntityType::#entity_name;
+            }
+
             impl WorldCreate<#entity_name> for World {
-                fn create(&mut self, e: #entity_name) -> Entity {
-                    let id = Uuid::new_v4();
+                fn create_with_id(&mut self, id: Uuid, e: #entity_name) -> Entity {
                     self.index_lookup.insert(id, (self.#field_name.entities.len(), EntityType::#entity_name));
                     let entity = Entity {
                         entity_type: EntityType::#entity_name,
                         id,
+                        generation: self.generations.remove(&id).unwrap_or_default(),
                     };
                     self.#field_name.entities.push(entity);
                     #(#push_lines)*
+                    #(#push_ticks_lines)*
+                    #index_created_rs
+                    #link_created_rs
+                    #(#on_create_hooks(self, entity);)*
                     entity
                 }
             }
         });
 
-        let pop_and_drop_code = entity.fields.iter().map(|field| {
-            // let field_name = format_ident!("{}", to_plural(&field.name));
+        let swap_remove_code = entity.fields.iter().map(|field| {
             let component_field_name = format_ident!("{}", to_plural(&field.name));
             quote! {
-                self.#component_field_name.swap(index, last_index);
-                self.#component_field_name.pop();
+                self.#component_field_name.swap_remove(index);
             }
         });
 
-        let pop_and_drop_code_copy = pop_and_drop_code.clone();
 
         code_rs.push(quote! {
             #[allow(dead_code)]
             impl #archetype_type {
-                fn destroy(&mut self, index: usize) {
-                    let last_index = self.entities.len() - 1;
-
-                    if index != last_index {
-                        #(#pop_and_drop_code)*
-                    } else {
-                        #(#pop_and_drop_code_copy)*
-                    }
+                /// Swap-removes the row at `index`, returning the entity that was moved into it.
+                fn destroy(&mut self, index: usize) -> Option<Entity> {
+                    #(#swap_remove_code)*
+                    #(self.#ticks_fields.swap_remove(index);)*
+                    self.entities.get(index).copied()
                 }
 
-                fn merge(&mut self, table_to_merge: &mut #archetype_type) {
+                fn merge(&mut self, table_to_merge: &mut #archetype_type, tick: u64) {
                     let num_new = table_to_merge.entities.len();
 
                     #merge_entities_line
 
                     #(#merge_extend_lines)*
+
+                    self.sync_ticks(tick);
+                }
+
+                /// Pads the tick columns to the table length, marking untracked rows as added at `tick`.
+                ///
+                /// Tick columns are not serialized, so rows that arrived through deserialization
+                /// have none until they are merged into a live world.
+                fn sync_ticks(&mut self, tick: u64) {
+                    let len = self.entities.len();
+                    #(self.#ticks_fields.resize(len, ComponentTicks::new(tick));)*
+                }
+
+                /// Fills the columns serde skips with defaults and adds the tick columns, for a
+                /// table that was just deserialized.
+                fn sync_deserialized(&mut self, tick: u64) {
+                    #(self.#skipped_columns.resize_with(self.entities.len(), Default::default);)*
+                    self.sync_ticks(tick);
                 }
             }
         });
@@ -300,8 +479,21 @@ pub fn generate_world_rs(
             let #offset_ident = self.#field_name.entities.len();
         });
         
+        if has_string_id(entity) {
+            index_merged_rs.push(quote! {
+                for index in #offset_ident..self.#field_name.entities.len() {
+                    let entity = self.#field_name.entities[index];
+                    self.index_string_id(entity);
+                }
+            });
+        }
+
         table_merges.push(quote! {
-            self.#field_name.merge(&mut other.#field_name);
+            self.#field_name.merge(&mut other.#field_name, self.change_tick);
+        });
+
+        set_system_ticks_rs.push(quote! {
+            self.#field_name.system_ticks = system_ticks;
         });
 
         match_merge_index.push(quote! {
@@ -314,21 +506,45 @@ pub fn generate_world_rs(
         match_destroy_rs.push(quote! {
             EntityType::#entity_name => self.#field_name.destroy(index),
         });
+
+        match_entity_at_rs.push(quote! {
+            EntityType::#entity_name => self.#field_name.entities.get(index).copied(),
+        });
+
+        match_entity_at_ptr_rs.push(quote! {
+            EntityType::#entity_name => (&(*world).#field_name.entities).get(index).copied(),
+        });
     }
 
     code_rs.push(quote! {
         #[allow(dead_code)]
         impl World {
-            pub fn merge(&mut self, mut other: World) {
+            /// Moves every entity of `other` into this world.
+            ///
+            /// Entities whose id is already used here get a fresh id, and the entity references
+            /// in `other`'s components are rewritten to follow. Resources `other` sets replace
+            /// ours. Hooks don't run for merged entities. Returns the replaced ids, old to new.
+            pub fn merge(&mut self, mut other: World) -> HashMap<Uuid, Uuid> {
+                let reassigned = other.reassign_colliding_ids(self);
+
+                self.resources.merge(std::mem::take(&mut other.resources));
+
                 #(#offset_decls)*
 
                 #(#table_merges)*
 
+                let kept_ids = other.reusable_ids.iter().filter(|id| other.index_lookup.contains_key(*id));
+                self.reusable_ids.extend(kept_ids);
+
                 for (uuid, (index, entity_type)) in other.index_lookup.drain() {
                     match entity_type {
                         #(#match_merge_index)*
                     }
                 }
+
+                #(#index_merged_rs)*
+
+                reassigned
             }
         }
     });
@@ -336,23 +552,180 @@ pub fn generate_world_rs(
     code_rs.push(quote! {
         #[allow(dead_code)]
         impl World {
-            fn destroy(&mut self, id: Uuid) {
-                if let Some(&(index, entity_type)) = self.index_lookup.get(&id) {
-                    self.index_lookup.remove(&id);
-                    match entity_type {
-                        #(#match_destroy_rs)*
-                    }
+            /// Removes an entity from the world, after running its `#[on_destroy]` hooks.
+            ///
+            /// Its children are left without a parent; `despawn_recursive` removes them too.
+            ///
+            /// Returns `false` if the handle is stale or the entity does not exist.
+            pub fn despawn(&mut self, entity: Entity) -> bool {
+                if !self.is_alive(entity) {
+                    return false;
+                }
+
+                match entity.entity_type {
+                    #(#match_on_destroy_rs)*
+                }
+
+                self.unlink_despawned(entity);
+                self.unindex_string_id(entity);
+
+                // Hooks may despawn other entities, moving this one, or this one itself.
+                let Some((index, entity_type)) = entity.locate(self) else {
+                    return true;
+                };
+
+                self.index_lookup.remove(&entity.id);
+                if self.reusable_ids.remove(&entity.id) {
+                    self.generations.insert(entity.id, entity.generation.wrapping_add(1));
+                }
+
+                let moved = match entity_type {
+                    #(#match_destroy_rs)*
+                };
+
+                if let Some(moved) = moved {
+                    self.index_lookup.insert(moved.id, (index, moved.entity_type));
+                }
+
+                true
+            }
+
+            /// Returns the live handle for an id, if the entity exists.
+            pub fn entity(&self, id: Uuid) -> Option<Entity> {
+                let (index, entity_type) = id.locate(self)?;
+                self.entity_at(index, entity_type)
+            }
+
+            /// Returns `true` if the handle still refers to a live entity.
+            pub fn is_alive(&self, entity: Entity) -> bool {
+                entity.locate(self).is_some()
+            }
+
+            fn entity_at(&self, index: usize, entity_type: EntityType) -> Option<Entity> {
+                match entity_type {
+                    #(#match_entity_at_rs)*
+                }
+            }
+
+            /// Like `Entity::locate`, but only borrows the id index and the `entities`
+            /// columns, so it can run while other columns are borrowed through a `WorldCell`.
+            ///
+            /// # Safety
+            /// `world` must point at a live `World` whose id index and `entities` columns are
+            /// not being written.
+            #[allow(clippy::needless_borrow)]
+            unsafe fn locate_ptr(world: *const World, entity: Entity) -> Option<(usize, EntityType)> {
+                unsafe {
+                    let (index, entity_type) = (&(*world).index_lookup).get(&entity.id).copied()?;
+                    let found = match entity_type {
+                        #(#match_entity_at_ptr_rs)*
+                    };
+
+                    (found == Some(entity)).then_some((index, entity_type))
                 }
             }
         }
     });
 
+    code_rs.push(quote! {
+        #[allow(dead_code)]
+        impl World {
+            /// Advances the change tick for the named system, so its `Added`/`Changed` filters
+            /// match rows touched since the previous time it began.
+            pub fn begin_system(&mut self, name: &'static str) {
+                self.change_tick += 1;
+                let last_run = self.system_last_runs.insert(name, self.change_tick);
+                self.set_system_ticks(SystemTicks {
+                    this_run: self.change_tick,
+                    last_run,
+                });
+            }
+
+            /// Advances the change tick for systems that run side by side.
+            ///
+            /// They share one set of ticks, seeing every row touched since the earliest of
+            /// their previous runs.
+            pub fn begin_systems(&mut self, names: &[&'static str]) {
+                self.change_tick += 1;
+                let mut last_run = Some(self.change_tick);
+                for name in names {
+                    let previous = self.system_last_runs.insert(name, self.change_tick);
+                    last_run = last_run.min(previous);
+                }
+                self.set_system_ticks(SystemTicks {
+                    this_run: self.change_tick,
+                    last_run,
+                });
+            }
+
+            /// Advances the change tick outside of any system.
+            ///
+            /// Writes made afterwards are seen as changes by every system, and filtered
+            /// queries run outside of a system match every row.
+            pub fn increment_change_tick(&mut self) {
+                self.change_tick += 1;
+                self.set_system_ticks(SystemTicks {
+                    this_run: self.change_tick,
+                    last_run: None,
+                });
+            }
+
+            pub fn change_tick(&self) -> u64 {
+                self.change_tick
+            }
+
+            fn set_system_ticks(&mut self, system_ticks: SystemTicks) {
+                #(#set_system_ticks_rs)*
+            }
+        }
+    });
+
     code_rs.push(quote! {
         #[derive(Default, Debug, serde::Deserialize, serde::Serialize)]
+        #[serde(default, from = "WorldData")]
         pub struct World {
             #(#world_fields)*
+            resources: WorldResources,
+            index_lookup: HashMap<Uuid, (usize, EntityType)>,
+            /// The next generation of every despawned id in `reusable_ids`.
+            #[serde(skip)]
+            generations: HashMap<Uuid, u32>,
+            /// Ids of live entities that came from data, through deserialization or `merge`,
+            /// the only ids that can come back, see `Entity`.
+            #[serde(skip)]
+            reusable_ids: std::collections::HashSet<Uuid>,
+            #[serde(skip)]
+            string_ids: HashMap<String, Vec<Entity>>,
+            #[serde(skip)]
+            change_tick: u64,
+            #[serde(skip)]
+            system_last_runs: HashMap<&'static str, u64>,
+            #[serde(skip)]
+            prefab_root: std::path::PathBuf,
+        }
+
+        /// The serialized part of a `World`, completed by `sync_deserialized` once it is read.
+        #[derive(Default, serde::Deserialize)]
+        #[serde(default)]
+        struct WorldData {
+            #(#world_fields)*
+            resources: WorldResources,
             index_lookup: HashMap<Uuid, (usize, EntityType)>,
         }
+
+        impl From<WorldData> for World {
+            fn from(data: WorldData) -> World {
+                let mut world = World {
+                    #(#archetype_field_names: data.#archetype_field_names,)*
+                    resources: data.resources,
+                    index_lookup: data.index_lookup,
+                    ..Default::default()
+                };
+                #(world.#archetype_field_names.sync_deserialized(world.change_tick);)*
+                world.reusable_ids = world.index_lookup.keys().copied().collect();
+                world
+            }
+        }
     });
 
     let code_rs = quote! {
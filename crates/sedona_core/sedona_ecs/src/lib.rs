@@ -4,6 +4,7 @@ pub mod ecs_build;
 mod generate;
 mod macros;
 mod parse;
+pub mod snapshot;
 
 use generate::*;
 use parse::*;
@@ -14,6 +15,8 @@ pub use sedona_ecs_macros::*;
 
 pub use itertools::chain;
 pub use itertools::izip;
+pub use log;
 pub use rayon;
 pub use rayon::prelude::*;
+pub use ron;
 pub use uuid::Uuid;
\ No newline at end of file
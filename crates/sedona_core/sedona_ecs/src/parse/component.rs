@@ -0,0 +1,184 @@
+use crate::parse::{Diagnostic, Location, SystemAttributes, SystemDef, parse_system_arguments, propagation_system_def};
+use quote::ToTokens;
+use syn::punctuated::Punctuated;
+use syn::spanned::Spanned;
+use syn::{Fields, ItemStruct, Meta, Token};
+
+#[derive(Debug)]
+pub struct ComponentDef {
+    pub name: String,
+    pub location: Location,
+    pub fields: Vec<ComponentDefField>,
+    /// Set by `#[component(propagate(...))]`: the component implements `Propagate` and a
+    /// built-in system carries it down the hierarchy.
+    pub propagate: bool,
+}
+
+#[derive(Debug)]
+pub struct ComponentDefField {
+    /// The field name, or its index for tuple structs.
+    pub member: String,
+    pub data_type: String,
+}
+
+impl ComponentDefField {
+    /// Returns `true` if any identifier in the field type is one of `names`.
+    pub fn mentions(&self, names: &[&str]) -> bool {
+        type_mentions(&self.data_type, names)
+    }
+}
+
+/// Returns `true` if any identifier in `data_type` is one of `names`.
+pub fn type_mentions(data_type: &str, names: &[&str]) -> bool {
+    data_type
+        .split(|c: char| !c.is_alphanumeric() && c != '_')
+        .any(|ident| names.contains(&ident))
+}
+
+/// Parses an `ItemStruct` and adds a `ComponentDef` to the list of collected components
+/// if the struct is marked with a `#[component]` attribute.
+///
+/// `#[component(propagate(group = update, after = foo))]` also adds the built-in system
+/// carrying the component down the hierarchy, scheduled like a `#[system]`.
+pub fn parse_component_def(
+    item_struct: &ItemStruct,
+    components: &mut Vec<ComponentDef>,
+    systems: &mut Vec<SystemDef>,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    let Some(attribute) = item_struct.attrs.iter().find(|attribute| attribute.path().is_ident("component")) else {
+        return;
+    };
+
+    let name = item_struct.ident.to_string();
+    let location = Location::from_span(item_struct.ident.span());
+    let propagate = parse_component_attributes(&attribute.meta, diagnostics);
+    let propagates = propagate.is_some();
+
+    if let Some(attributes) = propagate {
+        systems.push(propagation_system_def(&name, location.clone(), attributes));
+    }
+
+    components.push(ComponentDef {
+        name,
+        location,
+        fields: parse_component_def_fields(&item_struct.fields),
+        propagate: propagates,
+    });
+}
+
+/// Reads the `propagate` argument of a `#[component(...)]` attribute, if there is one.
+fn parse_component_attributes(meta: &Meta, diagnostics: &mut Vec<Diagnostic>) -> Option<SystemAttributes> {
+    // #[component]
+    let Meta::List(list) = meta else {
+        return None;
+    };
+
+    let arguments = match list.parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated) {
+        Ok(arguments) => arguments,
+        Err(error) => {
+            diagnostics.push(Diagnostic::error(
+                Location::from_span(error.span()),
+                format!("malformed #[component] attribute: {error}"),
+            ));
+            return None;
+        }
+    };
+
+    let mut propagate = None;
+    for argument in arguments {
+        match &argument {
+            // #[component(propagate(group = update))]
+            Meta::List(list) if list.path.is_ident("propagate") => {
+                propagate = Some(parse_system_arguments(list, "component(propagate)", diagnostics));
+            }
+            // #[component(propagate)]
+            Meta::Path(path) if path.is_ident("propagate") => {
+                propagate = Some(SystemAttributes::default());
+            }
+            _ => diagnostics.push(Diagnostic::warning(
+                Location::from_span(argument.span()),
+                format!(
+                    "unknown argument `{}` in #[component] attribute, expected `propagate(...)`",
+                    argument.path().to_token_stream(),
+                ),
+            )),
+        }
+    }
+
+    propagate
+}
+
+/// Lists the members of a struct with their types, by name or by index for tuple structs.
+pub fn parse_component_def_fields(fields: &Fields) -> Vec<ComponentDefField> {
+    match fields {
+        Fields::Named(named_fields) => named_fields
+            .named
+            .iter()
+            .map(|field| ComponentDefField {
+                member: field.ident.as_ref().unwrap().to_string(),
+                data_type: field.ty.to_token_stream().to_string(),
+            })
+            .collect(),
+        Fields::Unnamed(unnamed_fields) => unnamed_fields
+            .unnamed
+            .iter()
+            .enumerate()
+            .map(|(index, field)| ComponentDefField {
+                member: index.to_string(),
+                data_type: field.ty.to_token_stream().to_string(),
+            })
+            .collect(),
+        Fields::Unit => vec![],
+    }
+}
+
+/// Returns the names of the components that hold entity references, directly through an
+/// `Entity` field or through another such component.
+pub fn components_with_entity_refs(components: &[ComponentDef]) -> Vec<String> {
+    let mut names = vec![String::from("Entity")];
+
+    loop {
+        let known: Vec<&str> = names.iter().map(String::as_str).collect();
+        let found: Vec<String> = components
+            .iter()
+            .filter(|component| !known.contains(&component.name.as_str()))
+            .filter(|component| component.fields.iter().any(|field| field.mentions(&known)))
+            .map(|component| component.name.clone())
+            .collect();
+
+        if found.is_empty() {
+            break;
+        }
+
+        names.extend(found);
+    }
+
+    names.remove(0);
+    names
+}
+
+/// The components `generate_hierarchy` defines in every world, so the other generators
+/// can treat them like `#[component]` structs.
+pub fn hierarchy_component_defs() -> Vec<ComponentDef> {
+    vec![
+        ComponentDef {
+            name: String::from("Parent"),
+            location: Location::default(),
+            fields: vec![ComponentDefField {
+                member: String::from("0"),
+                data_type: String::from("Option < Entity >"),
+            }],
+            propagate: false,
+        },
+        ComponentDef {
+            name: String::from("Children"),
+            location: Location::default(),
+            fields: vec![ComponentDefField {
+                member: String::from("0"),
+                data_type: String::from("Vec < Entity >"),
+            }],
+            propagate: false,
+        },
+    ]
+}
@@ -0,0 +1,72 @@
+use proc_macro2::Span;
+use std::fmt::{Display, Formatter};
+
+/// A position in a source file, reported alongside a diagnostic.
+#[derive(Debug, Default, Clone)]
+pub struct Location {
+    pub file: String,
+    pub line: usize,
+}
+
+impl Location {
+    /// The line of `span`. The file is filled in once the whole file has been parsed.
+    pub fn from_span(span: Span) -> Self {
+        Self {
+            file: String::new(),
+            line: span.start().line,
+        }
+    }
+}
+
+impl Display for Location {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.file, self.line)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiagnosticLevel {
+    Warning,
+    Error,
+}
+
+/// A problem found while collecting ECS definitions, reported to cargo by `build_ecs`.
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    pub level: DiagnosticLevel,
+    pub location: Location,
+    pub message: String,
+}
+
+impl Diagnostic {
+    pub fn warning(location: Location, message: impl Into<String>) -> Self {
+        Self {
+            level: DiagnosticLevel::Warning,
+            location,
+            message: message.into(),
+        }
+    }
+
+    pub fn error(location: Location, message: impl Into<String>) -> Self {
+        Self {
+            level: DiagnosticLevel::Error,
+            location,
+            message: message.into(),
+        }
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.level == DiagnosticLevel::Error
+    }
+
+    /// Prints the diagnostic as a cargo build script directive. Errors fail the build once
+    /// the build script finishes.
+    pub fn emit(&self) {
+        let directive = match self.level {
+            DiagnosticLevel::Warning => "warning",
+            DiagnosticLevel::Error => "error",
+        };
+
+        println!("cargo::{directive}={}: {}", self.location, self.message);
+    }
+}
@@ -1,9 +1,13 @@
+use crate::parse::{Diagnostic, Location};
 use quote::ToTokens;
-use syn::{Attribute, Fields, ItemStruct, Meta, MetaList};
+use syn::punctuated::Punctuated;
+use syn::spanned::Spanned;
+use syn::{Attribute, Expr, ExprLit, Fields, ItemStruct, Lit, Meta, MetaNameValue, Token};
 
 #[derive(Debug)]
 pub struct EntityDef {
     pub name: String,
+    pub location: Location,
     pub fields: Vec<EntityDefField>,
     pub serialize: bool,
 }
@@ -21,12 +25,18 @@ pub struct EntityDefField {
 /// # Parameters
 /// - `item_struct`: The struct item being inspected.
 /// - `entities`: The list to which `EntityDef`s are appended if applicable.
-pub fn parse_entity_def(item_struct: ItemStruct, entities: &mut Vec<EntityDef>) {
+/// - `diagnostics`: The list to which attribute problems are appended.
+pub fn parse_entity_def(
+    item_struct: ItemStruct,
+    entities: &mut Vec<EntityDef>,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
     for attribute in &item_struct.attrs {
-        if let Some(serialize) = parse_entity_attributes(&attribute.meta) {
+        if let Some(serialize) = parse_entity_attributes(&attribute.meta, diagnostics) {
             let fields = parse_entity_def_fields(&item_struct.fields);
             entities.push(EntityDef {
                 name: item_struct.ident.to_string(),
+                location: Location::from_span(item_struct.ident.span()),
                 fields,
                 serialize,
             });
@@ -40,7 +50,9 @@ pub fn parse_entity_def(item_struct: ItemStruct, entities: &mut Vec<EntityDef>)
 /// Returns `Some(true)` if the attribute is `#[entity]` or `#[entity(serialize = true)]`.
 /// Returns `Some(false)` if `#[entity(serialize = false)]`.
 /// Returns `None` if the attribute is not `#[entity]`.
-fn parse_entity_attributes(meta: &Meta) -> Option<bool> {
+///
+/// Malformed attributes are reported as errors and unknown keys as warnings.
+fn parse_entity_attributes(meta: &Meta, diagnostics: &mut Vec<Diagnostic>) -> Option<bool> {
     match meta {
         // #[entity]
         Meta::Path(path) => {
@@ -52,25 +64,46 @@ fn parse_entity_attributes(meta: &Meta) -> Option<bool> {
         // #[entity(serialize = false)]
         Meta::List(list) => {
             if list.path.is_ident("entity") {
-                let tokens_string = list.tokens.to_string();
-                let mut key_value_parts = tokens_string.split('=');
+                let arguments = match list
+                    .parse_args_with(Punctuated::<MetaNameValue, Token![,]>::parse_terminated)
+                {
+                    Ok(arguments) => arguments,
+                    Err(error) => {
+                        diagnostics.push(Diagnostic::error(
+                            Location::from_span(error.span()),
+                            format!("malformed #[entity] attribute: {error}"),
+                        ));
+                        return Some(true);
+                    }
+                };
 
-                if let Some(key) = key_value_parts.next() {
-                    if let Some(value) = key_value_parts.next() {
-                        let key = key.trim();
-                        let value = value.trim();
+                let mut serialize = true;
+
+                for argument in arguments {
+                    if !argument.path.is_ident("serialize") {
+                        diagnostics.push(Diagnostic::warning(
+                            Location::from_span(argument.path.span()),
+                            format!(
+                                "unknown key `{}` in #[entity] attribute, expected `serialize`",
+                                argument.path.to_token_stream(),
+                            ),
+                        ));
+                        continue;
+                    }
 
-                        if key == "serialize" {
-                            return match value {
-                                "false" => Some(false),
-                                "true" => Some(true),
-                                _ => Some(true), // default to true if malformed
-                            };
-                        }
+                    match &argument.value {
+                        Expr::Lit(ExprLit {
+                            lit: Lit::Bool(value),
+                            ..
+                        }) => serialize = value.value,
+                        value => diagnostics.push(Diagnostic::error(
+                            Location::from_span(value.span()),
+                            "`serialize` in #[entity] attribute must be `true` or `false`",
+                        )),
                     }
                 }
 
-                return Some(true); // no explicit value, default to true
+                return Some(serialize);
             }
         }
 
@@ -0,0 +1,93 @@
+use crate::parse::{Diagnostic, EntityDef, Location};
+use syn::spanned::Spanned;
+use syn::{ItemFn, Meta};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HookKind {
+    Create,
+    Destroy,
+}
+
+impl HookKind {
+    fn attribute(&self) -> &'static str {
+        match self {
+            HookKind::Create => "on_create",
+            HookKind::Destroy => "on_destroy",
+        }
+    }
+}
+
+/// A function run with `(&mut World, Entity)` when an entity is created or despawned.
+#[derive(Debug)]
+pub struct HookDef {
+    pub name: String,
+    pub location: Location,
+    pub kind: HookKind,
+    /// The entity or component type the hook is for, or `None` for every entity.
+    pub target: Option<String>,
+}
+
+impl HookDef {
+    /// Returns `true` if the hook runs for entities of this type.
+    pub fn applies_to(&self, entity: &EntityDef) -> bool {
+        match &self.target {
+            Some(target) => {
+                entity.name == *target || entity.fields.iter().any(|field| field.data_type == *target)
+            }
+            None => true,
+        }
+    }
+}
+
+/// Parses a function item and adds a `HookDef` for each `#[on_create]` or `#[on_destroy]`
+/// attribute on it.
+///
+/// The attribute names the entity or component the hook is for, e.g.
+/// `#[on_destroy(NodeEntityRef)]`. Without one, the hook runs for every entity.
+pub fn parse_hook_defs(item_fn: &ItemFn, hooks: &mut Vec<HookDef>, diagnostics: &mut Vec<Diagnostic>) {
+    for attribute in &item_fn.attrs {
+        let Some(kind) = [HookKind::Create, HookKind::Destroy]
+            .into_iter()
+            .find(|kind| attribute.path().is_ident(kind.attribute()))
+        else {
+            continue;
+        };
+
+        let target = match &attribute.meta {
+            // #[on_destroy]
+            Meta::Path(_) => None,
+            // #[on_destroy(PropEntity)]
+            Meta::List(list) => match list.parse_args::<syn::Path>() {
+                Ok(path) => path.segments.last().map(|segment| segment.ident.to_string()),
+                Err(error) => {
+                    diagnostics.push(Diagnostic::error(
+                        Location::from_span(error.span()),
+                        format!(
+                            "malformed #[{}] attribute, expected an entity or component type: {error}",
+                            kind.attribute(),
+                        ),
+                    ));
+                    continue;
+                }
+            },
+            // #[on_destroy = PropEntity]
+            Meta::NameValue(name_value) => {
+                diagnostics.push(Diagnostic::error(
+                    Location::from_span(name_value.span()),
+                    format!(
+                        "unsupported #[{0} = ...] attribute, use #[{0}(Type)]",
+                        kind.attribute(),
+                    ),
+                ));
+                continue;
+            }
+        };
+
+        hooks.push(HookDef {
+            name: item_fn.sig.ident.to_string(),
+            location: Location::from_span(item_fn.sig.ident.span()),
+            kind,
+            target,
+        });
+    }
+}
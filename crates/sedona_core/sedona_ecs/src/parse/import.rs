@@ -0,0 +1,81 @@
+use crate::parse::CollectedData;
+use std::path::{Component, Path};
+
+/// An item of a source with a module prefix, which the generated code imports by path.
+#[derive(Debug, Clone)]
+pub struct ItemImport {
+    /// The full path of the item, e.g. `sedona_audio::systems::emitter::audio_update`.
+    pub path: String,
+}
+
+/// Returns the module a source file defines, relative to the root of its source.
+///
+/// `lib.rs`, `main.rs` and `mod.rs` define their directory's module, and every other file
+/// the module named after it, e.g. `systems/emitter.rs` defines `systems::emitter`.
+/// Returns `None` for files outside of `root`.
+pub fn file_module_path(root: &Path, file: &Path) -> Option<Vec<String>> {
+    let relative = file.strip_prefix(root).ok()?.with_extension("");
+
+    let mut segments: Vec<String> = relative
+        .components()
+        .map(|component| match component {
+            Component::Normal(segment) => segment.to_str().map(str::to_string),
+            _ => None,
+        })
+        .collect::<Option<_>>()?;
+
+    let root_file = segments.len() == 1 && matches!(segments[0].as_str(), "lib" | "main");
+    if root_file || segments.last().is_some_and(|segment| segment == "mod") {
+        segments.pop();
+    }
+
+    Some(segments)
+}
+
+/// Lists the items collected from one file, to be imported from `module`.
+///
+/// Free functions are left out, since run conditions are called by their full path and
+/// functions of the same name may come from several sources.
+pub fn collect_item_imports(collected: &CollectedData, module: &str) -> Vec<ItemImport> {
+    collected
+        .entities
+        .iter()
+        .map(|entity| &entity.name)
+        .chain(collected.components.iter().map(|component| &component.name))
+        .chain(collected.resources.iter().map(|resource| &resource.name))
+        .chain(collected.systems.iter().map(|system| &system.name))
+        .chain(collected.hooks.iter().map(|hook| &hook.name))
+        .map(|name| ItemImport {
+            path: format!("{module}::{name}"),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn module_path(file: &str) -> Option<Vec<String>> {
+        file_module_path(Path::new("/game/src"), Path::new(file))
+    }
+
+    #[test]
+    fn files_define_the_module_named_after_them() {
+        assert_eq!(module_path("/game/src/systems/emitter.rs").unwrap(), ["systems", "emitter"]);
+        assert_eq!(module_path("/game/src/resources.rs").unwrap(), ["resources"]);
+    }
+
+    #[test]
+    fn root_and_mod_files_define_their_directory() {
+        assert!(module_path("/game/src/lib.rs").unwrap().is_empty());
+        assert!(module_path("/game/src/main.rs").unwrap().is_empty());
+        assert_eq!(module_path("/game/src/systems/mod.rs").unwrap(), ["systems"]);
+        assert_eq!(module_path("/game/src/systems/lib.rs").unwrap(), ["systems", "lib"]);
+    }
+
+    #[test]
+    fn files_outside_of_the_root_define_no_module() {
+        assert_eq!(module_path("/game/build.rs"), None);
+        assert_eq!(module_path("/other/src/lib.rs"), None);
+    }
+}
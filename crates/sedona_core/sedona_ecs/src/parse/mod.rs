@@ -1,9 +1,21 @@
+pub mod component;
+pub mod diagnostic;
 pub mod entity;
+pub mod hook;
+pub mod import;
 pub mod parse;
 pub mod query;
+pub mod resource;
 pub mod system;
+pub mod validate;
 
+pub use component::*;
+pub use diagnostic::*;
 pub use entity::*;
+pub use hook::*;
+pub use import::*;
 pub use parse::*;
 pub use query::*;
+pub use resource::*;
 pub use system::*;
+pub use validate::*;
@@ -1,14 +1,34 @@
-use crate::parse::{parse_entity_def, parse_system_def, EntityDef, QueryDef, SystemDef};
-use std::collections::HashSet;
+use crate::parse::{
+    parse_component_def, parse_entity_def, parse_hook_defs, parse_query_def, parse_resource_def,
+    parse_system_def, ComponentDef, Diagnostic, EntityDef, FunctionDef, HookDef, ItemImport,
+    Location, QueryDef, ResourceDef, SystemDef,
+};
+use quote::ToTokens;
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::hash::{DefaultHasher, Hash, Hasher};
-use syn::Item;
+use syn::visit::{self, Visit};
+use syn::spanned::Spanned;
+use syn::{ExprPath, FnArg, GenericArgument, Item, PathArguments, TypePath};
 
 #[derive(Debug, Default)]
 pub struct CollectedData {
     pub entities: Vec<EntityDef>,
+    pub components: Vec<ComponentDef>,
+    pub resources: Vec<ResourceDef>,
     pub queries: Vec<QueryDef>,
     pub systems: Vec<SystemDef>,
+    pub hooks: Vec<HookDef>,
+    /// Items of sources with a module prefix, which the generated code imports by path.
+    pub imports: Vec<ItemImport>,
+    /// Every free function, keyed by its name qualified by its module, used to call run
+    /// conditions.
+    pub functions: HashMap<String, FunctionDef>,
+    /// Groups whose non-conflicting systems run concurrently.
+    pub parallel_groups: HashSet<String>,
+    /// Groups whose `systems_<group>` runner is referenced somewhere in the sources.
+    pub called_groups: HashSet<String>,
+    pub diagnostics: Vec<Diagnostic>,
 }
 
 impl CollectedData {
@@ -23,40 +43,164 @@ impl CollectedData {
     }
 }
 
-/// Parses a Rust source file and collects ECS definitions (`EntityDef`, `SystemDef`, and `QueryDef`).
+/// Parses a Rust source file and collects ECS definitions (`EntityDef`, `ComponentDef`,
+/// `ResourceDef`, `SystemDef`, `HookDef`, and `QueryDef`).
 ///
 /// Reads and parses a file at the specified path, extracting ECS-related declarations
-/// from structs and functions annotated with relevant attributes.
+/// from structs and functions annotated with relevant attributes. Files that cannot be
+/// read or parsed are reported in `diagnostics` rather than failing the build script.
 pub fn collect_ecs_defs(path: &str) -> CollectedData {
+    match fs::read_to_string(path) {
+        Ok(content) => collect_ecs_defs_from_source(path, &content),
+        Err(error) => CollectedData {
+            diagnostics: vec![Diagnostic::error(
+                Location {
+                    file: path.to_string(),
+                    line: 0,
+                },
+                format!("failed to read file: {error}"),
+            )],
+            ..Default::default()
+        },
+    }
+}
+
+/// Collects the ECS definitions of `content`, reporting them as coming from `path`.
+pub(crate) fn collect_ecs_defs_from_source(path: &str, content: &str) -> CollectedData {
     let mut entities = Vec::new();
+    let mut components = Vec::new();
+    let mut resources = Vec::new();
     let mut queries = Vec::new();
     let mut systems = Vec::new();
+    let mut hooks = Vec::new();
+    let mut functions = HashMap::new();
+    let mut called_groups = HashSet::new();
+    let mut diagnostics = Vec::new();
 
-    let content = match fs::read_to_string(path) {
-        Ok(content) => content,
-        Err(error) => {
-            panic!("Failed to read file '{path}': {error}");
-        }
+    let file_location = |line| Location {
+        file: path.to_string(),
+        line,
     };
 
-    let parsed_file = match syn::parse_file(&content) {
+    let parsed_file = match syn::parse_file(content) {
         Ok(file) => file,
         Err(error) => {
-            panic!("Failed to parse file '{path}': {error}");
+            return CollectedData {
+                diagnostics: vec![Diagnostic::error(
+                    file_location(error.span().start().line),
+                    format!("failed to parse file: {error}"),
+                )],
+                ..Default::default()
+            };
         }
     };
 
+    SourceVisitor {
+        queries: &mut queries,
+        called_groups: &mut called_groups,
+    }
+    .visit_file(&parsed_file);
+
     for item in parsed_file.items {
         match item {
-            Item::Struct(item_struct) => parse_entity_def(item_struct, &mut entities),
-            Item::Fn(item_fn) => parse_system_def(item_fn, &mut systems, &mut queries),
+            Item::Struct(item_struct) => {
+                parse_component_def(&item_struct, &mut components, &mut systems, &mut diagnostics);
+                parse_resource_def(&item_struct, &mut resources);
+                parse_entity_def(item_struct, &mut entities, &mut diagnostics)
+            }
+            Item::Fn(item_fn) => {
+                let params = item_fn
+                    .sig
+                    .inputs
+                    .iter()
+                    .filter_map(|input| match input {
+                        FnArg::Typed(pat_type) => Some(pat_type.pat.to_token_stream().to_string()),
+                        FnArg::Receiver(_) => None,
+                    })
+                    .collect();
+                let name = item_fn.sig.ident.to_string();
+                functions.insert(
+                    name.clone(),
+                    FunctionDef {
+                        path: name.clone(),
+                        name,
+                        params,
+                        ..Default::default()
+                    },
+                );
+
+                parse_hook_defs(&item_fn, &mut hooks, &mut diagnostics);
+
+                parse_system_def(item_fn, &mut systems, &mut queries, &mut diagnostics)
+            }
             _ => {}
         }
     }
 
+    let locations = entities
+        .iter_mut()
+        .map(|entity| &mut entity.location)
+        .chain(components.iter_mut().map(|component| &mut component.location))
+        .chain(resources.iter_mut().map(|resource| &mut resource.location))
+        .chain(queries.iter_mut().map(|query| &mut query.location))
+        .chain(systems.iter_mut().map(|system| &mut system.location))
+        .chain(hooks.iter_mut().map(|hook| &mut hook.location))
+        .chain(diagnostics.iter_mut().map(|diagnostic| &mut diagnostic.location));
+    for location in locations {
+        location.file = path.to_string();
+    }
+
     CollectedData {
         entities,
+        components,
+        resources,
         queries,
         systems,
+        hooks,
+        functions,
+        called_groups,
+        diagnostics,
+        ..Default::default()
+    }
+}
+
+/// Collects every `Query<T, F>` type written anywhere in a file, so queries built with
+/// `Query::new()` outside of system parameters (helpers, tests) get implementations too,
+/// and every group runner (`systems_<group>`) the file refers to.
+struct SourceVisitor<'q> {
+    queries: &'q mut Vec<QueryDef>,
+    called_groups: &'q mut HashSet<String>,
+}
+
+impl<'ast> Visit<'ast> for SourceVisitor<'_> {
+    fn visit_type_path(&mut self, type_path: &'ast TypePath) {
+        if let Some(segment) = type_path.path.segments.last()
+            && segment.ident == "Query"
+            && let PathArguments::AngleBracketed(arguments) = &segment.arguments
+        {
+            let mut types = arguments.args.iter().filter_map(|argument| match argument {
+                GenericArgument::Type(ty) => Some(ty),
+                _ => None,
+            });
+
+            if let Some(query_ty) = types.next()
+                && let Some(mut query) = parse_query_def(query_ty, types.next())
+            {
+                query.location = Location::from_span(type_path.span());
+                self.queries.push(query);
+            }
+        }
+
+        visit::visit_type_path(self, type_path);
+    }
+
+    fn visit_expr_path(&mut self, expr_path: &'ast ExprPath) {
+        if let Some(segment) = expr_path.path.segments.last()
+            && let Some(group) = segment.ident.to_string().strip_prefix("systems_")
+        {
+            self.called_groups.insert(group.to_string());
+        }
+
+        visit::visit_expr_path(self, expr_path);
     }
 }
@@ -1,25 +1,124 @@
+use crate::parse::{EntityDef, Location};
 use quote::ToTokens;
 use std::hash::{Hash, Hasher};
-use syn::Type;
+use syn::{GenericArgument, PathArguments, Type};
 
 #[derive(Debug, Clone)]
 pub struct QueryDef {
-    pub mut_fields: Vec<String>,
-    pub const_fields: Vec<String>,
+    pub fields: Vec<QueryDefField>,
+    pub filters: Vec<QueryFilter>,
+    /// Where the query type was first written. Not part of the query's identity.
+    pub location: Location,
+}
+
+/// A single element of a query tuple, such as `&T`, `&mut T`, `Option<&T>` or `Via<R, &T>`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct QueryDefField {
+    pub data_type: String,
+    pub mutable: bool,
+    pub optional: bool,
+    /// For `Via<R, &T>`, the component `R` of the row pointing at the entity `T` is fetched from.
+    pub via: Option<String>,
+}
+
+impl QueryDefField {
+    /// The component the field reads from the matched row itself.
+    pub fn row_component(&self) -> &str {
+        self.via.as_deref().unwrap_or(&self.data_type)
+    }
+}
+
+/// A row filter from the second generic argument of `Query<T, F>`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum QueryFilter {
+    /// `Added<T>`: the component was added since the system last ran.
+    Added(String),
+    /// `Changed<T>`: the component was added or mutably accessed since the system last ran.
+    Changed(String),
+    /// `With<T>`: the entity type has the component, without fetching it.
+    With(String),
+    /// `Without<T>`: the entity type does not have the component.
+    Without(String),
+}
+
+impl QueryFilter {
+    /// The filter's type name, e.g. `Changed`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            QueryFilter::Added(_) => "Added",
+            QueryFilter::Changed(_) => "Changed",
+            QueryFilter::With(_) => "With",
+            QueryFilter::Without(_) => "Without",
+        }
+    }
+
+    pub fn component(&self) -> &str {
+        match self {
+            QueryFilter::Added(component)
+            | QueryFilter::Changed(component)
+            | QueryFilter::With(component)
+            | QueryFilter::Without(component) => component,
+        }
+    }
+
+    /// Returns `true` if the filter is evaluated per row from change ticks rather than
+    /// per entity type.
+    pub fn is_tick_filter(&self) -> bool {
+        matches!(self, QueryFilter::Added(_) | QueryFilter::Changed(_))
+    }
+}
+
+impl QueryDef {
+    pub fn is_mutable(&self) -> bool {
+        self.fields.iter().any(|field| field.mutable)
+    }
+
+    /// Returns `true` if any field follows a reference to another entity.
+    pub fn has_via(&self) -> bool {
+        self.fields.iter().any(|field| field.via.is_some())
+    }
+
+    /// Returns `true` if rows of `entity` can be returned by the query: it has every
+    /// non-optional field and satisfies every filter.
+    pub fn matches(&self, entity: &EntityDef) -> bool {
+        let has_component = |data_type: &str| {
+            entity
+                .fields
+                .iter()
+                .any(|entity_field| entity_field.data_type == data_type)
+        };
+
+        let fields_present = self
+            .fields
+            .iter()
+            .filter(|field| !field.optional)
+            .all(|field| has_component(field.row_component()));
+
+        let filters_pass = self.filters.iter().all(|filter| match filter {
+            QueryFilter::Without(component) => !has_component(component),
+            _ => has_component(filter.component()),
+        });
+
+        fields_present && filters_pass
+    }
+
+    pub fn has_tick_filters(&self) -> bool {
+        self.filters.iter().any(QueryFilter::is_tick_filter)
+    }
 }
 
 impl Eq for QueryDef {}
 
 impl PartialEq for QueryDef {
     fn eq(&self, other: &Self) -> bool {
-        self.mut_fields == other.mut_fields && self.const_fields == other.const_fields
+        self.fields == other.fields && self.filters == other.filters
     }
 }
 
 impl Hash for QueryDef {
     fn hash<H: Hasher>(&self, state: &mut H) {
-        self.mut_fields.hash(state);
-        self.const_fields.hash(state);
+        self.fields.hash(state);
+        self.filters.hash(state);
     }
 }
 
@@ -27,57 +126,129 @@ impl Hash for QueryDef {
 ///
 /// This function supports queries in the form of:
 /// - Single references: `&T`, `&mut T`
-/// - Tuples of references: `(&T, &mut U)`
+/// - Optional references: `Option<&T>`, `Option<&mut T>`
+/// - Joins along a reference component: `Via<R, &T>`, `Via<R, &mut T>`
+/// - Tuples of the above: `(&T, &mut U, Option<&V>)`
 ///
 /// # Parameters
 /// - `ty`: The type of the query generic (typically inside `Query<T>`).
+/// - `filter`: The optional filter generic (the `F` in `Query<T, F>`).
 ///
 /// # Returns
 /// - `Some(QueryDef)` if any valid query fields were extracted.
 /// - `None` if the type contains no extractable fields or isn't a supported form.
-pub fn parse_query_def(ty: &Type) -> Option<QueryDef> {
+pub fn parse_query_def(ty: &Type, filter: Option<&Type>) -> Option<QueryDef> {
+    let filters = filter.map(parse_query_filters).unwrap_or_default();
+
+    let fields: Vec<QueryDefField> = match ty {
+        Type::Tuple(type_tuple) => type_tuple
+            .elems
+            .iter()
+            .filter_map(parse_query_def_field)
+            .collect(),
+        _ => parse_query_def_field(ty).into_iter().collect(),
+    };
+
+    if fields.is_empty() {
+        None
+    } else {
+        Some(QueryDef {
+            fields,
+            filters,
+            location: Location::default(),
+        })
+    }
+}
+
+/// Parses a single query element: `&T`, `&mut T`, `Option<&T>`, `Option<&mut T>`,
+/// `Via<R, &T>` or `Via<R, &mut T>`.
+fn parse_query_def_field(ty: &Type) -> Option<QueryDefField> {
     match ty {
         Type::Reference(type_reference) => {
-            let elem = &type_reference.elem;
-            if let Type::Path(type_path) = &**elem {
-                let name = type_path.to_token_stream().to_string();
-                let (mutable_fields, const_fields) = if type_reference.mutability.is_some() {
-                    (vec![name], vec![])
-                } else {
-                    (vec![], vec![name])
-                };
-                Some(QueryDef {
-                    mut_fields: mutable_fields,
-                    const_fields,
+            if let Type::Path(type_path) = &*type_reference.elem {
+                Some(QueryDefField {
+                    data_type: type_path.to_token_stream().to_string(),
+                    mutable: type_reference.mutability.is_some(),
+                    optional: false,
+                    via: None,
                 })
             } else {
                 None
             }
         }
-        Type::Tuple(type_tuple) => {
-            let mut mutable_fields = Vec::new();
-            let mut const_fields = Vec::new();
-
-            for elem in &type_tuple.elems {
-                if let Type::Reference(type_reference) = elem {
-                    let name = type_reference.elem.to_token_stream().to_string();
-                    if type_reference.mutability.is_some() {
-                        mutable_fields.push(name);
-                    } else {
-                        const_fields.push(name);
-                    }
-                }
+        Type::Path(type_path) => {
+            let segment = type_path.path.segments.last()?;
+            if segment.ident != "Option" && segment.ident != "Via" {
+                return None;
             }
 
-            if mutable_fields.is_empty() && const_fields.is_empty() {
-                None
-            } else {
-                Some(QueryDef {
-                    mut_fields: mutable_fields,
-                    const_fields,
-                })
+            let PathArguments::AngleBracketed(arguments) = &segment.arguments else {
+                return None;
+            };
+            let mut types = arguments.args.iter().filter_map(|argument| match argument {
+                GenericArgument::Type(ty) => Some(ty),
+                _ => None,
+            });
+
+            if segment.ident == "Via" {
+                let reference = types.next()?.to_token_stream().to_string();
+                let target_ty = types.next()?;
+
+                return match target_ty {
+                    Type::Reference(_) => parse_query_def_field(target_ty).map(|field| QueryDefField {
+                        via: Some(reference),
+                        ..field
+                    }),
+                    _ => None,
+                };
+            }
+
+            match types.next()? {
+                inner_ty @ Type::Reference(_) => parse_query_def_field(inner_ty).map(|field| QueryDefField {
+                    optional: true,
+                    ..field
+                }),
+                _ => None,
             }
         }
         _ => None,
     }
 }
+
+/// Parses the filter generic of a query into a list of `QueryFilter`s.
+///
+/// Accepts a single filter (`Changed<T>`) or a tuple of filters (`(Changed<T>, Without<U>)`).
+/// Unrecognised filter types are ignored.
+fn parse_query_filters(ty: &Type) -> Vec<QueryFilter> {
+    match ty {
+        Type::Tuple(type_tuple) => type_tuple
+            .elems
+            .iter()
+            .filter_map(parse_query_filter)
+            .collect(),
+        _ => parse_query_filter(ty).into_iter().collect(),
+    }
+}
+
+fn parse_query_filter(ty: &Type) -> Option<QueryFilter> {
+    let Type::Path(type_path) = ty else {
+        return None;
+    };
+
+    let segment = type_path.path.segments.last()?;
+    let PathArguments::AngleBracketed(arguments) = &segment.arguments else {
+        return None;
+    };
+    let Some(GenericArgument::Type(component_ty)) = arguments.args.first() else {
+        return None;
+    };
+    let component = component_ty.to_token_stream().to_string();
+
+    match segment.ident.to_string().as_str() {
+        "Added" => Some(QueryFilter::Added(component)),
+        "Changed" => Some(QueryFilter::Changed(component)),
+        "With" => Some(QueryFilter::With(component)),
+        "Without" => Some(QueryFilter::Without(component)),
+        _ => None,
+    }
+}
@@ -0,0 +1,31 @@
+use crate::parse::{ComponentDefField, Location, parse_component_def_fields};
+use heck::ToSnakeCase;
+use syn::ItemStruct;
+
+#[derive(Debug, Default)]
+pub struct ResourceDef {
+    pub name: String,
+    pub location: Location,
+    pub fields: Vec<ComponentDefField>,
+}
+
+impl ResourceDef {
+    /// Name of the `WorldResources` field holding the resource.
+    pub fn field_name(&self) -> String {
+        self.name.to_snake_case()
+    }
+}
+
+/// Parses an `ItemStruct` and adds a `ResourceDef` to the list of collected resources
+/// if the struct is marked with a `#[resource]` attribute.
+pub fn parse_resource_def(item_struct: &ItemStruct, resources: &mut Vec<ResourceDef>) {
+    if !item_struct.attrs.iter().any(|attribute| attribute.path().is_ident("resource")) {
+        return;
+    }
+
+    resources.push(ResourceDef {
+        name: item_struct.ident.to_string(),
+        location: Location::from_span(item_struct.ident.span()),
+        fields: parse_component_def_fields(&item_struct.fields),
+    });
+}
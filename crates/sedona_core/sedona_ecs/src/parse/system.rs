@@ -1,19 +1,114 @@
-use crate::parse::{parse_query_def, QueryDef};
+use crate::parse::{parse_query_def, Diagnostic, Location, QueryDef, ResourceDef};
+use heck::ToSnakeCase;
 use quote::ToTokens;
-use syn::{FnArg, GenericArgument, ItemFn, Meta, PatType, PathArguments, PathSegment, Type};
+use syn::spanned::Spanned;
+use std::collections::{BTreeSet, HashMap};
+use syn::punctuated::Punctuated;
+use syn::{
+    Expr, FnArg, GenericArgument, ItemFn, Meta, MetaList, MetaNameValue, PatType, PathArguments,
+    PathSegment, Token, Type,
+};
 
 #[derive(Debug, Default)]
 pub struct SystemDef {
     pub name: String,
+    pub location: Location,
     pub group: String,
     pub params: Vec<SystemDefParam>,
+    /// Systems in the same group that must run after this one.
+    pub before: Vec<String>,
+    /// Systems in the same group that must run before this one.
+    pub after: Vec<String>,
+    /// Conditions that must all hold for the system to run.
+    pub run_if: Vec<RunCondition>,
+    /// The module the system is defined in, filled in by the build for each source file.
+    pub module: String,
+}
+
+/// A function deciding whether a system runs, called with the system parameters it names.
+#[derive(Debug, Default, Clone)]
+pub struct RunCondition {
+    /// The function as the attribute names it, e.g. `ready` or `conditions::ready`.
+    pub name: String,
+    /// The path the group runner calls the function by, filled in by `resolve_run_conditions`.
+    pub path: String,
+    /// The condition's parameter names, filled in by `resolve_run_conditions`.
+    pub params: Vec<String>,
+}
+
+/// A free function of the sources, which systems may name as a run condition.
+#[derive(Debug, Default, Clone)]
+pub struct FunctionDef {
+    pub name: String,
+    /// The module the function is defined in, e.g. `crate::systems::player`, filled in by
+    /// the build for each source file.
+    pub module: String,
+    /// The path the generated code calls the function by: its name for the crate being
+    /// built, whose world module imports its items itself, and its full path otherwise.
+    pub path: String,
+    pub params: Vec<String>,
+}
+
+impl FunctionDef {
+    /// The function's name qualified by its module, which `CollectedData::functions` is
+    /// keyed by.
+    pub fn qualified_name(&self) -> String {
+        if self.module.is_empty() {
+            self.name.clone()
+        } else {
+            format!("{}::{}", self.module, self.name)
+        }
+    }
+}
+
+/// The arguments of a `#[system(...)]` attribute.
+#[derive(Debug)]
+pub(crate) struct SystemAttributes {
+    group: String,
+    before: Vec<String>,
+    after: Vec<String>,
+    run_if: Vec<String>,
+}
+
+impl Default for SystemAttributes {
+    fn default() -> Self {
+        Self {
+            group: "main".into(),
+            before: vec![],
+            after: vec![],
+            run_if: vec![],
+        }
+    }
 }
 
 #[derive(Debug)]
 pub enum SystemDefParam {
-    Query(String),
+    Commands(String),
+    Query(SystemDefParamQuery),
     Reference(SystemDefParamReference),
+    /// A `&T` or `&mut T` of a `#[resource]`, resolved by `resolve_resource_params`.
+    Resource(SystemDefParamReference),
     Value(SystemDefParamValue),
+    WorldCell(String),
+}
+
+impl SystemDefParam {
+    pub fn name(&self) -> &str {
+        match self {
+            SystemDefParam::Commands(name) | SystemDefParam::WorldCell(name) => name,
+            SystemDefParam::Query(query) => &query.name,
+            SystemDefParam::Reference(reference) | SystemDefParam::Resource(reference) => {
+                &reference.name
+            }
+            SystemDefParam::Value(value) => &value.name,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SystemDefParamQuery {
+    pub name: String,
+    pub query: QueryDef,
 }
 
 #[derive(Debug, Default, Clone)]
@@ -37,22 +132,36 @@ pub struct SystemDefParamValue {
 /// - `item_fn`: A reference to the function item (`syn::ItemFn`) being processed.
 /// - `systems`: The list to which valid `SystemDef` entries are appended.
 /// - `queries`: The list to which any discovered `QueryDef` entries are appended.
+/// - `diagnostics`: The list to which problems with the attribute or parameters are appended.
 pub fn parse_system_def(
     item_fn: ItemFn,
     systems: &mut Vec<SystemDef>,
     queries: &mut Vec<QueryDef>,
+    diagnostics: &mut Vec<Diagnostic>,
 ) {
     let function_name = item_fn.sig.ident.to_string();
-    if let Some(system_group) = parse_system_attributes(&item_fn) {
+    if let Some(attributes) = parse_system_attributes(&item_fn, diagnostics) {
         let mut system_def = SystemDef {
-            group: system_group,
+            group: attributes.group,
             name: function_name.clone(),
+            location: Location::from_span(item_fn.sig.ident.span()),
             params: vec![],
+            before: attributes.before,
+            after: attributes.after,
+            run_if: attributes
+                .run_if
+                .into_iter()
+                .map(|name| RunCondition {
+                    name,
+                    ..Default::default()
+                })
+                .collect(),
+            module: String::new(),
         };
 
         for input in &item_fn.sig.inputs {
             if let FnArg::Typed(pat_type) = input {
-                if let Some(param) = parse_system_def_param(pat_type, queries) {
+                if let Some(param) = parse_system_def_param(pat_type, queries, diagnostics) {
                     system_def.params.push(param);
                 }
             }
@@ -62,49 +171,50 @@ pub fn parse_system_def(
     }
 }
 
-/// Extracts the system group name from the `#[system]` attribute of a function.
+/// Extracts the system group and ordering constraints from the `#[system]` attribute of a function.
 ///
 /// This function looks for the `#[system]` attribute in the given `ItemFn`.
-/// If a `group` is explicitly specified (e.g., `#[system(group = foo)]`), it returns that.
-/// Otherwise, if the attribute is present without a group, it defaults to `main`.
+/// If a `group` is explicitly specified (e.g., `#[system(group = foo)]`), it is used,
+/// otherwise the group defaults to `main`. `before` and `after` take a system name or a
+/// list of names (e.g., `#[system(group = update, after = [a, b], before = c)]`), and so
+/// does `run_if`, naming conditions that must all return `true` for the system to run.
 /// Returns `None` if the `#[system]` attribute is not found.
 ///
+/// Malformed attributes are reported as errors and unknown keys as warnings.
+///
 /// # Parameters
 /// - `item_fn`: A reference to a syn `ItemFn`, representing a function in the AST.
+/// - `diagnostics`: The list to which attribute problems are appended.
 ///
 /// # Returns
-/// - `Some(group_name)` if the function is a system and its group can be determined.
+/// - `Some(SystemAttributes)` if the function is a system.
 /// - `None` if the function does not have a `#[system]` attribute.
-fn parse_system_attributes(item_fn: &ItemFn) -> Option<String> {
+fn parse_system_attributes(
+    item_fn: &ItemFn,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> Option<SystemAttributes> {
     for attribute in &item_fn.attrs {
         match &attribute.meta {
             // #[system]
             Meta::Path(path) => {
                 if path.is_ident("system") {
-                    return Some("main".into());
+                    return Some(SystemAttributes::default());
                 }
             }
-            // #[system(group=foo)]
+            // #[system(group = foo, before = bar, after = [baz, qux])]
             Meta::List(list) => {
                 if list.path.is_ident("system") {
-                    let tokens_string = list.tokens.to_string();
-                    let mut key_value_parts = tokens_string.split('=');
-
-                    if let Some(key) = key_value_parts.next() {
-                        if let Some(value) = key_value_parts.next() {
-                            if key.trim() == "group" {
-                                return Some(value.trim().into());
-                            }
-                        }
-                    }
-
-                    return Some("main".into());
+                    return Some(parse_system_arguments(list, "system", diagnostics));
                 }
             }
             // #[system = foo]
             Meta::NameValue(name_value) => {
                 if name_value.path.is_ident("system") {
-                    return Some("main".into()); // Unsupported syntax.
+                    diagnostics.push(Diagnostic::error(
+                        Location::from_span(name_value.span()),
+                        "unsupported #[system = ...] attribute, use #[system(group = ...)]",
+                    ));
+                    return Some(SystemAttributes::default());
                 }
             }
         }
@@ -113,12 +223,362 @@ fn parse_system_attributes(item_fn: &ItemFn) -> Option<String> {
     None
 }
 
+/// Reads the `group`, `before`, `after` and `run_if` arguments of an attribute scheduling a
+/// system, such as `#[system(...)]`. `attribute` names it in diagnostics.
+pub(crate) fn parse_system_arguments(
+    list: &MetaList,
+    attribute: &str,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> SystemAttributes {
+    let mut attributes = SystemAttributes::default();
+
+    let arguments = match list.parse_args_with(Punctuated::<MetaNameValue, Token![,]>::parse_terminated) {
+        Ok(arguments) => arguments,
+        Err(error) => {
+            diagnostics.push(Diagnostic::error(
+                Location::from_span(error.span()),
+                format!("malformed #[{attribute}] attribute: {error}"),
+            ));
+            return attributes;
+        }
+    };
+
+    for argument in arguments {
+        let names = parse_system_names(&argument.value);
+
+        if argument.path.is_ident("group") {
+            if let Some(group) = names.into_iter().next() {
+                attributes.group = group;
+            }
+        } else if argument.path.is_ident("before") {
+            attributes.before.extend(names);
+        } else if argument.path.is_ident("after") {
+            attributes.after.extend(names);
+        } else if argument.path.is_ident("run_if") {
+            attributes.run_if.extend(names);
+        } else {
+            diagnostics.push(Diagnostic::warning(
+                Location::from_span(argument.path.span()),
+                format!(
+                    "unknown key `{}` in #[{attribute}] attribute, expected `group`, `before`, `after` or `run_if`",
+                    argument.path.to_token_stream(),
+                ),
+            ));
+        }
+    }
+
+    attributes
+}
+
+/// The built-in system `generate_hierarchy` defines for a `#[component(propagate(...))]`,
+/// taking only the world.
+pub(crate) fn propagation_system_def(
+    component: &str,
+    location: Location,
+    attributes: SystemAttributes,
+) -> SystemDef {
+    SystemDef {
+        name: propagation_system_name(component),
+        location,
+        group: attributes.group,
+        params: vec![SystemDefParam::Reference(SystemDefParamReference {
+            name: String::from("world"),
+            ty: String::from("World"),
+            mutable: true,
+        })],
+        before: attributes.before,
+        after: attributes.after,
+        run_if: attributes
+            .run_if
+            .into_iter()
+            .map(|name| RunCondition {
+                name,
+                ..Default::default()
+            })
+            .collect(),
+        module: String::new(),
+    }
+}
+
+/// Name of the built-in system carrying `component` down the hierarchy, e.g.
+/// `propagate_node_component`.
+pub fn propagation_system_name(component: &str) -> String {
+    format!("propagate_{}", component.to_snake_case())
+}
+
+/// Reads a system attribute value as a list of names: `foo` or `[foo, bar]`.
+fn parse_system_names(value: &Expr) -> Vec<String> {
+    match value {
+        Expr::Array(array) => array.elems.iter().flat_map(parse_system_names).collect(),
+        _ => vec![value.to_token_stream().to_string().replace(' ', "")],
+    }
+}
+
+/// Looks up the function of every run condition, so the group runner can call it with
+/// the matching parameters of its system.
+///
+/// A condition names a function of the system's own module, or relative to it, before
+/// one of any other module whose path ends with the name. Naming more of the path, such as
+/// `conditions::ready`, tells functions of the same name apart.
+///
+/// # Returns
+/// - `Err(Diagnostic)` if a condition names no function or more than one, or takes a
+///   parameter its system does not have.
+pub fn resolve_run_conditions(
+    systems: &mut [SystemDef],
+    functions: &HashMap<String, FunctionDef>,
+) -> Result<(), Diagnostic> {
+    for system in systems.iter_mut() {
+        let system_params: Vec<&str> = system.params.iter().map(SystemDefParam::name).collect();
+
+        for condition in system.run_if.iter_mut() {
+            let function = match find_run_condition(functions, &system.module, &condition.name) {
+                Ok(function) => function,
+                Err(candidates) if candidates.is_empty() => {
+                    return Err(Diagnostic::error(
+                        system.location.clone(),
+                        format!(
+                            "system `{}` runs if `{}`, but no function with that name was found",
+                            system.name, condition.name,
+                        ),
+                    ));
+                }
+                Err(candidates) => {
+                    let mut paths: Vec<String> = candidates
+                        .iter()
+                        .map(|function| format!("`{}`", function.qualified_name()))
+                        .collect();
+                    paths.sort();
+                    return Err(Diagnostic::error(
+                        system.location.clone(),
+                        format!(
+                            "system `{}` runs if `{}`, which could be any of {}; name it by path",
+                            system.name,
+                            condition.name,
+                            paths.join(", "),
+                        ),
+                    ));
+                }
+            };
+
+            if let Some(param) = function.params.iter().find(|p| !system_params.contains(&p.as_str())) {
+                return Err(Diagnostic::error(
+                    system.location.clone(),
+                    format!(
+                        "condition `{}` takes `{param}`, which is not a parameter of system `{}`",
+                        condition.name, system.name,
+                    ),
+                ));
+            }
+
+            condition.path = function.path.clone();
+            condition.params = function.params.clone();
+        }
+    }
+
+    Ok(())
+}
+
+/// Finds the function `name` refers to from `module`, or every function it might refer to.
+fn find_run_condition<'f>(
+    functions: &'f HashMap<String, FunctionDef>,
+    module: &str,
+    name: &str,
+) -> Result<&'f FunctionDef, Vec<&'f FunctionDef>> {
+    if !module.is_empty()
+        && let Some(function) = functions.get(&format!("{module}::{name}"))
+    {
+        return Ok(function);
+    }
+
+    let suffix = format!("::{name}");
+    let mut candidates: Vec<&FunctionDef> = functions
+        .iter()
+        .filter(|(path, _)| *path == name || path.ends_with(&suffix))
+        .map(|(_, function)| function)
+        .collect();
+
+    match candidates.len() {
+        1 => Ok(candidates.remove(0)),
+        _ => Err(candidates),
+    }
+}
+
+/// Turns the reference parameters naming a `#[resource]` into resource parameters, which
+/// the group runner borrows from the world's resources.
+///
+/// # Returns
+/// - `Err(Diagnostic)` if a system takes a resource alongside a world access that would
+///   alias it: `&mut World` or `WorldCell`, or `&World` with a `&mut` resource.
+pub fn resolve_resource_params(
+    systems: &mut [SystemDef],
+    resources: &[ResourceDef],
+) -> Result<(), Diagnostic> {
+    for system in systems.iter_mut() {
+        for param in system.params.iter_mut() {
+            // A path such as `&crate::resources::ActiveCamera` names the resource by its last
+            // segment; the runner refers to it by its bare name, which the imports bring in.
+            if let SystemDefParam::Reference(reference) = param
+                && let Some(resource) = resources.iter().find(|resource| {
+                    reference.ty.rsplit("::").next().map(str::trim) == Some(resource.name.as_str())
+                })
+            {
+                *param = SystemDefParam::Resource(SystemDefParamReference {
+                    ty: resource.name.clone(),
+                    ..reference.clone()
+                });
+            }
+        }
+
+        let writes_resource = system
+            .params
+            .iter()
+            .any(|param| matches!(param, SystemDefParam::Resource(resource) if resource.mutable));
+        let takes_resource = system
+            .params
+            .iter()
+            .any(|param| matches!(param, SystemDefParam::Resource(_)));
+
+        let aliased_world = system.params.iter().find(|param| match param {
+            SystemDefParam::Reference(reference) if reference.ty == "World" => {
+                reference.mutable || writes_resource
+            }
+            SystemDefParam::WorldCell(_) => true,
+            _ => false,
+        });
+
+        if takes_resource && let Some(world) = aliased_world {
+            return Err(Diagnostic::error(
+                system.location.clone(),
+                format!(
+                    "system `{}` takes resources alongside `{}`, which can also reach them; use `world.resource()` instead",
+                    system.name,
+                    world.name(),
+                ),
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// Orders systems within each group so that every `before`/`after` constraint holds.
+///
+/// Systems without constraints between them keep running in alphabetical order, so adding
+/// a constraint only moves the systems it names.
+///
+/// # Returns
+/// - `Ok(Vec<SystemDef>)` with the systems of each group in execution order.
+/// - `Err(Diagnostic)` if a constraint names a system that is not in the same group, or the
+///   constraints of a group form a cycle.
+pub fn order_systems(systems: Vec<SystemDef>) -> Result<Vec<SystemDef>, Diagnostic> {
+    let mut groups: Vec<(String, Vec<SystemDef>)> = vec![];
+    for system in systems {
+        match groups.iter_mut().find(|(group, _)| *group == system.group) {
+            Some((_, group_systems)) => group_systems.push(system),
+            None => groups.push((system.group.clone(), vec![system])),
+        }
+    }
+
+    let mut ordered = vec![];
+    for (group, mut group_systems) in groups {
+        group_systems.sort_by(|a, b| a.name.cmp(&b.name));
+        ordered.extend(order_group_systems(&group, group_systems)?);
+    }
+
+    Ok(ordered)
+}
+
+/// Topologically sorts the systems of one group, breaking ties alphabetically.
+fn order_group_systems(
+    group: &str,
+    systems: Vec<SystemDef>,
+) -> Result<Vec<SystemDef>, Diagnostic> {
+    let index_of: HashMap<&str, usize> = systems
+        .iter()
+        .enumerate()
+        .map(|(index, system)| (system.name.as_str(), index))
+        .collect();
+
+    let mut successors: Vec<BTreeSet<usize>> = vec![BTreeSet::new(); systems.len()];
+
+    for (index, system) in systems.iter().enumerate() {
+        let constraints = system
+            .before
+            .iter()
+            .map(|name| ("before", name))
+            .chain(system.after.iter().map(|name| ("after", name)));
+
+        for (kind, name) in constraints {
+            let Some(&other) = index_of.get(name.as_str()) else {
+                return Err(Diagnostic::error(
+                    system.location.clone(),
+                    format!(
+                        "system `{}` is ordered {kind} `{name}`, but group `{group}` has no system named `{name}`",
+                        system.name,
+                    ),
+                ));
+            };
+
+            if kind == "before" {
+                successors[index].insert(other);
+            } else {
+                successors[other].insert(index);
+            }
+        }
+    }
+
+    let mut in_degree = vec![0; systems.len()];
+    for &successor in successors.iter().flatten() {
+        in_degree[successor] += 1;
+    }
+
+    // Indices follow alphabetical order, so the smallest ready index is the next system.
+    let mut ready: BTreeSet<usize> = (0..systems.len()).filter(|&i| in_degree[i] == 0).collect();
+    let mut order = vec![];
+
+    while let Some(index) = ready.pop_first() {
+        order.push(index);
+        for &successor in &successors[index] {
+            in_degree[successor] -= 1;
+            if in_degree[successor] == 0 {
+                ready.insert(successor);
+            }
+        }
+    }
+
+    if order.len() < systems.len() {
+        let unordered: Vec<usize> = (0..systems.len()).filter(|&i| in_degree[i] > 0).collect();
+        let cycle = unordered
+            .iter()
+            .map(|&i| format!("`{}`", systems[i].name))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        return Err(Diagnostic::error(
+            systems[unordered[0]].location.clone(),
+            format!(
+                "before/after constraints in group `{group}` form a cycle; could not order {cycle}"
+            ),
+        ));
+    }
+
+    let mut systems: Vec<Option<SystemDef>> = systems.into_iter().map(Some).collect();
+    Ok(order
+        .into_iter()
+        .filter_map(|index| systems[index].take())
+        .collect())
+}
+
 /// Parses a single function parameter and returns the corresponding `SystemDefParam`,
-/// pushing to `queries` if it is a `Query` type.
+/// pushing to `queries` if it is a `Query` type. `&mut Commands` parameters are recognised
+/// separately so the group runner can share and flush a single command buffer, and
+/// `WorldCell` parameters so it can hand out world access to parallel systems.
 ///
 /// # Parameters
 /// - `pat_type`: The typed parameter to inspect.
 /// - `queries`: The list of collected `QueryDef` entries, modified in-place if needed.
+/// - `diagnostics`: The list to which unsupported parameter types are appended.
 ///
 /// # Returns
 /// - `Some(SystemDefParam)` if the parameter is a recognized type.
@@ -126,6 +586,7 @@ fn parse_system_attributes(item_fn: &ItemFn) -> Option<String> {
 fn parse_system_def_param(
     pat_type: &PatType,
     queries: &mut Vec<QueryDef>,
+    diagnostics: &mut Vec<Diagnostic>,
 ) -> Option<SystemDefParam> {
     let param_name = pat_type.pat.to_token_stream().to_string();
 
@@ -137,6 +598,8 @@ fn parse_system_def_param(
                     if let Some(query_param) = parse_query_param(segment, &param_name, queries) {
                         return Some(query_param);
                     }
+                } else if name == "WorldCell" {
+                    return Some(SystemDefParam::WorldCell(param_name));
                 } else {
                     let ty_str = typed_path.to_token_stream().to_string();
                     return Some(SystemDefParam::Value(SystemDefParamValue {
@@ -151,6 +614,10 @@ fn parse_system_def_param(
             let mutable = type_ref.mutability.is_some();
             let ty_str = type_ref.elem.to_token_stream().to_string();
 
+            if mutable && ty_str == "Commands" {
+                return Some(SystemDefParam::Commands(param_name));
+            }
+
             Some(SystemDefParam::Reference(SystemDefParamReference {
                 name: param_name,
                 ty: ty_str,
@@ -158,12 +625,16 @@ fn parse_system_def_param(
             }))
         }
         _ => {
-            panic!("Unsupported param type in system: {param_name}");
+            diagnostics.push(Diagnostic::error(
+                Location::from_span(pat_type.span()),
+                format!("unsupported type for system parameter `{param_name}`"),
+            ));
+            None
         }
     }
 }
 
-/// Parses a `Query<T>` parameter and adds the extracted query to `queries` if valid.
+/// Parses a `Query<T>` or `Query<T, F>` parameter and adds the extracted query to `queries` if valid.
 ///
 /// # Parameters
 /// - `segment`: The `PathSegment` expected to be the `Query`.
@@ -179,12 +650,261 @@ pub fn parse_query_param(
     queries: &mut Vec<QueryDef>,
 ) -> Option<SystemDefParam> {
     if let PathArguments::AngleBracketed(arguments) = &segment.arguments {
-        if let Some(GenericArgument::Type(query_ty)) = arguments.args.iter().next() {
-            if let Some(query) = parse_query_def(query_ty) {
-                queries.push(query);
-                return Some(SystemDefParam::Query(param_name.to_string()));
+        let mut types = arguments.args.iter().filter_map(|argument| match argument {
+            GenericArgument::Type(ty) => Some(ty),
+            _ => None,
+        });
+
+        if let Some(query_ty) = types.next() {
+            if let Some(mut query) = parse_query_def(query_ty, types.next()) {
+                query.location = Location::from_span(segment.span());
+                queries.push(query.clone());
+                return Some(SystemDefParam::Query(SystemDefParamQuery {
+                    name: param_name.to_string(),
+                    query,
+                }));
             }
         }
     }
     None
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn system(name: &str, group: &str, before: &[&str], after: &[&str]) -> SystemDef {
+        SystemDef {
+            name: name.to_string(),
+            group: group.to_string(),
+            before: before.iter().map(|name| name.to_string()).collect(),
+            after: after.iter().map(|name| name.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
+    fn names(systems: &[SystemDef]) -> Vec<&str> {
+        systems.iter().map(|system| system.name.as_str()).collect()
+    }
+
+    #[test]
+    fn unconstrained_systems_run_alphabetically() {
+        let systems = vec![
+            system("c", "update", &[], &[]),
+            system("a", "update", &[], &[]),
+            system("b", "update", &[], &[]),
+        ];
+
+        assert_eq!(names(&order_systems(systems).unwrap()), ["a", "b", "c"]);
+    }
+
+    #[test]
+    fn constraints_only_move_the_systems_they_name() {
+        let systems = vec![
+            system("a", "update", &[], &["d"]),
+            system("b", "update", &[], &[]),
+            system("c", "update", &["b"], &[]),
+            system("d", "update", &[], &[]),
+        ];
+
+        assert_eq!(names(&order_systems(systems).unwrap()), ["c", "b", "d", "a"]);
+    }
+
+    #[test]
+    fn groups_are_ordered_separately() {
+        let systems = vec![
+            system("b", "startup", &[], &[]),
+            system("z", "update", &["y"], &[]),
+            system("a", "startup", &[], &[]),
+            system("y", "update", &[], &[]),
+        ];
+
+        assert_eq!(names(&order_systems(systems).unwrap()), ["a", "b", "z", "y"]);
+    }
+
+    #[test]
+    fn unknown_names_are_errors() {
+        let systems = vec![
+            system("a", "update", &[], &[]),
+            system("b", "startup", &["a"], &[]),
+        ];
+
+        let error = order_systems(systems).unwrap_err();
+        assert!(error.is_error());
+        assert!(error.message.contains("system `b` is ordered before `a`"), "{}", error.message);
+        assert!(error.message.contains("group `startup`"), "{}", error.message);
+    }
+
+    #[test]
+    fn cycles_are_errors() {
+        let systems = vec![
+            system("a", "update", &["b"], &[]),
+            system("b", "update", &["c"], &[]),
+            system("c", "update", &["a"], &[]),
+            system("d", "update", &[], &[]),
+        ];
+
+        let error = order_systems(systems).unwrap_err();
+        assert!(error.message.contains("form a cycle"), "{}", error.message);
+        assert!(error.message.contains("`a`, `b`, `c`"), "{}", error.message);
+        assert!(!error.message.contains("`d`"), "{}", error.message);
+    }
+
+    fn parsed(source: &str) -> SystemDef {
+        let mut systems = vec![];
+        parse_system_def(syn::parse_str(source).unwrap(), &mut systems, &mut vec![], &mut vec![]);
+        systems.pop().unwrap()
+    }
+
+    fn functions(definitions: &[(&str, &str, &[&str])]) -> HashMap<String, FunctionDef> {
+        definitions
+            .iter()
+            .map(|(module, name, params)| {
+                let function = FunctionDef {
+                    name: name.to_string(),
+                    module: module.to_string(),
+                    path: format!("{module}::{name}"),
+                    params: params.iter().map(|param| param.to_string()).collect(),
+                };
+                (function.qualified_name(), function)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn run_conditions_take_the_parameters_they_name() {
+        let mut systems = vec![parsed(
+            "#[system(group=update, run_if=[ready, unpaused])] fn a(world: &World, resources: &GameResources) {}",
+        )];
+        let functions = functions(&[
+            ("crate::conditions", "ready", &["resources"]),
+            ("crate::conditions", "unpaused", &[]),
+        ]);
+
+        resolve_run_conditions(&mut systems, &functions).unwrap();
+
+        let conditions = &systems[0].run_if;
+        assert_eq!(conditions[0].name, "ready");
+        assert_eq!(conditions[0].path, "crate::conditions::ready");
+        assert_eq!(conditions[0].params, ["resources"]);
+        assert_eq!(conditions[1].name, "unpaused");
+        assert!(conditions[1].params.is_empty());
+    }
+
+    #[test]
+    fn run_conditions_prefer_their_own_module() {
+        let mut systems = vec![
+            parsed("#[system(group=update, run_if=ready)] fn a(world: &World) {}"),
+            parsed("#[system(group=update, run_if=ready)] fn b(world: &World) {}"),
+            parsed("#[system(group=update, run_if=conditions::ready)] fn c(world: &World) {}"),
+            parsed("#[system(group=update, run_if=audio::conditions::ready)] fn d(world: &World) {}"),
+        ];
+        systems[0].module = "crate::systems".to_string();
+        systems[1].module = "audio::conditions".to_string();
+        systems[2].module = "crate::systems".to_string();
+        let functions = functions(&[
+            ("crate::systems", "ready", &[]),
+            ("crate::systems::conditions", "ready", &[]),
+            ("audio::conditions", "ready", &[]),
+        ]);
+
+        resolve_run_conditions(&mut systems, &functions).unwrap();
+
+        let paths: Vec<&str> = systems.iter().map(|system| system.run_if[0].path.as_str()).collect();
+        assert_eq!(
+            paths,
+            [
+                "crate::systems::ready",
+                "audio::conditions::ready",
+                "crate::systems::conditions::ready",
+                "audio::conditions::ready",
+            ]
+        );
+    }
+
+    #[test]
+    fn ambiguous_run_conditions_are_errors() {
+        let mut systems = vec![parsed(
+            "#[system(group=update, run_if=ready)] fn a(world: &World) {}",
+        )];
+        systems[0].module = "crate::systems".to_string();
+        let functions = functions(&[("crate::conditions", "ready", &[]), ("audio::conditions", "ready", &[])]);
+
+        let error = resolve_run_conditions(&mut systems, &functions).unwrap_err();
+        assert!(
+            error.message.contains("could be any of `audio::conditions::ready`, `crate::conditions::ready`"),
+            "{}",
+            error.message
+        );
+    }
+
+    #[test]
+    fn unknown_run_conditions_are_errors() {
+        let mut systems = vec![parsed(
+            "#[system(group=update, run_if=ready)] fn a(world: &World) {}",
+        )];
+
+        let error = resolve_run_conditions(&mut systems, &HashMap::new()).unwrap_err();
+        assert!(error.message.contains("no function with that name"), "{}", error.message);
+    }
+
+    #[test]
+    fn run_conditions_only_take_parameters_of_their_system() {
+        let mut systems = vec![parsed(
+            "#[system(group=update, run_if=ready)] fn a(world: &World) {}",
+        )];
+        let functions = functions(&[("crate::conditions", "ready", &["resources"])]);
+
+        let error = resolve_run_conditions(&mut systems, &functions).unwrap_err();
+        assert!(
+            error.message.contains("condition `ready` takes `resources`, which is not a parameter of system `a`"),
+            "{}",
+            error.message
+        );
+    }
+
+    #[test]
+    fn resource_params_may_name_a_path() {
+        let mut systems = vec![parsed(
+            "#[system(group=update)] fn a(camera: &mut crate::resources::ActiveCamera, loads: &ModelLoads) {}",
+        )];
+        let resources = ["ActiveCamera", "ModelLoads"].map(|name| ResourceDef {
+            name: name.to_string(),
+            ..Default::default()
+        });
+
+        resolve_resource_params(&mut systems, &resources).unwrap();
+
+        let SystemDefParam::Resource(camera) = &systems[0].params[0] else {
+            panic!("{:?}", systems[0].params[0]);
+        };
+        assert_eq!(camera.ty, "ActiveCamera");
+        assert!(camera.mutable);
+        assert!(matches!(&systems[0].params[1], SystemDefParam::Resource(loads) if loads.ty == "ModelLoads"));
+    }
+
+    #[test]
+    fn propagated_components_add_a_built_in_system() {
+        let (mut components, mut systems, mut diagnostics) = (vec![], vec![], vec![]);
+        let item_struct = syn::parse_str(
+            "#[component(propagate(group = update, after = transform_update))] struct NodeComponent { global: Mat4 }",
+        )
+        .unwrap();
+
+        crate::parse::parse_component_def(&item_struct, &mut components, &mut systems, &mut diagnostics);
+
+        assert!(diagnostics.is_empty(), "{diagnostics:?}");
+        assert!(components[0].propagate);
+        assert_eq!(systems[0].name, "propagate_node_component");
+        assert_eq!(systems[0].group, "update");
+        assert_eq!(systems[0].after, ["transform_update"]);
+        assert!(matches!(&systems[0].params[..], [SystemDefParam::Reference(world)] if world.ty == "World" && world.mutable));
+
+        let item_struct = syn::parse_str("#[component(propagated)] struct Global(Mat4);").unwrap();
+        crate::parse::parse_component_def(&item_struct, &mut components, &mut systems, &mut diagnostics);
+
+        assert!(!components[1].propagate);
+        assert_eq!(systems.len(), 1);
+        assert!(diagnostics[0].message.contains("unknown argument `propagated`"), "{}", diagnostics[0].message);
+    }
+}
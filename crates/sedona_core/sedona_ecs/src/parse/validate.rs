@@ -0,0 +1,359 @@
+use crate::parse::{CollectedData, Diagnostic, Location, QueryDef};
+use itertools::Itertools;
+use std::collections::HashMap;
+
+/// Checks the definitions collected from every source file against each other.
+///
+/// Reports items defined twice across source roots, entities with more than one component
+/// of the same type, queries and hooks that match no entity type, `Via` joins that can't be
+/// followed safely, and groups whose `systems_<group>` runner is never called.
+pub fn validate_collected(collected: &CollectedData) -> Vec<Diagnostic> {
+    let mut diagnostics = vec![];
+
+    let types = collected
+        .entities
+        .iter()
+        .map(|entity| (&entity.name, &entity.location))
+        .chain(collected.components.iter().map(|component| (&component.name, &component.location)))
+        .chain(collected.resources.iter().map(|resource| (&resource.name, &resource.location)));
+    let functions = collected
+        .systems
+        .iter()
+        .map(|system| (&system.name, &system.location))
+        .chain(collected.hooks.iter().map(|hook| (&hook.name, &hook.location)));
+    diagnostics.extend(duplicate_names(types));
+    diagnostics.extend(duplicate_names(functions));
+
+    for entity in collected.entities.iter() {
+        let duplicates = entity
+            .fields
+            .iter()
+            .into_group_map_by(|field| field.data_type.as_str())
+            .into_iter()
+            .filter(|(_, fields)| fields.len() > 1)
+            .sorted_by_key(|(data_type, _)| *data_type);
+
+        for (data_type, fields) in duplicates {
+            let names = fields.iter().map(|field| format!("`{}`", field.name)).join(", ");
+            diagnostics.push(Diagnostic::error(
+                entity.location.clone(),
+                format!(
+                    "entity `{}` has more than one `{data_type}` component ({names}), so queries can't tell them apart",
+                    entity.name,
+                ),
+            ));
+        }
+    }
+
+    for query in collected.queries.iter() {
+        if !collected.entities.iter().any(|entity| query.matches(entity)) {
+            diagnostics.push(Diagnostic::warning(
+                query.location.clone(),
+                format!("`{}` matches no entity type", describe_query(query)),
+            ));
+        }
+    }
+
+    for query in collected.queries.iter().filter(|query| query.has_via()) {
+        diagnostics.extend(validate_via_query(collected, query));
+    }
+
+    for hook in collected.hooks.iter() {
+        if !collected.entities.iter().any(|entity| hook.applies_to(entity)) {
+            diagnostics.push(Diagnostic::warning(
+                hook.location.clone(),
+                format!(
+                    "hook `{}` is for `{}`, which no entity type is or has",
+                    hook.name,
+                    hook.target.as_deref().unwrap_or_default(),
+                ),
+            ));
+        }
+    }
+
+    let uncalled_groups = collected
+        .systems
+        .iter()
+        .filter(|system| !collected.called_groups.contains(&system.group))
+        .into_group_map_by(|system| system.group.as_str());
+
+    for (group, systems) in uncalled_groups.into_iter().sorted_by_key(|(group, _)| *group) {
+        let names = systems.iter().map(|system| format!("`{}`", system.name)).join(", ");
+        let verb = if systems.len() == 1 { "runs" } else { "run" };
+        diagnostics.push(Diagnostic::warning(
+            systems[0].location.clone(),
+            format!("`systems_{group}` is never called, so {names} never {verb}"),
+        ));
+    }
+
+    diagnostics
+}
+
+/// Reports every name defined more than once, since the generated code refers to items by name.
+///
+/// Definitions from an empty file are the built-in components of the generated world.
+fn duplicate_names<'a>(items: impl Iterator<Item = (&'a String, &'a Location)>) -> Vec<Diagnostic> {
+    let mut diagnostics = vec![];
+    let mut seen: HashMap<&str, &Location> = HashMap::new();
+
+    for (name, location) in items {
+        let Some(first) = seen.get(name.as_str()) else {
+            seen.insert(name, location);
+            continue;
+        };
+
+        let diagnostic = if location.file.is_empty() || first.file.is_empty() {
+            let user_location = if location.file.is_empty() { *first } else { location };
+            Diagnostic::error(
+                user_location.clone(),
+                format!("`{name}` is a built-in component of the generated world"),
+            )
+        } else {
+            Diagnostic::error(
+                location.clone(),
+                format!("`{name}` is also defined at {first}, and the generated code can't tell them apart"),
+            )
+        };
+        diagnostics.push(diagnostic);
+    }
+
+    diagnostics
+}
+
+/// Checks that the joins of a query follow `Option<Entity>` references, and that nothing the
+/// query borrows mutably is borrowed elsewhere in it, since a join may lead back to the row.
+fn validate_via_query(collected: &CollectedData, query: &QueryDef) -> Vec<Diagnostic> {
+    let mut diagnostics = vec![];
+
+    for reference in query.fields.iter().filter_map(|field| field.via.as_deref()) {
+        let follows_entity = collected.components.iter().any(|component| {
+            component.name == reference
+                && matches!(component.fields.as_slice(), [field] if field.data_type == "Option < Entity >")
+        });
+
+        if !follows_entity {
+            diagnostics.push(Diagnostic::error(
+                query.location.clone(),
+                format!(
+                    "`{}` joins through `{reference}`, which must be a component like `struct {reference}(pub Option<Entity>)`",
+                    describe_query(query),
+                ),
+            ));
+        }
+    }
+
+    let borrows = query
+        .fields
+        .iter()
+        .flat_map(|field| {
+            let reference = field.via.as_deref().map(|via| (via, false));
+            std::iter::once((field.data_type.as_str(), field.mutable)).chain(reference)
+        })
+        .chain(
+            query
+                .filters
+                .iter()
+                .filter(|filter| filter.is_tick_filter())
+                .map(|filter| (filter.component(), false)),
+        )
+        .into_group_map();
+
+    for (component, borrows) in borrows.into_iter().sorted_by_key(|(component, _)| *component) {
+        if borrows.len() > 1 && borrows.iter().any(|mutable| *mutable) {
+            diagnostics.push(Diagnostic::error(
+                query.location.clone(),
+                format!(
+                    "`{}` borrows `{component}` mutably and elsewhere in the query, which a `Via` join could alias",
+                    describe_query(query),
+                ),
+            ));
+        }
+    }
+
+    diagnostics
+}
+
+/// Formats a query the way it is written in source, e.g. `Query<(&A, Option<&B>), With<C>>`.
+fn describe_query(query: &QueryDef) -> String {
+    let fields: Vec<String> = query
+        .fields
+        .iter()
+        .map(|field| {
+            let reference = if field.mutable {
+                format!("&mut {}", field.data_type)
+            } else {
+                format!("&{}", field.data_type)
+            };
+
+            if let Some(via) = &field.via {
+                format!("Via<{via}, {reference}>")
+            } else if field.optional {
+                format!("Option<{reference}>")
+            } else {
+                reference
+            }
+        })
+        .collect();
+
+    let data = match fields.as_slice() {
+        [field] => field.clone(),
+        _ => format!("({})", fields.join(", ")),
+    };
+
+    if query.filters.is_empty() {
+        return format!("Query<{data}>");
+    }
+
+    let filters: Vec<String> = query
+        .filters
+        .iter()
+        .map(|filter| format!("{}<{}>", filter.name(), filter.component()))
+        .collect();
+
+    match filters.as_slice() {
+        [filter] => format!("Query<{data}, {filter}>"),
+        _ => format!("Query<{data}, ({})>", filters.join(", ")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::{DiagnosticLevel, collect_ecs_defs_from_source};
+
+    const COMPONENTS: &str = "
+        #[component]
+        struct Position { x: f32 }
+
+        #[component]
+        struct Velocity { x: f32 }
+    ";
+
+    fn collect(source: &str) -> CollectedData {
+        let mut collected = collect_ecs_defs_from_source("src/test.rs", &format!("{COMPONENTS}{source}"));
+        collected.retain_unique_queries();
+        collected.called_groups.insert("update".to_string());
+        collected
+    }
+
+    fn messages(diagnostics: &[Diagnostic], level: DiagnosticLevel) -> Vec<String> {
+        diagnostics
+            .iter()
+            .filter(|diagnostic| diagnostic.level == level)
+            .map(|diagnostic| format!("{}: {}", diagnostic.location, diagnostic.message))
+            .collect()
+    }
+
+    #[test]
+    fn duplicate_component_types_are_errors() {
+        let collected = collect(
+            "
+            #[entity]
+            struct Body { position: Position, previous: Position, velocity: Velocity }
+            ",
+        );
+
+        assert_eq!(
+            messages(&validate_collected(&collected), DiagnosticLevel::Error),
+            ["src/test.rs:9: entity `Body` has more than one `Position` component (`position`, `previous`), so queries can't tell them apart"],
+        );
+    }
+
+    #[test]
+    fn duplicate_definitions_are_errors() {
+        let collected = collect(
+            "
+            #[component]
+            struct Position { y: f32 }
+            ",
+        );
+
+        assert_eq!(
+            messages(&validate_collected(&collected), DiagnosticLevel::Error),
+            ["src/test.rs:9: `Position` is also defined at src/test.rs:3, and the generated code can't tell them apart"],
+        );
+    }
+
+    #[test]
+    fn unmatched_queries_are_warnings() {
+        let collected = collect(
+            "
+            #[entity]
+            struct Body { position: Position }
+
+            #[system(group=update)]
+            fn fall(bodies: Query<&mut Position>, moving: Query<(&Position, &Velocity)>) {}
+            ",
+        );
+
+        assert_eq!(
+            messages(&validate_collected(&collected), DiagnosticLevel::Warning),
+            ["src/test.rs:12: `Query<(&Position, &Velocity)>` matches no entity type"],
+        );
+    }
+
+    #[test]
+    fn uncalled_groups_are_warnings() {
+        let collected = collect(
+            "
+            #[system(group=late)]
+            fn a() {}
+
+            #[system(group=late)]
+            fn b() {}
+            ",
+        );
+
+        assert_eq!(
+            messages(&validate_collected(&collected), DiagnosticLevel::Warning),
+            ["src/test.rs:9: `systems_late` is never called, so `a`, `b` never run"],
+        );
+    }
+
+    #[test]
+    fn unknown_attribute_keys_are_warnings() {
+        let collected = collect(
+            "
+            #[system(group=update, stage=late)]
+            fn a() {}
+            ",
+        );
+
+        assert_eq!(
+            messages(&collected.diagnostics, DiagnosticLevel::Warning),
+            ["src/test.rs:8: unknown key `stage` in #[system] attribute, expected `group`, `before`, `after` or `run_if`"],
+        );
+        assert_eq!(collected.systems[0].group, "update");
+    }
+
+    #[test]
+    fn malformed_attributes_are_errors() {
+        let collected = collect(
+            "
+            #[system(group)]
+            fn a() {}
+
+            #[system = update]
+            fn b() {}
+            ",
+        );
+
+        let errors = messages(&collected.diagnostics, DiagnosticLevel::Error);
+        assert_eq!(errors.len(), 2, "{errors:?}");
+        assert!(errors[0].starts_with("src/test.rs:8: malformed #[system] attribute"), "{}", errors[0]);
+        assert_eq!(
+            errors[1],
+            "src/test.rs:11: unsupported #[system = ...] attribute, use #[system(group = ...)]",
+        );
+    }
+
+    #[test]
+    fn unparsable_files_are_errors() {
+        let collected = collect("fn a( {}");
+
+        let errors = messages(&collected.diagnostics, DiagnosticLevel::Error);
+        assert_eq!(errors.len(), 1, "{errors:?}");
+        assert!(errors[0].starts_with("src/test.rs:"), "{}", errors[0]);
+        assert!(errors[0].contains("failed to parse file"), "{}", errors[0]);
+    }
+}
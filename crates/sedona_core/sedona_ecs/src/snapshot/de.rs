@@ -0,0 +1,372 @@
+use crate::snapshot::SnapshotError;
+use serde::de::{
+    DeserializeOwned, DeserializeSeed, Deserializer, EnumAccess, IntoDeserializer, MapAccess,
+    SeqAccess, VariantAccess, Visitor,
+};
+
+/// Reads values written by `BinarySerializer`.
+pub struct BinaryDeserializer<'de> {
+    input: &'de [u8],
+}
+
+impl<'de> BinaryDeserializer<'de> {
+    pub fn new(input: &'de [u8]) -> Self {
+        Self { input }
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'de [u8], SnapshotError> {
+        if self.input.len() < len {
+            return Err(SnapshotError::UnexpectedEnd);
+        }
+
+        let (bytes, rest) = self.input.split_at(len);
+        self.input = rest;
+        Ok(bytes)
+    }
+
+    fn take_array<const N: usize>(&mut self) -> Result<[u8; N], SnapshotError> {
+        Ok(self.take(N)?.try_into().unwrap())
+    }
+
+    fn read_len(&mut self) -> Result<usize, SnapshotError> {
+        let len = u64::from_le_bytes(self.take_array()?);
+        usize::try_from(len).map_err(|_| SnapshotError::UnexpectedEnd)
+    }
+
+    fn read_u32(&mut self) -> Result<u32, SnapshotError> {
+        Ok(u32::from_le_bytes(self.take_array()?))
+    }
+
+    fn read_str(&mut self) -> Result<&'de str, SnapshotError> {
+        let len = self.read_len()?;
+        std::str::from_utf8(self.take(len)?).map_err(|_| SnapshotError::InvalidUtf8)
+    }
+}
+
+/// Decodes a value from the binary snapshot layout, rejecting leftover bytes.
+pub fn from_bytes<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, SnapshotError> {
+    let mut deserializer = BinaryDeserializer::new(bytes);
+    let value = T::deserialize(&mut deserializer)?;
+
+    match deserializer.input.len() {
+        0 => Ok(value),
+        count => Err(SnapshotError::TrailingBytes(count)),
+    }
+}
+
+macro_rules! deserialize_le {
+    ($($method:ident: $ty:ty => $visit:ident),* $(,)?) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SnapshotError> {
+                visitor.$visit(<$ty>::from_le_bytes(self.take_array()?))
+            }
+        )*
+    };
+}
+
+impl<'de> Deserializer<'de> for &mut BinaryDeserializer<'de> {
+    type Error = SnapshotError;
+
+    deserialize_le! {
+        deserialize_i8: i8 => visit_i8,
+        deserialize_i16: i16 => visit_i16,
+        deserialize_i32: i32 => visit_i32,
+        deserialize_i64: i64 => visit_i64,
+        deserialize_i128: i128 => visit_i128,
+        deserialize_u8: u8 => visit_u8,
+        deserialize_u16: u16 => visit_u16,
+        deserialize_u32: u32 => visit_u32,
+        deserialize_u64: u64 => visit_u64,
+        deserialize_u128: u128 => visit_u128,
+        deserialize_f32: f32 => visit_f32,
+        deserialize_f64: f64 => visit_f64,
+    }
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+
+    fn deserialize_any<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, SnapshotError> {
+        Err(SnapshotError::Unsupported("self-describing values"))
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, SnapshotError> {
+        Err(SnapshotError::Unsupported("ignored values"))
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SnapshotError> {
+        match self.take(1)?[0] {
+            0 => visitor.visit_bool(false),
+            1 => visitor.visit_bool(true),
+            byte => Err(SnapshotError::InvalidBool(byte)),
+        }
+    }
+
+    fn deserialize_char<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SnapshotError> {
+        let code = self.read_u32()?;
+        visitor.visit_char(char::from_u32(code).ok_or(SnapshotError::InvalidChar(code))?)
+    }
+
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SnapshotError> {
+        visitor.visit_borrowed_str(self.read_str()?)
+    }
+
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SnapshotError> {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_bytes<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SnapshotError> {
+        let len = self.read_len()?;
+        visitor.visit_borrowed_bytes(self.take(len)?)
+    }
+
+    fn deserialize_byte_buf<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SnapshotError> {
+        self.deserialize_bytes(visitor)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SnapshotError> {
+        match self.take(1)?[0] {
+            0 => visitor.visit_none(),
+            1 => visitor.visit_some(self),
+            byte => Err(SnapshotError::InvalidBool(byte)),
+        }
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SnapshotError> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, SnapshotError> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, SnapshotError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SnapshotError> {
+        let remaining = self.read_len()?;
+        visitor.visit_seq(Elements {
+            deserializer: self,
+            remaining,
+        })
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(self, len: usize, visitor: V) -> Result<V::Value, SnapshotError> {
+        visitor.visit_seq(Elements {
+            deserializer: self,
+            remaining: len,
+        })
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, SnapshotError> {
+        self.deserialize_tuple(len, visitor)
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SnapshotError> {
+        let remaining = self.read_len()?;
+        visitor.visit_map(Elements {
+            deserializer: self,
+            remaining,
+        })
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, SnapshotError> {
+        self.deserialize_tuple(fields.len(), visitor)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, SnapshotError> {
+        visitor.visit_enum(self)
+    }
+
+    fn deserialize_identifier<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SnapshotError> {
+        visitor.visit_u32(self.read_u32()?)
+    }
+}
+
+/// The elements of a sequence, tuple, struct or map, read one after another.
+struct Elements<'a, 'de> {
+    deserializer: &'a mut BinaryDeserializer<'de>,
+    remaining: usize,
+}
+
+impl<'de> SeqAccess<'de> for Elements<'_, 'de> {
+    type Error = SnapshotError;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, SnapshotError> {
+        if self.remaining == 0 {
+            return Ok(None);
+        }
+
+        self.remaining -= 1;
+        seed.deserialize(&mut *self.deserializer).map(Some)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.remaining)
+    }
+}
+
+impl<'de> MapAccess<'de> for Elements<'_, 'de> {
+    type Error = SnapshotError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>, SnapshotError> {
+        self.next_element_seed(seed)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, SnapshotError> {
+        seed.deserialize(&mut *self.deserializer)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.remaining)
+    }
+}
+
+impl<'de> EnumAccess<'de> for &mut BinaryDeserializer<'de> {
+    type Error = SnapshotError;
+    type Variant = Self;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(self, seed: V) -> Result<(V::Value, Self), SnapshotError> {
+        let variant_index = self.read_u32()?;
+        let deserializer: serde::de::value::U32Deserializer<SnapshotError> = variant_index.into_deserializer();
+        let value = seed.deserialize(deserializer)?;
+        Ok((value, self))
+    }
+}
+
+impl<'de> VariantAccess<'de> for &mut BinaryDeserializer<'de> {
+    type Error = SnapshotError;
+
+    fn unit_variant(self) -> Result<(), SnapshotError> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value, SnapshotError> {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, len: usize, visitor: V) -> Result<V::Value, SnapshotError> {
+        self.deserialize_tuple(len, visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, SnapshotError> {
+        self.deserialize_tuple(fields.len(), visitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::snapshot::to_bytes;
+    use serde::{Deserialize, Serialize};
+    use std::collections::BTreeMap;
+    use std::fmt::Debug;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    enum Shape {
+        Empty,
+        Circle(f32),
+        Segment(f32, f32),
+        Rect { width: f32, height: f32 },
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Drawing {
+        name: String,
+        layer: Option<u8>,
+        shapes: Vec<Shape>,
+    }
+
+    fn round_trip<T: Serialize + DeserializeOwned + PartialEq + Debug>(value: T) {
+        let bytes = to_bytes(&value).unwrap();
+        assert_eq!(from_bytes::<T>(&bytes).unwrap(), value);
+    }
+
+    #[test]
+    fn primitives_round_trip() {
+        round_trip(true);
+        round_trip(-5i8);
+        round_trip(u16::MAX);
+        round_trip(i64::MIN);
+        round_trip(u128::MAX);
+        round_trip(1.5f32);
+        round_trip(f64::INFINITY);
+        round_trip('é');
+        round_trip(String::from("snapshot"));
+        round_trip(());
+        round_trip(Some(3u32));
+        round_trip(None::<u32>);
+    }
+
+    #[test]
+    fn sequences_round_trip() {
+        round_trip(Vec::<u8>::new());
+        round_trip(vec![String::from("a"), String::from("bc")]);
+        round_trip((1u8, String::from("two"), 3.0f64));
+        round_trip([[1u16, 2], [3, 4]]);
+    }
+
+    #[test]
+    fn maps_round_trip() {
+        round_trip(BTreeMap::<String, u32>::new());
+        round_trip(BTreeMap::from([((1u8, 2u8), vec![3u8]), ((4, 5), vec![])]));
+        round_trip(BTreeMap::from([(String::from("a"), Some(1i32)), (String::from("b"), None)]));
+    }
+
+    #[test]
+    fn enums_and_structs_round_trip() {
+        round_trip(Drawing {
+            name: String::from("shapes"),
+            layer: Some(2),
+            shapes: vec![
+                Shape::Empty,
+                Shape::Circle(1.0),
+                Shape::Segment(0.0, 2.0),
+                Shape::Rect { width: 3.0, height: 4.0 },
+            ],
+        });
+    }
+
+    #[test]
+    fn malformed_input_is_rejected() {
+        assert!(matches!(from_bytes::<bool>(&[2]), Err(SnapshotError::InvalidBool(2))));
+        assert!(matches!(from_bytes::<u32>(&[1, 2]), Err(SnapshotError::UnexpectedEnd)));
+        assert!(matches!(from_bytes::<u8>(&[1, 2]), Err(SnapshotError::TrailingBytes(1))));
+        assert!(matches!(
+            from_bytes::<String>(&[1, 0, 0, 0, 0, 0, 0, 0, 0xff]),
+            Err(SnapshotError::InvalidUtf8)
+        ));
+        assert!(from_bytes::<Shape>(&[9, 0, 0, 0]).is_err());
+    }
+}
@@ -0,0 +1,63 @@
+use std::fmt::{Display, Formatter};
+
+#[derive(Debug)]
+pub enum SnapshotError {
+    /// A serde error raised by the value being encoded or decoded.
+    Message(String),
+    UnexpectedEnd,
+    TrailingBytes(usize),
+    InvalidBool(u8),
+    InvalidChar(u32),
+    InvalidUtf8,
+    /// The value needs a self-describing format, e.g. `deserialize_any` or skipped fields.
+    Unsupported(&'static str),
+    BadMagic,
+    UnsupportedFormat(u16),
+    UnsupportedCompression(u16),
+    ChecksumMismatch { expected: u32, actual: u32 },
+    Io(std::io::Error),
+}
+
+impl Display for SnapshotError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SnapshotError::Message(message) => write!(f, "{message}"),
+            SnapshotError::UnexpectedEnd => write!(f, "snapshot ends unexpectedly"),
+            SnapshotError::TrailingBytes(count) => {
+                write!(f, "snapshot has {count} bytes after its value")
+            }
+            SnapshotError::InvalidBool(byte) => write!(f, "invalid bool {byte}"),
+            SnapshotError::InvalidChar(code) => write!(f, "invalid char {code:#x}"),
+            SnapshotError::InvalidUtf8 => write!(f, "invalid UTF-8 in string"),
+            SnapshotError::Unsupported(what) => {
+                write!(f, "binary snapshots do not support {what}")
+            }
+            SnapshotError::BadMagic => write!(f, "not a snapshot"),
+            SnapshotError::UnsupportedFormat(format) => {
+                write!(f, "unsupported snapshot format {format}")
+            }
+            SnapshotError::UnsupportedCompression(compression) => {
+                write!(f, "unsupported snapshot compression {compression}")
+            }
+            SnapshotError::ChecksumMismatch { expected, actual } => write!(
+                f,
+                "snapshot checksum is {actual:#010x}, expected {expected:#010x}"
+            ),
+            SnapshotError::Io(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl std::error::Error for SnapshotError {}
+
+impl serde::ser::Error for SnapshotError {
+    fn custom<T: Display>(message: T) -> Self {
+        SnapshotError::Message(message.to_string())
+    }
+}
+
+impl serde::de::Error for SnapshotError {
+    fn custom<T: Display>(message: T) -> Self {
+        SnapshotError::Message(message.to_string())
+    }
+}
@@ -0,0 +1,176 @@
+pub mod de;
+pub mod error;
+pub mod ser;
+
+pub use de::*;
+pub use error::*;
+pub use ser::*;
+
+use flate2::Compression as DeflateLevel;
+use flate2::read::DeflateDecoder;
+use flate2::write::DeflateEncoder;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use std::io::{Read, Write};
+
+const MAGIC: &[u8; 4] = b"SDNS";
+const FORMAT: u16 = 1;
+const HEADER_LEN: usize = 24;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Compression {
+    None,
+    #[default]
+    Deflate,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SnapshotHeader {
+    pub compression: Compression,
+    pub version: u32,
+    pub checksum: u32,
+    pub payload_len: u64,
+}
+
+/// Encodes `value` into a snapshot tagged with the data `version`.
+///
+/// A snapshot is a fixed header followed by the payload:
+///
+/// | bytes | field                                          |
+/// |-------|------------------------------------------------|
+/// | 4     | magic `SDNS`                                   |
+/// | 2     | snapshot format                                |
+/// | 2     | compression (0 = none, 1 = deflate)            |
+/// | 4     | version of the stored data, chosen by the user |
+/// | 4     | CRC-32 of the payload as stored                |
+/// | 8     | payload length                                 |
+pub fn write_snapshot<T: Serialize + ?Sized>(
+    value: &T,
+    version: u32,
+    compression: Compression,
+) -> Result<Vec<u8>, SnapshotError> {
+    let encoded = to_bytes(value)?;
+
+    let payload = match compression {
+        Compression::None => encoded,
+        Compression::Deflate => {
+            let mut encoder = DeflateEncoder::new(vec![], DeflateLevel::fast());
+            encoder.write_all(&encoded).map_err(SnapshotError::Io)?;
+            encoder.finish().map_err(SnapshotError::Io)?
+        }
+    };
+
+    let compression_tag: u16 = match compression {
+        Compression::None => 0,
+        Compression::Deflate => 1,
+    };
+
+    let mut bytes = Vec::with_capacity(HEADER_LEN + payload.len());
+    bytes.extend_from_slice(MAGIC);
+    bytes.extend(FORMAT.to_le_bytes());
+    bytes.extend(compression_tag.to_le_bytes());
+    bytes.extend(version.to_le_bytes());
+    bytes.extend(crc32fast::hash(&payload).to_le_bytes());
+    bytes.extend((payload.len() as u64).to_le_bytes());
+    bytes.extend(payload);
+
+    Ok(bytes)
+}
+
+/// Reads the header of a snapshot without decoding its payload.
+pub fn read_snapshot_header(bytes: &[u8]) -> Result<SnapshotHeader, SnapshotError> {
+    let header = bytes.get(..HEADER_LEN).ok_or(SnapshotError::UnexpectedEnd)?;
+    if &header[0..4] != MAGIC {
+        return Err(SnapshotError::BadMagic);
+    }
+
+    let u16_at = |at: usize| u16::from_le_bytes(header[at..at + 2].try_into().unwrap());
+    let u32_at = |at: usize| u32::from_le_bytes(header[at..at + 4].try_into().unwrap());
+
+    let format = u16_at(4);
+    if format != FORMAT {
+        return Err(SnapshotError::UnsupportedFormat(format));
+    }
+
+    let compression = match u16_at(6) {
+        0 => Compression::None,
+        1 => Compression::Deflate,
+        compression => return Err(SnapshotError::UnsupportedCompression(compression)),
+    };
+
+    Ok(SnapshotHeader {
+        compression,
+        version: u32_at(8),
+        checksum: u32_at(12),
+        payload_len: u64::from_le_bytes(header[16..24].try_into().unwrap()),
+    })
+}
+
+/// Decodes the value of a snapshot, after checking its payload against the checksum.
+pub fn read_snapshot<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, SnapshotError> {
+    let header = read_snapshot_header(bytes)?;
+
+    let payload = usize::try_from(header.payload_len)
+        .ok()
+        .and_then(|len| bytes.get(HEADER_LEN..HEADER_LEN.checked_add(len)?))
+        .ok_or(SnapshotError::UnexpectedEnd)?;
+
+    let actual = crc32fast::hash(payload);
+    if actual != header.checksum {
+        return Err(SnapshotError::ChecksumMismatch {
+            expected: header.checksum,
+            actual,
+        });
+    }
+
+    match header.compression {
+        Compression::None => from_bytes(payload),
+        Compression::Deflate => {
+            let mut encoded = vec![];
+            DeflateDecoder::new(payload)
+                .read_to_end(&mut encoded)
+                .map_err(SnapshotError::Io)?;
+            from_bytes(&encoded)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn snapshots_round_trip() {
+        let value = (String::from("sedona"), vec![1u32, 2, 3]);
+
+        for compression in [Compression::None, Compression::Deflate] {
+            let bytes = write_snapshot(&value, 7, compression).unwrap();
+            let header = read_snapshot_header(&bytes).unwrap();
+
+            assert_eq!((header.compression, header.version), (compression, 7));
+            assert_eq!(read_snapshot::<(String, Vec<u32>)>(&bytes).unwrap(), value);
+        }
+    }
+
+    #[test]
+    fn unknown_compressions_are_errors() {
+        let mut bytes = write_snapshot(&1u8, 1, Compression::None).unwrap();
+        bytes[6..8].copy_from_slice(&9u16.to_le_bytes());
+
+        assert!(matches!(
+            read_snapshot_header(&bytes),
+            Err(SnapshotError::UnsupportedCompression(9))
+        ));
+    }
+
+    #[test]
+    fn corrupted_payloads_are_errors() {
+        let mut bytes = write_snapshot(&1u64, 1, Compression::None).unwrap();
+        *bytes.last_mut().unwrap() ^= 1;
+
+        assert!(matches!(
+            read_snapshot::<u64>(&bytes),
+            Err(SnapshotError::ChecksumMismatch { .. })
+        ));
+    }
+}
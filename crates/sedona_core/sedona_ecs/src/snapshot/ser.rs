@@ -0,0 +1,317 @@
+use crate::snapshot::SnapshotError;
+use serde::ser::{
+    Serialize, SerializeMap, SerializeSeq, SerializeStruct, SerializeStructVariant,
+    SerializeTuple, SerializeTupleStruct, SerializeTupleVariant, Serializer,
+};
+
+/// Writes values in declaration order without field names or type tags.
+///
+/// Numbers are little-endian, lengths are `u64` and enum variants are their `u32` index,
+/// so the reader must know the exact type that was written.
+pub struct BinarySerializer {
+    pub output: Vec<u8>,
+}
+
+impl BinarySerializer {
+    fn write_len(&mut self, len: usize) {
+        self.output.extend((len as u64).to_le_bytes());
+    }
+}
+
+/// Encodes `value` into the binary snapshot layout, without framing or compression.
+pub fn to_bytes<T: Serialize + ?Sized>(value: &T) -> Result<Vec<u8>, SnapshotError> {
+    let mut serializer = BinarySerializer { output: vec![] };
+    value.serialize(&mut serializer)?;
+    Ok(serializer.output)
+}
+
+macro_rules! serialize_le {
+    ($($method:ident: $ty:ty),* $(,)?) => {
+        $(
+            fn $method(self, value: $ty) -> Result<(), SnapshotError> {
+                self.output.extend(value.to_le_bytes());
+                Ok(())
+            }
+        )*
+    };
+}
+
+impl Serializer for &mut BinarySerializer {
+    type Ok = ();
+    type Error = SnapshotError;
+    type SerializeSeq = Self;
+    type SerializeTuple = Self;
+    type SerializeTupleStruct = Self;
+    type SerializeTupleVariant = Self;
+    type SerializeMap = Self;
+    type SerializeStruct = Self;
+    type SerializeStructVariant = Self;
+
+    serialize_le! {
+        serialize_i8: i8,
+        serialize_i16: i16,
+        serialize_i32: i32,
+        serialize_i64: i64,
+        serialize_i128: i128,
+        serialize_u8: u8,
+        serialize_u16: u16,
+        serialize_u32: u32,
+        serialize_u64: u64,
+        serialize_u128: u128,
+        serialize_f32: f32,
+        serialize_f64: f64,
+    }
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+
+    fn serialize_bool(self, value: bool) -> Result<(), SnapshotError> {
+        self.output.push(value as u8);
+        Ok(())
+    }
+
+    fn serialize_char(self, value: char) -> Result<(), SnapshotError> {
+        self.serialize_u32(value as u32)
+    }
+
+    fn serialize_str(self, value: &str) -> Result<(), SnapshotError> {
+        self.serialize_bytes(value.as_bytes())
+    }
+
+    fn serialize_bytes(self, value: &[u8]) -> Result<(), SnapshotError> {
+        self.write_len(value.len());
+        self.output.extend_from_slice(value);
+        Ok(())
+    }
+
+    fn serialize_none(self) -> Result<(), SnapshotError> {
+        self.output.push(0);
+        Ok(())
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<(), SnapshotError> {
+        self.output.push(1);
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<(), SnapshotError> {
+        Ok(())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<(), SnapshotError> {
+        Ok(())
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+    ) -> Result<(), SnapshotError> {
+        self.serialize_u32(variant_index)
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<(), SnapshotError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+        value: &T,
+    ) -> Result<(), SnapshotError> {
+        self.serialize_u32(variant_index)?;
+        value.serialize(self)
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self, SnapshotError> {
+        let len = len.ok_or(SnapshotError::Unsupported("sequences of unknown length"))?;
+        self.write_len(len);
+        Ok(self)
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self, SnapshotError> {
+        Ok(self)
+    }
+
+    fn serialize_tuple_struct(self, _name: &'static str, _len: usize) -> Result<Self, SnapshotError> {
+        Ok(self)
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self, SnapshotError> {
+        self.serialize_u32(variant_index)?;
+        Ok(self)
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<Self, SnapshotError> {
+        let len = len.ok_or(SnapshotError::Unsupported("maps of unknown length"))?;
+        self.write_len(len);
+        Ok(self)
+    }
+
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Self, SnapshotError> {
+        Ok(self)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self, SnapshotError> {
+        self.serialize_u32(variant_index)?;
+        Ok(self)
+    }
+}
+
+impl SerializeSeq for &mut BinarySerializer {
+    type Ok = ();
+    type Error = SnapshotError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SnapshotError> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), SnapshotError> {
+        Ok(())
+    }
+}
+
+impl SerializeTuple for &mut BinarySerializer {
+    type Ok = ();
+    type Error = SnapshotError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SnapshotError> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), SnapshotError> {
+        Ok(())
+    }
+}
+
+impl SerializeTupleStruct for &mut BinarySerializer {
+    type Ok = ();
+    type Error = SnapshotError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SnapshotError> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), SnapshotError> {
+        Ok(())
+    }
+}
+
+impl SerializeTupleVariant for &mut BinarySerializer {
+    type Ok = ();
+    type Error = SnapshotError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SnapshotError> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), SnapshotError> {
+        Ok(())
+    }
+}
+
+impl SerializeMap for &mut BinarySerializer {
+    type Ok = ();
+    type Error = SnapshotError;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), SnapshotError> {
+        key.serialize(&mut **self)
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SnapshotError> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), SnapshotError> {
+        Ok(())
+    }
+}
+
+impl SerializeStruct for &mut BinarySerializer {
+    type Ok = ();
+    type Error = SnapshotError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        _key: &'static str,
+        value: &T,
+    ) -> Result<(), SnapshotError> {
+        value.serialize(&mut **self)
+    }
+
+    fn skip_field(&mut self, _key: &'static str) -> Result<(), SnapshotError> {
+        Err(SnapshotError::Unsupported("conditionally skipped fields"))
+    }
+
+    fn end(self) -> Result<(), SnapshotError> {
+        Ok(())
+    }
+}
+
+impl SerializeStructVariant for &mut BinarySerializer {
+    type Ok = ();
+    type Error = SnapshotError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        _key: &'static str,
+        value: &T,
+    ) -> Result<(), SnapshotError> {
+        value.serialize(&mut **self)
+    }
+
+    fn skip_field(&mut self, _key: &'static str) -> Result<(), SnapshotError> {
+        Err(SnapshotError::Unsupported("conditionally skipped fields"))
+    }
+
+    fn end(self) -> Result<(), SnapshotError> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn numbers_are_little_endian() {
+        assert_eq!(to_bytes(&0x0102u16).unwrap(), [0x02, 0x01]);
+        assert_eq!(to_bytes(&-2i32).unwrap(), [0xfe, 0xff, 0xff, 0xff]);
+        assert_eq!(to_bytes(&1.0f32).unwrap(), 1.0f32.to_le_bytes());
+        assert_eq!(to_bytes(&true).unwrap(), [1]);
+    }
+
+    #[test]
+    fn lengths_prefix_strings_and_sequences() {
+        assert_eq!(to_bytes("ab").unwrap(), [2, 0, 0, 0, 0, 0, 0, 0, b'a', b'b']);
+        assert_eq!(to_bytes(&vec![7u8]).unwrap(), [1, 0, 0, 0, 0, 0, 0, 0, 7]);
+        assert_eq!(to_bytes(&(7u8, 8u8)).unwrap(), [7, 8]);
+    }
+
+    #[test]
+    fn options_and_variants_are_tagged() {
+        assert_eq!(to_bytes(&None::<u8>).unwrap(), [0]);
+        assert_eq!(to_bytes(&Some(5u8)).unwrap(), [1, 5]);
+        assert_eq!(to_bytes(&Err::<u8, u8>(5)).unwrap(), [1, 0, 0, 0, 5]);
+    }
+}
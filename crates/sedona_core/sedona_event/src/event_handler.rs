@@ -1,8 +1,59 @@
-use crate::Event;
+use crate::{Event, HandlerQueue, ObserverPriority};
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
 
+/// Whether the handlers after this one still see an event.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Propagation {
+    #[default]
+    Continue,
+    /// The event was handled, e.g. a key press swallowed by a menu.
+    Consumed,
+}
+
+/// Identifies a registered handler, so it can be unregistered.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct HandlerId(u64);
+
+impl HandlerId {
+    /// Returns an id no other handler has, so a `HandlerQueue` can hand one out before the
+    /// handler reaches its `EventHandlers`.
+    pub(crate) fn next() -> Self {
+        static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+        HandlerId(NEXT_ID.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+pub(crate) type BoxedHandler<E, W, R> = Box<dyn FnMut(&E, &mut W, &mut R) -> Propagation + Send + Sync>;
+
+/// Boxes a handler that never consumes its event.
+pub(crate) fn continuing<E, W, R, H>(mut handler: H) -> BoxedHandler<E, W, R>
+where
+    H: FnMut(&E, &mut W, &mut R) + Send + Sync + 'static,
+{
+    Box::new(move |event: &E, world: &mut W, resources: &mut R| {
+        handler(event, world, resources);
+        Propagation::Continue
+    })
+}
+
+struct RegisteredHandler<E, W, R> {
+    id: HandlerId,
+    priority: ObserverPriority,
+    handler: BoxedHandler<E, W, R>,
+}
+
+/// The handlers of every event key, run by `dispatch`.
+///
+/// Handlers are `FnMut`, so closures can keep state between events. The handlers can't be
+/// borrowed while they run; a handler or system registers and unregisters through a
+/// `HandlerQueue` instead, which `apply` empties.
+///
+/// The handlers themselves are private, since their order and ids must stay consistent;
+/// `keys` and `handler_count` tell which keys have any.
 pub struct EventHandlers<E: Event, W, R> {
-    pub handlers: HashMap<E::Key, Vec<fn(&E, &mut W, &mut R)>>,
+    /// Sorted by priority, keeping registration order within a priority.
+    handlers: HashMap<E::Key, Vec<RegisteredHandler<E, W, R>>>,
 }
 
 impl<E: Event, W, R> EventHandlers<E, W, R> {
@@ -12,18 +63,118 @@ impl<E: Event, W, R> EventHandlers<E, W, R> {
         }
     }
 
-    pub fn register(&mut self, key: E::Key, handler: fn(&E, &mut W, &mut R)) {
-        self.handlers.entry(key).or_default().push(handler);
+    /// Registers a handler with `ObserverPriority::During`.
+    pub fn register<H>(&mut self, key: E::Key, handler: H) -> HandlerId
+    where
+        H: FnMut(&E, &mut W, &mut R) + Send + Sync + 'static,
+    {
+        self.register_with_priority(key, ObserverPriority::During, handler)
+    }
+
+    pub fn register_with_priority<H>(
+        &mut self,
+        key: E::Key,
+        priority: ObserverPriority,
+        handler: H,
+    ) -> HandlerId
+    where
+        H: FnMut(&E, &mut W, &mut R) + Send + Sync + 'static,
+    {
+        let id = HandlerId::next();
+        self.insert(id, key, priority, continuing(handler));
+        id
+    }
+
+    /// Registers a handler that can consume the event, so the handlers after it skip it.
+    pub fn register_consumer<H>(
+        &mut self,
+        key: E::Key,
+        priority: ObserverPriority,
+        handler: H,
+    ) -> HandlerId
+    where
+        H: FnMut(&E, &mut W, &mut R) -> Propagation + Send + Sync + 'static,
+    {
+        let id = HandlerId::next();
+        self.insert(id, key, priority, Box::new(handler));
+        id
+    }
+
+    /// Removes a handler, returning `false` if it was already removed.
+    pub fn unregister(&mut self, id: HandlerId) -> bool {
+        for handlers in self.handlers.values_mut() {
+            if let Some(index) = handlers.iter().position(|registered| registered.id == id) {
+                handlers.remove(index);
+                return true;
+            }
+        }
+
+        false
+    }
+
+    /// Makes the registrations and removals waiting in `queue`, in the order they were
+    /// requested.
+    pub fn apply(&mut self, queue: &mut HandlerQueue<E, W, R>) {
+        for change in queue.drain() {
+            match change {
+                HandlerChange::Register {
+                    id,
+                    key,
+                    priority,
+                    handler,
+                } => self.insert(id, key, priority, handler),
+                HandlerChange::Unregister(id) => {
+                    self.unregister(id);
+                }
+            }
+        }
+    }
+
+    /// Returns the keys with at least one handler.
+    pub fn keys(&self) -> impl Iterator<Item = &E::Key> {
+        self.handlers
+            .iter()
+            .filter(|(_, handlers)| !handlers.is_empty())
+            .map(|(key, _)| key)
     }
 
-    pub fn dispatch(&self, event: E, world: &mut W, resources: &mut R) {
+    /// Returns how many handlers `key` has.
+    pub fn handler_count(&self, key: &E::Key) -> usize {
+        self.handlers.get(key).map_or(0, Vec::len)
+    }
+
+    /// Runs the handlers of the event's key in priority order, until one consumes it.
+    pub fn dispatch(&mut self, event: E, world: &mut W, resources: &mut R) -> Propagation {
         let key = event.key();
 
-        if let Some(handlers) = self.handlers.get(&key) {
-            for handler in handlers {
-                handler(&event, world, resources);
+        if let Some(handlers) = self.handlers.get_mut(&key) {
+            for registered in handlers {
+                if (registered.handler)(&event, world, resources) == Propagation::Consumed {
+                    return Propagation::Consumed;
+                }
             }
         }
+
+        Propagation::Continue
+    }
+
+    fn insert(
+        &mut self,
+        id: HandlerId,
+        key: E::Key,
+        priority: ObserverPriority,
+        handler: BoxedHandler<E, W, R>,
+    ) {
+        let handlers = self.handlers.entry(key).or_default();
+        let index = handlers.partition_point(|registered| registered.priority <= priority);
+        handlers.insert(
+            index,
+            RegisteredHandler {
+                id,
+                priority,
+                handler,
+            },
+        );
     }
 }
 
@@ -32,3 +183,146 @@ impl<E: Event, W, R> Default for EventHandlers<E, W, R> {
         Self::new()
     }
 }
+
+/// A registration or removal waiting in a `HandlerQueue`.
+pub(crate) enum HandlerChange<E: Event, W, R> {
+    Register {
+        id: HandlerId,
+        key: E::Key,
+        priority: ObserverPriority,
+        handler: BoxedHandler<E, W, R>,
+    },
+    Unregister(HandlerId),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct KeyEvent {
+        key: char,
+    }
+
+    impl Event for KeyEvent {
+        type Key = char;
+
+        fn key(&self) -> char {
+            self.key
+        }
+    }
+
+    #[test]
+    fn handlers_run_in_priority_order_until_consumed() {
+        fn log_last(_: &KeyEvent, log: &mut Vec<&'static str>, _: &mut ()) {
+            log.push("last");
+        }
+        fn log_during(_: &KeyEvent, log: &mut Vec<&'static str>, _: &mut ()) {
+            log.push("during");
+        }
+        fn log_during_later(_: &KeyEvent, log: &mut Vec<&'static str>, _: &mut ()) {
+            log.push("during later");
+        }
+        fn swallow_escape(event: &KeyEvent, log: &mut Vec<&'static str>, _: &mut ()) -> Propagation {
+            log.push("menu");
+            if event.key == '\u{1b}' {
+                Propagation::Consumed
+            } else {
+                Propagation::Continue
+            }
+        }
+
+        let mut handlers: EventHandlers<KeyEvent, Vec<&'static str>, ()> = EventHandlers::new();
+        for key in ['p', '\u{1b}'] {
+            handlers.register_with_priority(key, ObserverPriority::Last, log_last);
+            handlers.register(key, log_during);
+            handlers.register(key, log_during_later);
+            handlers.register_consumer(key, ObserverPriority::First, swallow_escape);
+        }
+
+        let mut log = vec![];
+        let propagation = handlers.dispatch(KeyEvent { key: 'p' }, &mut log, &mut ());
+        assert_eq!(propagation, Propagation::Continue);
+        assert_eq!(log, vec!["menu", "during", "during later", "last"]);
+
+        let mut log = vec![];
+        let propagation = handlers.dispatch(KeyEvent { key: '\u{1b}' }, &mut log, &mut ());
+        assert_eq!(propagation, Propagation::Consumed);
+        assert_eq!(log, vec!["menu"]);
+    }
+
+    #[test]
+    fn closures_keep_state_and_can_be_unregistered() {
+        let mut handlers: EventHandlers<KeyEvent, Vec<String>, ()> = EventHandlers::new();
+        let mut photos = 0;
+        handlers.register('p', move |_, log, _| {
+            photos += 1;
+            log.push(format!("photo {photos}"));
+        });
+
+        let mut bindings = vec![];
+        for (key, label) in [('\u{1b}', "close"), ('p', "capture")] {
+            bindings.push(handlers.register_consumer(
+                key,
+                ObserverPriority::First,
+                move |_, log: &mut Vec<String>, _| {
+                    log.push(String::from(label));
+                    Propagation::Consumed
+                },
+            ));
+        }
+
+        let mut log = vec![];
+        handlers.dispatch(KeyEvent { key: 'p' }, &mut log, &mut ());
+        assert_eq!(log, vec!["capture"]);
+        assert_eq!(handlers.handler_count(&'p'), 2);
+
+        for id in bindings {
+            assert!(handlers.unregister(id));
+            assert!(!handlers.unregister(id));
+        }
+        assert_eq!(handlers.handler_count(&'\u{1b}'), 0);
+        assert_eq!(handlers.keys().collect::<Vec<_>>(), vec![&'p']);
+
+        let mut log = vec![];
+        handlers.dispatch(KeyEvent { key: 'p' }, &mut log, &mut ());
+        handlers.dispatch(KeyEvent { key: '\u{1b}' }, &mut log, &mut ());
+        handlers.dispatch(KeyEvent { key: 'p' }, &mut log, &mut ());
+        assert_eq!(log, vec!["photo 1", "photo 2"]);
+    }
+
+    struct Resources {
+        handlers: HandlerQueue<KeyEvent, Vec<String>, Resources>,
+        once: Option<HandlerId>,
+    }
+
+    #[test]
+    fn handlers_change_the_handlers_through_a_queue() {
+        let mut handlers: EventHandlers<KeyEvent, Vec<String>, Resources> = EventHandlers::new();
+        let mut resources = Resources {
+            handlers: HandlerQueue::new(),
+            once: None,
+        };
+
+        // Opening a menu binds escape to close it, and the binding removes itself.
+        handlers.register('m', |_, log, resources: &mut Resources| {
+            log.push(String::from("open"));
+            let id = resources.handlers.register('\u{1b}', |_, log, resources: &mut Resources| {
+                log.push(String::from("close"));
+                if let Some(id) = resources.once.take() {
+                    resources.handlers.unregister(id);
+                }
+            });
+            resources.once = Some(id);
+        });
+
+        let mut log = vec![];
+        for key in ['m', '\u{1b}', '\u{1b}'] {
+            handlers.apply(&mut resources.handlers);
+            handlers.dispatch(KeyEvent { key }, &mut log, &mut resources);
+        }
+        handlers.apply(&mut resources.handlers);
+
+        assert_eq!(log, vec!["open", "close"]);
+        assert_eq!(handlers.handler_count(&'\u{1b}'), 0);
+    }
+}
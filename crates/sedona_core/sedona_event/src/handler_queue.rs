@@ -0,0 +1,83 @@
+use crate::event_handler::{continuing, HandlerChange};
+use crate::{Event, HandlerId, ObserverPriority, Propagation};
+
+/// Registrations and removals requested where the `EventHandlers` can't be borrowed, such
+/// as from a handler or a system, made by `EventHandlers::apply`.
+///
+/// Ids are handed out right away, so a handler registered here can be unregistered before
+/// it is applied.
+pub struct HandlerQueue<E: Event, W, R> {
+    changes: Vec<HandlerChange<E, W, R>>,
+}
+
+impl<E: Event, W, R> HandlerQueue<E, W, R> {
+    pub fn new() -> Self {
+        Self { changes: Vec::new() }
+    }
+
+    /// Queues a handler with `ObserverPriority::During`.
+    pub fn register<H>(&mut self, key: E::Key, handler: H) -> HandlerId
+    where
+        H: FnMut(&E, &mut W, &mut R) + Send + Sync + 'static,
+    {
+        self.register_with_priority(key, ObserverPriority::During, handler)
+    }
+
+    pub fn register_with_priority<H>(
+        &mut self,
+        key: E::Key,
+        priority: ObserverPriority,
+        handler: H,
+    ) -> HandlerId
+    where
+        H: FnMut(&E, &mut W, &mut R) + Send + Sync + 'static,
+    {
+        let id = HandlerId::next();
+        self.changes.push(HandlerChange::Register {
+            id,
+            key,
+            priority,
+            handler: continuing(handler),
+        });
+        id
+    }
+
+    /// Queues a handler that can consume the event, so the handlers after it skip it.
+    pub fn register_consumer<H>(
+        &mut self,
+        key: E::Key,
+        priority: ObserverPriority,
+        handler: H,
+    ) -> HandlerId
+    where
+        H: FnMut(&E, &mut W, &mut R) -> Propagation + Send + Sync + 'static,
+    {
+        let id = HandlerId::next();
+        self.changes.push(HandlerChange::Register {
+            id,
+            key,
+            priority,
+            handler: Box::new(handler),
+        });
+        id
+    }
+
+    /// Queues the removal of a handler, whether it is registered yet or still queued.
+    pub fn unregister(&mut self, id: HandlerId) {
+        self.changes.push(HandlerChange::Unregister(id));
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    pub(crate) fn drain(&mut self) -> std::vec::Drain<'_, HandlerChange<E, W, R>> {
+        self.changes.drain(..)
+    }
+}
+
+impl<E: Event, W, R> Default for HandlerQueue<E, W, R> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
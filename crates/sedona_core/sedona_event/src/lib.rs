@@ -3,9 +3,11 @@ extern crate self as sedona_event;
 pub mod event;
 pub mod event_handler;
 pub mod event_queue;
+pub mod handler_queue;
 pub mod observer_priority;
 
 pub use event::*;
 pub use event_handler::*;
 pub use event_queue::*;
+pub use handler_queue::*;
 pub use observer_priority::*;
@@ -1,6 +1,11 @@
+/// When a handler runs relative to the other handlers of the same event.
+///
+/// Handlers run from `First` to `Last`, and in registration order within a priority.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum ObserverPriority {
     First,
     Before,
+    #[default]
     During,
     After,
     Last,
@@ -198,6 +198,12 @@ impl RenderMaterials {
         hash
     }
 
+    pub fn remove_material(&mut self, index: usize) -> Result<RenderMaterial, RendererError> {
+        self.materials
+            .try_remove(index)
+            .ok_or(RendererError::InvalidMaterialIndex { index })
+    }
+
     pub fn get_material(&self, index: usize) -> Result<&RenderMaterial, RendererError> {
         self.materials
             .get(index)
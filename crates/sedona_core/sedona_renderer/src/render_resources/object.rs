@@ -39,15 +39,22 @@ impl RenderObjects {
         self.nodes.insert(node)
     }
 
-    pub fn remove_object(&mut self, index: usize) {
-        self.objects.remove(index);
+    pub fn remove_object(&mut self, index: usize) -> Result<RenderObject, RendererError> {
+        let object = self
+            .objects
+            .try_remove(index)
+            .ok_or(RendererError::InvalidObjectIndex { index })?;
 
         self.opaque_objects.retain(|&i| i != index);
         self.translucent_objects.retain(|&i| i != index);
+
+        Ok(object)
     }
 
-    pub fn remove_node(&mut self, index: usize) {
-        self.nodes.remove(index);
+    pub fn remove_node(&mut self, index: usize) -> Result<RenderNode, RendererError> {
+        self.nodes
+            .try_remove(index)
+            .ok_or(RendererError::InvalidNodeIndex { index })
     }
 
     pub fn get_object(&self, index: usize) -> Result<&RenderObject, RendererError> {
@@ -163,12 +163,26 @@ impl Renderer {
         let vbo = VertexBufferObject::new(vertices, indices, &self.device.borrow());
         let object = RenderObject::new(vbo, material, node);
 
-        let material = self.resources.materials.get_material(material)?;
+        let material = self.resources.materials.get_material_mut(material)?;
+        material.add_reference();
         let translucent = material.shader_key.flags.contains(ShaderFlags::BLEND_ALPHA);
 
         Ok(self.resources.objects.insert_object(object, translucent))
     }
 
+    /// Removes a render object, and its material once no other object uses it.
+    pub fn remove_render_object(&mut self, index: usize) -> Result<(), RendererError> {
+        let object = self.resources.objects.remove_object(index)?;
+
+        let material = self.resources.materials.get_material_mut(object.material)?;
+        material.remove_reference();
+        if !material.has_references() {
+            self.resources.materials.remove_material(object.material)?;
+        }
+
+        Ok(())
+    }
+
     pub fn create_render_material(
         &mut self,
         uniform_data: MaterialUniforms,
@@ -191,6 +205,11 @@ impl Renderer {
         self.resources.objects.insert_node(node)
     }
 
+    /// Removes a render node. The objects drawn with it must be removed first.
+    pub fn remove_render_node(&mut self, index: usize) -> Result<(), RendererError> {
+        self.resources.objects.remove_node(index).map(drop)
+    }
+
     pub fn create_material_texture(&mut self, pixels: impl Pixels) -> u64 {
         self.resources
             .materials
@@ -1,12 +1,13 @@
 use glam::{Quat, Vec2, Vec3, Vec4};
 use sedona_utils::time::Time;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use uuid::Uuid;
 
 pub type Array = Vec<SettingsValue>;
 pub type Table = HashMap<String, SettingsValue>;
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum SettingsValue {
     Array(Array),
     Bool(bool),